@@ -1,9 +1,18 @@
-use std::collections::HashMap;
+//! hana.toml parsing and path resolution.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use crate::helper::expand_path_glob::expand_path_glob;
+use crate::helper::glob_match::glob_match;
+
+pub use crate::helper::expand_env_refs::EnvSyntax;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ConfigError {
     /// Cannot read config file
     ReadFile {
@@ -12,8 +21,14 @@ pub enum ConfigError {
     },
     /// TOML parse error
     Parse { message: String },
+    /// `.agents/.hanaprofile` names a profile that hana.toml does not define
+    UnknownProfile { name: String },
+    /// The global level was asked for but there is no home directory
+    NoHomeDir,
 }
 
+impl std::error::Error for ConfigError {}
+
 impl std::fmt::Display for ConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -21,25 +36,40 @@ impl std::fmt::Display for ConfigError {
                 write!(f, "cannot read config file ({}): {source}", path.display())
             }
             Self::Parse { message } => write!(f, "TOML parse error: {message}"),
+            Self::UnknownProfile { name } => write!(
+                f,
+                "active profile '{name}' is not defined in hana.toml. Run `hana use --clear` or pick another profile."
+            ),
+            Self::NoHomeDir => write!(f, "could not determine home directory"),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// An agent hana syncs to: a built-in one, or any other `[target.<name>]` in hana.toml.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum AgentName {
     Claude,
     Codex,
     Pi,
     Opencode,
+    Custom(CustomAgent),
 }
 
+/// Name of a custom agent, declared with `[target.<name>] custom = true`. Never spells a
+/// built-in agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CustomAgent(&'static str);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum TargetFeature {
     Skills,
     Instructions,
 }
 
 impl AgentName {
+    /// Built-in agents, in display order.
     pub const ALL: [AgentName; 4] = [
         AgentName::Claude,
         AgentName::Codex,
@@ -47,32 +77,135 @@ impl AgentName {
         AgentName::Opencode,
     ];
 
+    /// The built-in agent called `name`, or a custom one. Only config parsing creates custom
+    /// agents, so the interned names are bounded by what hana.toml declares.
+    pub(crate) fn new(name: &str) -> Self {
+        Self::builtin(name).unwrap_or_else(|| AgentName::Custom(CustomAgent(intern(name))))
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|agent| agent.as_str() == name)
+    }
+
     pub fn as_str(self) -> &'static str {
         match self {
             AgentName::Claude => "claude",
             AgentName::Codex => "codex",
             AgentName::Pi => "pi",
             AgentName::Opencode => "opencode",
+            AgentName::Custom(CustomAgent(name)) => name,
         }
     }
 
+    /// Built-in agents only; [`Config::agent_names`] adds custom ones.
     pub fn iter() -> impl Iterator<Item = AgentName> {
         Self::ALL.into_iter()
     }
 }
 
+/// Custom agent names live for the whole process so `AgentName` stays `Copy`. Each distinct
+/// name is stored once, however often hana.toml is loaded.
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut names = NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(known) = names.get(name) {
+        return known;
+    }
+    let leaked: &'static str = Box::leak(name.to_string().into_boxed_str());
+    names.insert(leaked);
+    leaked
+}
+
 impl fmt::Display for AgentName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
 
+/// How managed symlinks point at the source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LinkStyle {
+    /// `../../.agents/skills/my-skill` (survives moving the project)
+    #[default]
+    Relative,
+    /// `/home/me/project/.agents/skills/my-skill`
+    Absolute,
+}
+
+impl LinkStyle {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LinkStyle::Relative => "relative",
+            LinkStyle::Absolute => "absolute",
+        }
+    }
+}
+
+/// How agent instruction files are produced from the source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InstructionMode {
+    /// Symlink to the source (byte-identical)
+    #[default]
+    Link,
+    /// Render from the source plus `.agents/instructions/<agent>.md`
+    Generate,
+}
+
+/// Glob patterns that decide which directories count as skills.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct SkillFilter {
+    /// Only names matching one of these (empty = everything)
+    pub include: Vec<String>,
+    /// Names matching one of these are skipped
+    pub exclude: Vec<String>,
+}
+
+impl SkillFilter {
+    /// Hidden directories (`.git`, `.DS_Store`, …) are never skills.
+    pub fn matches(&self, name: &str) -> bool {
+        if name.starts_with('.') {
+            return false;
+        }
+        let included = self.include.is_empty() || self.include.iter().any(|p| glob_match(p, name));
+        included && !self.exclude.iter().any(|p| glob_match(p, name))
+    }
+}
+
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct SourceConfig {
     pub skills_path: String,
     pub skills_path_global: String,
     pub instruction_path: String,
     pub instruction_path_global: String,
+    pub filter: SkillFilter,
+    /// Group skills into category directories (`<category>/<skill>/SKILL.md`)
+    pub namespaces: bool,
+    /// Joins category and skill names in flattened agent links (`frontend--react-review`)
+    pub namespace_separator: String,
+    pub link_style: LinkStyle,
+    pub instruction_mode: InstructionMode,
+    /// Expand `<!-- hana:include path -->` / `@path` for agents without import support
+    pub expand_includes: bool,
+    /// Render subagent definitions (opt-in)
+    pub subagents: bool,
+    /// Canonical subagent definitions (`*.md` with frontmatter)
+    pub subagents_path: String,
+    pub subagents_path_global: String,
+    /// Sync slash commands (opt-in)
+    pub commands: bool,
+    /// Canonical command definitions (`*.md` with frontmatter)
+    pub commands_path: String,
+    pub commands_path_global: String,
+    /// Canonical MCP server definitions (`[servers.<name>]`)
+    pub mcp_path: String,
+    pub mcp_path_global: String,
+    /// Local values for `${env:NAME}` placeholders, for agents that cannot expand them
+    pub env_path: String,
+    pub env_path_global: String,
 }
 
 impl Default for SourceConfig {
@@ -82,11 +215,28 @@ impl Default for SourceConfig {
             skills_path_global: "~/.agents/skills".to_string(),
             instruction_path: "AGENTS.md".to_string(),
             instruction_path_global: "~/.agents/AGENTS.md".to_string(),
+            filter: SkillFilter::default(),
+            namespaces: false,
+            namespace_separator: "--".to_string(),
+            link_style: LinkStyle::default(),
+            instruction_mode: InstructionMode::default(),
+            expand_includes: false,
+            subagents: false,
+            subagents_path: ".agents/agents".to_string(),
+            subagents_path_global: "~/.agents/agents".to_string(),
+            commands: false,
+            commands_path: ".agents/commands".to_string(),
+            commands_path_global: "~/.agents/commands".to_string(),
+            mcp_path: ".agents/mcp.toml".to_string(),
+            mcp_path_global: "~/.agents/mcp.toml".to_string(),
+            env_path: ".agents/.env".to_string(),
+            env_path_global: "~/.agents/.env".to_string(),
         }
     }
 }
 
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct TargetConfig {
     pub skills: bool,
     pub instructions: bool,
@@ -94,55 +244,491 @@ pub struct TargetConfig {
     pub skills_path_global: String,
     pub instruction_path: String,
     pub instruction_path_global: String,
+    pub filter: SkillFilter,
+    /// The agent resolves `@path` imports in its instruction file itself
+    pub imports: bool,
+    /// Extra files linked from their own source (`files = [{ source, dest }]`)
+    pub files: Vec<TargetFile>,
+    pub files_global: Vec<TargetFile>,
+    /// Where and how subagents are rendered (`None`: the agent has no subagents)
+    pub subagents: Option<RenderTarget>,
+    /// Where and how slash commands are rendered when `[source] commands = true`
+    pub commands: Option<RenderTarget>,
+    /// The agent config file MCP servers are merged into (`None`: no MCP support)
+    pub mcp: Option<McpTarget>,
+}
+
+/// How list values (e.g. `tools: [Read, Grep]`) are written in a target's frontmatter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListStyle {
+    /// `tools: Read, Grep`
+    #[default]
+    Csv,
+    /// `tools:` followed by `- Read` lines
+    Yaml,
+    /// `tools:` followed by `Read: true` lines
+    Map,
+}
+
+/// `[target.<agent>.subagents]` / `[target.<agent>.commands]`: destination plus the
+/// frontmatter translation table.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct RenderTarget {
+    /// `None`: the agent has no such directory at this level
+    pub path: Option<String>,
+    pub path_global: Option<String>,
+    /// Canonical key → target key; `None` drops the field. Unlisted keys pass through.
+    pub fields: Vec<(String, Option<String>)>,
+    pub lists: ListStyle,
+    /// Fields added to (or overriding) every rendered definition
+    pub set: Vec<(String, String)>,
+    /// Keys the agent cannot express; a definition using one is reported, not rendered
+    pub unsupported: Vec<String>,
+}
+
+impl RenderTarget {
+    /// Render into `path` (`None`: not at project level) and `path_global`, fields as-is.
+    pub fn new(path: Option<&str>, path_global: &str) -> Self {
+        Self {
+            path: path.map(str::to_string),
+            path_global: Some(path_global.to_string()),
+            fields: vec![],
+            lists: ListStyle::Csv,
+            set: vec![],
+            unsupported: vec![],
+        }
+    }
+
+    fn subagents_for(agent: AgentName) -> Option<Self> {
+        match agent {
+            AgentName::Claude => Some(Self::new(Some(".claude/agents"), ".claude/agents")),
+            AgentName::Opencode => Some(Self {
+                fields: vec![("name".to_string(), None)],
+                lists: ListStyle::Map,
+                set: vec![("mode".to_string(), "subagent".to_string())],
+                ..Self::new(Some(".opencode/agent"), ".config/opencode/agent")
+            }),
+            AgentName::Codex | AgentName::Pi | AgentName::Custom(_) => None,
+        }
+    }
+
+    fn commands_for(agent: AgentName) -> Option<Self> {
+        let unsupported = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect();
+        Some(match agent {
+            AgentName::Custom(_) => return None,
+            AgentName::Claude => Self::new(Some(".claude/commands"), ".claude/commands"),
+            AgentName::Opencode => Self {
+                fields: vec![("argument-hint".to_string(), None)],
+                unsupported: unsupported(&["allowed-tools"]),
+                ..Self::new(Some(".opencode/command"), ".config/opencode/command")
+            },
+            // Codex reads custom prompts from ~/.codex/prompts only
+            AgentName::Codex => Self {
+                unsupported: unsupported(&["allowed-tools", "model"]),
+                ..Self::new(None, ".codex/prompts")
+            },
+            AgentName::Pi => Self {
+                fields: vec![("argument-hint".to_string(), None)],
+                unsupported: unsupported(&["allowed-tools", "model"]),
+                ..Self::new(Some(".pi/prompts"), ".pi/agent/prompts")
+            },
+        })
+    }
+}
+
+/// How an agent's config file stores MCP servers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum McpFormat {
+    /// `.mcp.json` / `~/.claude.json`: `mcpServers`
+    ClaudeJson,
+    /// `opencode.json`: `mcp`, with `local` / `remote` entries
+    OpencodeJson,
+    /// `config.toml`: `[mcp_servers.<name>]`
+    CodexToml,
+}
+
+impl McpFormat {
+    /// How `${env:NAME}` placeholders are written for this agent.
+    pub fn env_syntax(self) -> EnvSyntax {
+        match self {
+            Self::ClaudeJson => EnvSyntax::Dollar,
+            Self::OpencodeJson => EnvSyntax::Braced,
+            Self::CodexToml => EnvSyntax::Resolve,
+        }
+    }
+}
+
+/// `[target.<agent>.mcp]`: the agent config file hana merges MCP servers into.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct McpTarget {
+    pub path: String,
+    pub path_global: String,
+    pub format: McpFormat,
+}
+
+impl McpTarget {
+    pub fn new(path: &str, path_global: &str, format: McpFormat) -> Self {
+        Self {
+            path: path.to_string(),
+            path_global: path_global.to_string(),
+            format,
+        }
+    }
+
+    fn default_for(agent: AgentName) -> Option<Self> {
+        let (path, path_global, format) = match agent {
+            AgentName::Claude => (".mcp.json", ".claude.json", McpFormat::ClaudeJson),
+            AgentName::Codex => (
+                ".codex/config.toml",
+                ".codex/config.toml",
+                McpFormat::CodexToml,
+            ),
+            AgentName::Opencode => (
+                "opencode.json",
+                ".config/opencode/opencode.json",
+                McpFormat::OpencodeJson,
+            ),
+            AgentName::Pi | AgentName::Custom(_) => return None,
+        };
+        Some(Self {
+            path: path.to_string(),
+            path_global: path_global.to_string(),
+            format,
+        })
+    }
+}
+
+/// One `[servers.<name>]` of `.agents/mcp.toml`: a local command or a remote URL.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct McpServer {
+    pub name: String,
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub url: Option<String>,
+    pub headers: Vec<(String, String)>,
+    /// Limit the server to these agents (`None`: every agent with MCP support)
+    pub agents: Option<Vec<AgentName>>,
+    /// `false` removes the server from every agent config
+    pub enabled: bool,
+}
+
+impl McpServer {
+    /// A local server started with `command`, for every agent.
+    pub fn command(name: &str, command: &str) -> Self {
+        Self {
+            command: Some(command.to_string()),
+            ..Self::remote(name, "")
+        }
+    }
+
+    /// A remote server at `url`, for every agent.
+    pub fn remote(name: &str, url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            command: None,
+            args: vec![],
+            env: vec![],
+            url: (!url.is_empty()).then(|| url.to_string()),
+            headers: vec![],
+            agents: None,
+            enabled: true,
+        }
+    }
+
+    /// The same server with `f` applied to every string it would write (command, args, env and
+    /// header values, url).
+    pub fn try_map_strings(
+        &self,
+        f: impl Fn(&str) -> Result<String, String>,
+    ) -> Result<McpServer, String> {
+        let pairs = |pairs: &[(String, String)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), f(v)?)))
+                .collect::<Result<Vec<_>, String>>()
+        };
+        Ok(McpServer {
+            command: self.command.as_deref().map(&f).transpose()?,
+            args: self.args.iter().map(|a| f(a)).collect::<Result<_, _>>()?,
+            env: pairs(&self.env)?,
+            url: self.url.as_deref().map(&f).transpose()?,
+            headers: pairs(&self.headers)?,
+            ..self.clone()
+        })
+    }
+
+    /// Some string holds a `${env:NAME}` placeholder.
+    pub fn has_env_refs(&self) -> bool {
+        self.command
+            .iter()
+            .chain(&self.args)
+            .chain(self.env.iter().map(|(_, v)| v))
+            .chain(&self.url)
+            .chain(self.headers.iter().map(|(_, v)| v))
+            .any(|text| text.contains("${env:"))
+    }
+
+    /// The server should be present in this agent's config.
+    pub fn applies_to(&self, agent: AgentName) -> bool {
+        self.enabled
+            && self
+                .agents
+                .as_ref()
+                .is_none_or(|agents| agents.contains(&agent))
+    }
+}
+
+/// One extra managed file of a target, e.g. Pi's `SYSTEM.md`. Paths are relative to the
+/// config's base directory unless they start with `~/` or `/`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TargetFile {
+    pub source: String,
+    pub dest: String,
+}
+
+impl TargetFile {
+    pub fn new(source: &str, dest: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            dest: dest.to_string(),
+        }
+    }
 }
 
 impl TargetConfig {
+    /// A target with both skills and instructions turned off is not used at all.
+    pub fn is_used(&self) -> bool {
+        self.skills || self.instructions
+    }
+
+    /// Custom agents default to `.<name>/skills` and `AGENTS.md` (`~/.<name>/AGENTS.md`).
     pub fn default_for(agent: AgentName) -> Self {
+        let custom_dir = format!(".{agent}");
         let (skills_path, skills_path_global) = match agent {
-            AgentName::Claude => (".claude/skills", ".claude/skills"),
-            AgentName::Codex => (".agents/skills", ".agents/skills"),
-            AgentName::Pi => (".pi/skills", ".pi/agent/skills"),
-            AgentName::Opencode => (".opencode/skills", ".config/opencode/skills"),
+            AgentName::Claude => (".claude/skills".into(), ".claude/skills".into()),
+            AgentName::Codex => (".agents/skills".into(), ".agents/skills".into()),
+            AgentName::Pi => (".pi/skills".into(), ".pi/agent/skills".into()),
+            AgentName::Opencode => (".opencode/skills".into(), ".config/opencode/skills".into()),
+            AgentName::Custom(_) => (
+                format!("{custom_dir}/skills"),
+                format!("{custom_dir}/skills"),
+            ),
         };
 
         let (instruction_path, instruction_path_global) = match agent {
-            AgentName::Claude => ("CLAUDE.md", ".claude/CLAUDE.md"),
-            AgentName::Codex => ("AGENTS.md", ".codex/AGENTS.md"),
-            AgentName::Pi => ("AGENTS.md", ".pi/agent/AGENTS.md"),
-            AgentName::Opencode => ("AGENTS.md", ".config/opencode/AGENTS.md"),
+            AgentName::Claude => ("CLAUDE.md".into(), ".claude/CLAUDE.md".into()),
+            AgentName::Codex => ("AGENTS.md".into(), ".codex/AGENTS.md".into()),
+            AgentName::Pi => ("AGENTS.md".into(), ".pi/agent/AGENTS.md".into()),
+            AgentName::Opencode => ("AGENTS.md".into(), ".config/opencode/AGENTS.md".into()),
+            AgentName::Custom(_) => ("AGENTS.md".into(), format!("{custom_dir}/AGENTS.md")),
         };
 
         Self {
             skills: true,
             instructions: true,
-            skills_path: skills_path.to_string(),
-            skills_path_global: skills_path_global.to_string(),
-            instruction_path: instruction_path.to_string(),
-            instruction_path_global: instruction_path_global.to_string(),
+            skills_path,
+            skills_path_global,
+            instruction_path,
+            instruction_path_global,
+            filter: SkillFilter::default(),
+            imports: agent == AgentName::Claude,
+            files: vec![],
+            files_global: vec![],
+            subagents: RenderTarget::subagents_for(agent),
+            commands: RenderTarget::commands_for(agent),
+            mcp: McpTarget::default_for(agent),
         }
     }
 }
 
+/// Per-skill broadcast rule (`[skill.<name>]` or a `hana:` block in SKILL.md frontmatter).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct SkillRule {
+    /// Agents the skill is broadcast to (`None` = every enabled target)
+    pub targets: Option<Vec<AgentName>>,
+    /// Agents the skill is never broadcast to
+    pub exclude: Vec<AgentName>,
+}
+
+impl SkillRule {
+    pub fn allows(&self, agent: AgentName) -> bool {
+        let included = self
+            .targets
+            .as_ref()
+            .is_none_or(|targets| targets.contains(&agent));
+        included && !self.exclude.contains(&agent)
+    }
+
+    /// Combine two rules: `self` takes precedence for `targets`, excludes are unioned.
+    pub fn merge(&self, fallback: &SkillRule) -> SkillRule {
+        let mut exclude = self.exclude.clone();
+        for agent in &fallback.exclude {
+            if !exclude.contains(agent) {
+                exclude.push(*agent);
+            }
+        }
+        SkillRule {
+            targets: self.targets.clone().or_else(|| fallback.targets.clone()),
+            exclude,
+        }
+    }
+}
+
+/// `[profile.<name>]`: a named skill set and instruction file, switched on with `hana use`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Profile {
+    /// Glob patterns for the skills this profile broadcasts (empty = every skill)
+    pub skills: Vec<String>,
+    /// Instruction source used while the profile is active (`None` = `[source]` path)
+    pub instructions: Option<String>,
+}
+
+/// `[git]` table: how hana-created links relate to version control.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct GitConfig {
+    /// Keep a hana-managed `.gitignore` block listing every managed link
+    pub ignore_links: bool,
+}
+
+/// `[workspace]` table (global config): the repos `hana workspace` visits.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct WorkspaceConfig {
+    /// Repo roots; a `*` or `?` component matches every repo with a `.agents/hana.toml`
+    pub repos: Vec<String>,
+}
+
+/// What a `[[mapping]]` source is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MappingKind {
+    /// A directory whose entries are each linked into the destination directories
+    #[default]
+    Dir,
+    /// A single file linked to each destination path
+    File,
+}
+
+/// Whether real entries found at a mapping's destinations are moved into its source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CollectPolicy {
+    #[default]
+    Auto,
+    Never,
+}
+
+/// `[[mapping]]`: any shared directory or file several agents read from different paths.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Mapping {
+    pub name: String,
+    pub source: String,
+    pub kind: MappingKind,
+    pub collect: CollectPolicy,
+    /// `[mapping.dest]`: agent → path
+    pub dest: Vec<(AgentName, String)>,
+}
+
+impl Mapping {
+    /// A mapping of `source` with no destinations yet; add them to `dest`.
+    pub fn new(name: &str, source: &str, kind: MappingKind) -> Self {
+        Self {
+            name: name.to_string(),
+            source: source.to_string(),
+            kind,
+            collect: CollectPolicy::default(),
+            dest: vec![],
+        }
+    }
+
+    pub fn resolve_source(&self, base_dir: &Path) -> PathBuf {
+        resolve_path(base_dir, &self.source)
+    }
+
+    pub fn resolve_dests(&self, base_dir: &Path) -> HashMap<AgentName, PathBuf> {
+        self.dest
+            .iter()
+            .map(|(agent, path)| (*agent, resolve_path(base_dir, path)))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct Config {
     pub source: SourceConfig,
-    pub targets: HashMap<String, TargetConfig>,
+    pub targets: HashMap<AgentName, TargetConfig>,
+    pub skills: HashMap<String, SkillRule>,
+    pub git: GitConfig,
+    pub workspace: WorkspaceConfig,
+    pub mappings: Vec<Mapping>,
+    pub profiles: BTreeMap<String, Profile>,
+    /// Profile in effect, set by `apply_profile` (never read from hana.toml)
+    pub active_profile: Option<String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         let mut targets = HashMap::new();
         for agent in AgentName::iter() {
-            targets.insert(agent.as_str().to_string(), TargetConfig::default_for(agent));
+            targets.insert(agent, TargetConfig::default_for(agent));
         }
         Self {
             source: SourceConfig::default(),
             targets,
+            skills: HashMap::new(),
+            git: GitConfig::default(),
+            workspace: WorkspaceConfig::default(),
+            mappings: vec![],
+            profiles: BTreeMap::new(),
+            active_profile: None,
         }
     }
 }
 
+/// Base directory of a level: `~` for the global level, else the working directory.
+pub fn resolve_base_dir(global: bool) -> Result<PathBuf, ConfigError> {
+    if global {
+        dirs::home_dir().ok_or(ConfigError::NoHomeDir)
+    } else {
+        Ok(std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+    }
+}
+
+/// `(global, base_dir)` of the levels that have a hana.toml, global first. A project at `~` is
+/// the global level and is not listed twice.
+pub fn existing_levels() -> Result<Vec<(bool, PathBuf)>, ConfigError> {
+    let mut levels: Vec<(bool, PathBuf)> = Vec::new();
+    for global in [true, false] {
+        let base_dir = resolve_base_dir(global)?;
+        if base_dir.join(".agents/hana.toml").exists()
+            && !levels.iter().any(|(_, dir)| *dir == base_dir)
+        {
+            levels.push((global, base_dir));
+        }
+    }
+    Ok(levels)
+}
+
 impl Config {
+    /// `.agents/hana.toml` under `base_dir`, with the profile chosen by `hana use` applied.
+    pub fn load_level(base_dir: &Path) -> Result<Self, ConfigError> {
+        let mut config = Self::load(&base_dir.join(".agents/hana.toml"))?;
+        config.apply_saved_profile(base_dir)?;
+        Ok(config)
+    }
+
     pub fn load(path: &Path) -> Result<Self, ConfigError> {
         let content = fs::read_to_string(path).map_err(|e| ConfigError::ReadFile {
             path: path.to_path_buf(),
@@ -183,10 +769,74 @@ impl Config {
             instruction_path_global: table_get_str(source_table, "instruction_path_global")
                 .or_else(|| legacy_instruction_source.map(str::to_string))
                 .unwrap_or(source_defaults.instruction_path_global),
+            filter: table_get_filter(source_table),
+            namespaces: table_get_bool(source_table, "namespaces")
+                .unwrap_or(source_defaults.namespaces),
+            namespace_separator: table_get_str(source_table, "namespace_separator")
+                .unwrap_or(source_defaults.namespace_separator),
+            link_style: match table_get_str(source_table, "link_style").as_deref() {
+                None => source_defaults.link_style,
+                Some("relative") => LinkStyle::Relative,
+                Some("absolute") => LinkStyle::Absolute,
+                Some(other) => {
+                    return Err(ConfigError::Parse {
+                        message: format!(
+                            "source.link_style must be \"relative\" or \"absolute\", got \"{other}\""
+                        ),
+                    });
+                }
+            },
+            instruction_mode: match table_get_str(source_table, "instruction_mode").as_deref() {
+                None => source_defaults.instruction_mode,
+                Some("link") => InstructionMode::Link,
+                Some("generate") => InstructionMode::Generate,
+                Some(other) => {
+                    return Err(ConfigError::Parse {
+                        message: format!(
+                            "source.instruction_mode must be \"link\" or \"generate\", got \"{other}\""
+                        ),
+                    });
+                }
+            },
+            expand_includes: table_get_bool(source_table, "expand_includes")
+                .unwrap_or(source_defaults.expand_includes),
+            subagents: table_get_bool(source_table, "subagents")
+                .unwrap_or(source_defaults.subagents),
+            subagents_path: table_get_str(source_table, "subagents_path")
+                .unwrap_or(source_defaults.subagents_path),
+            subagents_path_global: table_get_str(source_table, "subagents_path_global")
+                .unwrap_or(source_defaults.subagents_path_global),
+            commands: table_get_bool(source_table, "commands").unwrap_or(source_defaults.commands),
+            commands_path: table_get_str(source_table, "commands_path")
+                .unwrap_or(source_defaults.commands_path),
+            commands_path_global: table_get_str(source_table, "commands_path_global")
+                .unwrap_or(source_defaults.commands_path_global),
+            mcp_path: table_get_str(source_table, "mcp_path").unwrap_or(source_defaults.mcp_path),
+            mcp_path_global: table_get_str(source_table, "mcp_path_global")
+                .unwrap_or(source_defaults.mcp_path_global),
+            env_path: table_get_str(source_table, "env_path").unwrap_or(source_defaults.env_path),
+            env_path_global: table_get_str(source_table, "env_path_global")
+                .unwrap_or(source_defaults.env_path_global),
         };
 
+        // Built-in agents always exist; any other `[target.<name>]` must opt in with
+        // `custom = true`, so a misspelled built-in name is an error
+        let custom_agents: BTreeSet<&str> = ["target", "targets"]
+            .into_iter()
+            .filter_map(|key| table.get(key).and_then(|v| v.as_table()))
+            .flat_map(|targets| targets.iter())
+            .filter(|(name, _)| AgentName::builtin(name).is_none())
+            .map(|(name, value)| match table_get_bool(Some(value), "custom") {
+                Some(true) => Ok(name.as_str()),
+                _ => Err(ConfigError::Parse {
+                    message: format!(
+                        "target.{name}: unknown agent (set `custom = true` to add a custom agent)"
+                    ),
+                }),
+            })
+            .collect::<Result<_, _>>()?;
         let mut targets = HashMap::new();
-        for agent in AgentName::iter() {
+        for agent in AgentName::iter().chain(custom_agents.into_iter().map(AgentName::new)) {
             let name = agent.as_str();
             let target_table = table.get("target").and_then(|v| v.get(name));
             let legacy_target_table = table.get("targets").and_then(|v| v.get(name));
@@ -211,21 +861,140 @@ impl Config {
                 instruction_path_global: table_get_str(target_table, "instruction_path_global")
                     .or_else(|| table_get_str(legacy_target_table, "instruction_path_global"))
                     .unwrap_or(default_target.instruction_path_global),
+                filter: table_get_filter(target_table.or(legacy_target_table)),
+                imports: table_get_bool(target_table, "imports")
+                    .or_else(|| table_get_bool(legacy_target_table, "imports"))
+                    .unwrap_or(default_target.imports),
+                files: table_get_files(target_table.or(legacy_target_table), name, "files")?,
+                files_global: table_get_files(
+                    target_table.or(legacy_target_table),
+                    name,
+                    "files_global",
+                )?,
+                subagents: table_get_render_target(
+                    target_table,
+                    agent,
+                    "subagents",
+                    RenderTarget::subagents_for(agent),
+                )?,
+                commands: table_get_render_target(
+                    target_table,
+                    agent,
+                    "commands",
+                    RenderTarget::commands_for(agent),
+                )?,
+                mcp: table_get_mcp(target_table, agent)?,
             };
-            targets.insert(name.to_string(), target);
+            targets.insert(agent, target);
         }
 
-        Ok(Self { source, targets })
+        let skills = table
+            .get("skill")
+            .and_then(|v| v.as_table())
+            .map(|skill_table| {
+                skill_table
+                    .iter()
+                    .map(|(name, value)| {
+                        let agents = |key: &str| {
+                            table_get_str_list(Some(value), key)
+                                .map(|names| {
+                                    names
+                                        .iter()
+                                        .map(|agent| {
+                                            find_agent(&targets, agent).ok_or_else(|| {
+                                                ConfigError::Parse {
+                                                    message: format!(
+                                                        "skill.{name}: unknown agent \"{agent}\""
+                                                    ),
+                                                }
+                                            })
+                                        })
+                                        .collect::<Result<Vec<_>, _>>()
+                                })
+                                .transpose()
+                        };
+                        let rule = SkillRule {
+                            targets: agents("targets")?,
+                            exclude: agents("exclude")?.unwrap_or_default(),
+                        };
+                        Ok((name.clone(), rule))
+                    })
+                    .collect::<Result<_, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let git = GitConfig {
+            ignore_links: table_get_bool(table.get("git"), "ignore_links").unwrap_or(false),
+        };
+
+        let workspace = WorkspaceConfig {
+            repos: table_get_str_list(table.get("workspace"), "repos").unwrap_or_default(),
+        };
+
+        let mappings = match table.get("mapping") {
+            None => vec![],
+            Some(value) => value
+                .as_array()
+                .ok_or_else(|| ConfigError::Parse {
+                    message: "mapping must be an array of tables ([[mapping]])".to_string(),
+                })?
+                .iter()
+                .map(|mapping| parse_mapping(mapping, &targets))
+                .collect::<Result<_, _>>()?,
+        };
+        check_mapping_overlaps(&mappings, &targets, &source)?;
+
+        let profiles = table
+            .get("profile")
+            .and_then(|v| v.as_table())
+            .map(|profile_table| {
+                profile_table
+                    .iter()
+                    .map(|(name, value)| {
+                        let profile = Profile {
+                            skills: table_get_str_list(Some(value), "skills").unwrap_or_default(),
+                            instructions: table_get_str(Some(value), "instructions"),
+                        };
+                        (name.clone(), profile)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            source,
+            targets,
+            skills,
+            git,
+            workspace,
+            mappings,
+            profiles,
+            active_profile: None,
+        })
     }
 
-    pub fn agent_names() -> impl Iterator<Item = AgentName> {
-        AgentName::iter()
+    /// The agent called `name`: a built-in one or a custom agent from hana.toml.
+    pub fn find_agent(&self, name: &str) -> Option<AgentName> {
+        find_agent(&self.targets, name)
+    }
+
+    /// Built-in agents, then custom ones by name.
+    pub fn agent_names(&self) -> impl Iterator<Item = AgentName> + use<> {
+        let mut custom: Vec<AgentName> = self
+            .targets
+            .keys()
+            .copied()
+            .filter(|agent| matches!(agent, AgentName::Custom(_)))
+            .collect();
+        custom.sort();
+        AgentName::iter().chain(custom)
     }
 
     pub fn enabled_targets(&self, feature: TargetFeature) -> impl Iterator<Item = AgentName> + '_ {
-        AgentName::iter().filter(move |agent| {
+        self.agent_names().filter(move |agent| {
             self.targets
-                .get(agent.as_str())
+                .get(agent)
                 .map(|target| match feature {
                     TargetFeature::Skills => target.skills,
                     TargetFeature::Instructions => target.instructions,
@@ -234,6 +1003,56 @@ impl Config {
         })
     }
 
+    /// Destinations of a `[[mapping]]`, skipping agents whose target is not used.
+    pub fn resolve_mapping_dests(
+        &self,
+        mapping: &Mapping,
+        base_dir: &Path,
+    ) -> HashMap<AgentName, PathBuf> {
+        mapping
+            .resolve_dests(base_dir)
+            .into_iter()
+            .filter(|(agent, _)| self.targets.get(agent).is_some_and(|t| t.is_used()))
+            .collect()
+    }
+
+    pub fn skill_rule(&self, skill: &str) -> Option<&SkillRule> {
+        self.skills.get(skill)
+    }
+
+    /// Narrow the config to `[profile.<name>]`: its instruction file becomes the source and
+    /// skills outside its patterns are excluded for every agent.
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), ConfigError> {
+        if !self.profiles.contains_key(name) {
+            return Err(ConfigError::UnknownProfile {
+                name: name.to_string(),
+            });
+        }
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Apply the profile recorded by `hana use`, if any.
+    pub fn apply_saved_profile(&mut self, base_dir: &Path) -> Result<(), ConfigError> {
+        match fs::read_to_string(self.resolve_profile_state_path(base_dir)) {
+            Ok(name) if !name.trim().is_empty() => self.apply_profile(name.trim()),
+            _ => Ok(()),
+        }
+    }
+
+    fn profile(&self) -> Option<&Profile> {
+        self.active_profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+    }
+
+    /// Whether the active profile (if any) includes `skill`.
+    pub fn profile_allows(&self, skill: &str) -> bool {
+        self.profile().is_none_or(|profile| {
+            profile.skills.is_empty() || profile.skills.iter().any(|p| glob_match(p, skill))
+        })
+    }
+
     pub fn source_skills_path(&self, global: bool) -> &str {
         if global {
             &self.source.skills_path_global
@@ -242,7 +1061,17 @@ impl Config {
         }
     }
 
+    /// The active profile's instruction file, or else the `[source]` one.
     pub fn source_instruction_path(&self, global: bool) -> &str {
+        if let Some(path) = self.profile().and_then(|p| p.instructions.as_deref()) {
+            return path;
+        }
+        self.configured_instruction_path(global)
+    }
+
+    /// The `[source]` instruction path, ignoring profiles. Agents that read it directly keep
+    /// doing so while a profile swaps the source.
+    pub fn configured_instruction_path(&self, global: bool) -> &str {
         if global {
             &self.source.instruction_path_global
         } else {
@@ -250,8 +1079,8 @@ impl Config {
         }
     }
 
-    pub fn target_skills_path(&self, agent: &str, global: bool) -> Option<&str> {
-        self.targets.get(agent).map(|target| {
+    pub fn target_skills_path(&self, agent: AgentName, global: bool) -> Option<&str> {
+        self.targets.get(&agent).map(|target| {
             if global {
                 target.skills_path_global.as_str()
             } else {
@@ -260,8 +1089,8 @@ impl Config {
         })
     }
 
-    pub fn target_instruction_path(&self, agent: &str, global: bool) -> Option<&str> {
-        self.targets.get(agent).map(|target| {
+    pub fn target_instruction_path(&self, agent: AgentName, global: bool) -> Option<&str> {
+        self.targets.get(&agent).map(|target| {
             if global {
                 target.instruction_path_global.as_str()
             } else {
@@ -270,6 +1099,293 @@ impl Config {
         })
     }
 
+    /// Name of the link an agent sees for a skill id (`frontend/react-review` → `frontend--react-review`).
+    pub fn skill_link_name(&self, skill: &str) -> String {
+        skill.replace('/', &self.source.namespace_separator)
+    }
+
+    /// Map a directory name found in an agent's skills path back to a skill id.
+    pub fn skill_id_from_link_name(&self, name: &str) -> String {
+        let separator = &self.source.namespace_separator;
+        if !self.source.namespaces || separator.is_empty() {
+            return name.to_string();
+        }
+        match name.split_once(separator.as_str()) {
+            Some((category, skill)) if !category.is_empty() && !skill.is_empty() => {
+                format!("{category}/{skill}")
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    /// Extra files of an agent at this level (`files` or `files_global`) as `(source, dest)`
+    /// absolute paths.
+    pub fn resolve_target_files(
+        &self,
+        agent: AgentName,
+        base_dir: &Path,
+        global: bool,
+    ) -> Vec<(PathBuf, PathBuf)> {
+        self.targets
+            .get(&agent)
+            .map(|target| {
+                let files = if global {
+                    &target.files_global
+                } else {
+                    &target.files
+                };
+                files
+                    .iter()
+                    .map(|file| {
+                        (
+                            resolve_path(base_dir, &file.source),
+                            resolve_path(base_dir, &file.dest),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn resolve_source_subagents_path(&self, base_dir: &Path, global: bool) -> PathBuf {
+        let raw = if global {
+            &self.source.subagents_path_global
+        } else {
+            &self.source.subagents_path
+        };
+        resolve_path(base_dir, raw)
+    }
+
+    /// Agents that render subagents, with their resolved destination directory (`None`: no
+    /// subagent directory at this level). Empty unless `[source] subagents = true`.
+    pub fn resolve_subagent_targets(
+        &self,
+        base_dir: &Path,
+        global: bool,
+    ) -> Vec<(AgentName, Option<PathBuf>, &RenderTarget)> {
+        if !self.source.subagents {
+            return vec![];
+        }
+        self.resolve_render_targets(base_dir, global, |t| t.subagents.as_ref())
+    }
+
+    pub fn resolve_source_commands_path(&self, base_dir: &Path, global: bool) -> PathBuf {
+        let raw = if global {
+            &self.source.commands_path_global
+        } else {
+            &self.source.commands_path
+        };
+        resolve_path(base_dir, raw)
+    }
+
+    /// Agents that render commands, like `resolve_subagent_targets`. Empty unless
+    /// `[source] commands = true`.
+    pub fn resolve_command_targets(
+        &self,
+        base_dir: &Path,
+        global: bool,
+    ) -> Vec<(AgentName, Option<PathBuf>, &RenderTarget)> {
+        if !self.source.commands {
+            return vec![];
+        }
+        self.resolve_render_targets(base_dir, global, |t| t.commands.as_ref())
+    }
+
+    /// Every directory hana may have rendered subagents into at this level, even with the
+    /// feature off now, so leftover generated files can be pruned.
+    pub fn resolve_subagent_prune_dirs(
+        &self,
+        base_dir: &Path,
+        global: bool,
+    ) -> Vec<(AgentName, PathBuf)> {
+        self.resolve_prune_dirs(
+            base_dir,
+            global,
+            |t| t.subagents.as_ref(),
+            RenderTarget::subagents_for,
+        )
+    }
+
+    /// Like `resolve_subagent_prune_dirs`, for commands.
+    pub fn resolve_command_prune_dirs(
+        &self,
+        base_dir: &Path,
+        global: bool,
+    ) -> Vec<(AgentName, PathBuf)> {
+        self.resolve_prune_dirs(
+            base_dir,
+            global,
+            |t| t.commands.as_ref(),
+            RenderTarget::commands_for,
+        )
+    }
+
+    fn resolve_prune_dirs(
+        &self,
+        base_dir: &Path,
+        global: bool,
+        pick: fn(&TargetConfig) -> Option<&RenderTarget>,
+        builtin: fn(AgentName) -> Option<RenderTarget>,
+    ) -> Vec<(AgentName, PathBuf)> {
+        let mut dirs: Vec<(AgentName, PathBuf)> = Vec::new();
+        for agent in self.agent_names() {
+            let configured = self.targets.get(&agent).and_then(pick).cloned();
+            for target in configured.into_iter().chain(builtin(agent)) {
+                let raw = if global {
+                    target.path_global
+                } else {
+                    target.path
+                };
+                if let Some(dir) = raw.map(|raw| resolve_path(base_dir, &raw)) {
+                    if !dirs.iter().any(|(_, d)| *d == dir) {
+                        dirs.push((agent, dir));
+                    }
+                }
+            }
+        }
+        dirs
+    }
+
+    pub fn resolve_source_mcp_path(&self, base_dir: &Path, global: bool) -> PathBuf {
+        let raw = if global {
+            &self.source.mcp_path_global
+        } else {
+            &self.source.mcp_path
+        };
+        resolve_path(base_dir, raw)
+    }
+
+    pub fn resolve_env_path(&self, base_dir: &Path, global: bool) -> PathBuf {
+        let raw = if global {
+            &self.source.env_path_global
+        } else {
+            &self.source.env_path
+        };
+        resolve_path(base_dir, raw)
+    }
+
+    /// Servers from the MCP source, sorted by name. A missing file means no servers.
+    pub fn load_mcp_servers(
+        &self,
+        base_dir: &Path,
+        global: bool,
+    ) -> Result<Vec<McpServer>, ConfigError> {
+        let path = self.resolve_source_mcp_path(base_dir, global);
+        match fs::read_to_string(&path) {
+            Ok(content) => parse_mcp_servers(&content, &self.targets),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(ConfigError::ReadFile { path, source: e }),
+        }
+    }
+
+    /// Enabled agents with MCP support, with their resolved config file.
+    pub fn resolve_mcp_targets(
+        &self,
+        base_dir: &Path,
+        global: bool,
+    ) -> Vec<(AgentName, PathBuf, McpFormat)> {
+        self.mcp_targets(base_dir, global, false)
+    }
+
+    /// Every agent with MCP support, including those turned off since, so the servers hana
+    /// wrote for them can be removed.
+    pub fn resolve_mcp_prune_targets(
+        &self,
+        base_dir: &Path,
+        global: bool,
+    ) -> Vec<(AgentName, PathBuf, McpFormat)> {
+        self.mcp_targets(base_dir, global, true)
+    }
+
+    fn mcp_targets(
+        &self,
+        base_dir: &Path,
+        global: bool,
+        include_unused: bool,
+    ) -> Vec<(AgentName, PathBuf, McpFormat)> {
+        self.agent_names()
+            .filter_map(|agent| {
+                let configured = self.targets.get(&agent)?;
+                let target = match &configured.mcp {
+                    Some(target) if include_unused || configured.is_used() => target.clone(),
+                    None if include_unused => McpTarget::default_for(agent)?,
+                    _ => return None,
+                };
+                let raw = if global {
+                    &target.path_global
+                } else {
+                    &target.path
+                };
+                Some((agent, resolve_path(base_dir, raw), target.format))
+            })
+            .collect()
+    }
+
+    fn resolve_render_targets(
+        &self,
+        base_dir: &Path,
+        global: bool,
+        pick: fn(&TargetConfig) -> Option<&RenderTarget>,
+    ) -> Vec<(AgentName, Option<PathBuf>, &RenderTarget)> {
+        self.agent_names()
+            .filter_map(|agent| {
+                let target = pick(self.targets.get(&agent)?)?;
+                let raw = if global {
+                    &target.path_global
+                } else {
+                    &target.path
+                };
+                let dest = raw.as_deref().map(|raw| resolve_path(base_dir, raw));
+                Some((agent, dest, target))
+            })
+            .collect()
+    }
+
+    /// Agent-specific instruction fragment for generated mode (`.agents/instructions/<agent>.md`).
+    pub fn resolve_fragment_path(&self, base_dir: &Path, agent: AgentName) -> PathBuf {
+        base_dir
+            .join(".agents/instructions")
+            .join(format!("{agent}.md"))
+    }
+
+    /// `.hanaignore` lives next to hana.toml.
+    pub fn resolve_ignore_path(&self, base_dir: &Path) -> PathBuf {
+        base_dir.join(".agents/.hanaignore")
+    }
+
+    /// `[workspace] repos`, in order and without duplicates. Wildcard matches without a
+    /// `.agents/hana.toml` are dropped; literal paths are kept so `hana workspace` can report them.
+    pub fn resolve_workspace_repos(&self, base_dir: &Path) -> Vec<PathBuf> {
+        let mut repos: Vec<PathBuf> = Vec::new();
+        for pattern in &self.workspace.repos {
+            let path = resolve_path(base_dir, pattern);
+            let found = if pattern.contains(['*', '?']) {
+                expand_path_glob(&path)
+                    .into_iter()
+                    .filter(|repo| repo.join(".agents/hana.toml").is_file())
+                    .collect()
+            } else {
+                vec![path]
+            };
+            for repo in found {
+                if !repos.contains(&repo) {
+                    repos.push(repo);
+                }
+            }
+        }
+        repos
+    }
+
+    /// The MCP servers hana wrote, per agent, next to hana.toml.
+    pub fn resolve_mcp_state_path(&self, base_dir: &Path) -> PathBuf {
+        base_dir.join(".agents/.hanamcp")
+    }
+
+    /// The active profile lives next to hana.toml, like `.hanaignore`.
+    pub fn resolve_profile_state_path(&self, base_dir: &Path) -> PathBuf {
+        base_dir.join(".agents/.hanaprofile")
+    }
+
     pub fn resolve_source_skills_path(&self, base_dir: &Path, global: bool) -> PathBuf {
         resolve_path(base_dir, self.source_skills_path(global))
     }
@@ -278,9 +1394,13 @@ impl Config {
         resolve_path(base_dir, self.source_instruction_path(global))
     }
 
+    pub fn resolve_configured_instruction_path(&self, base_dir: &Path, global: bool) -> PathBuf {
+        resolve_path(base_dir, self.configured_instruction_path(global))
+    }
+
     pub fn resolve_target_skills_path(
         &self,
-        agent: &str,
+        agent: AgentName,
         base_dir: &Path,
         global: bool,
     ) -> Option<PathBuf> {
@@ -290,7 +1410,7 @@ impl Config {
 
     pub fn resolve_target_instruction_path(
         &self,
-        agent: &str,
+        agent: AgentName,
         base_dir: &Path,
         global: bool,
     ) -> Option<PathBuf> {
@@ -306,6 +1426,361 @@ fn table_get_str(table: Option<&toml::Value>, key: &str) -> Option<String> {
         .map(str::to_string)
 }
 
+fn table_get_filter(table: Option<&toml::Value>) -> SkillFilter {
+    SkillFilter {
+        include: table_get_str_list(table, "include").unwrap_or_default(),
+        exclude: table_get_str_list(table, "exclude").unwrap_or_default(),
+    }
+}
+
+fn table_get_str_list(table: Option<&toml::Value>, key: &str) -> Option<Vec<String>> {
+    table.and_then(|v| v.get(key)).and_then(|v| match v {
+        toml::Value::String(s) => Some(vec![s.clone()]),
+        toml::Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .map(str::to_string)
+                .collect(),
+        ),
+        _ => None,
+    })
+}
+
+fn table_get_files(
+    table: Option<&toml::Value>,
+    agent: &str,
+    key: &str,
+) -> Result<Vec<TargetFile>, ConfigError> {
+    let Some(entries) = table.and_then(|v| v.get(key)) else {
+        return Ok(vec![]);
+    };
+    let invalid = || ConfigError::Parse {
+        message: format!(
+            "target.{agent}.{key} must be a list of {{ source = \"…\", dest = \"…\" }} tables"
+        ),
+    };
+    entries
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|entry| {
+            let entry = Some(entry);
+            Ok(TargetFile {
+                source: table_get_str(entry, "source").ok_or_else(invalid)?,
+                dest: table_get_str(entry, "dest").ok_or_else(invalid)?,
+            })
+        })
+        .collect()
+}
+
+/// `[target.x.<key>]` overrides the built-in table field by field; `<key> = false` turns the
+/// feature off for the target.
+fn table_get_render_target(
+    table: Option<&toml::Value>,
+    agent: AgentName,
+    key: &str,
+    defaults: Option<RenderTarget>,
+) -> Result<Option<RenderTarget>, ConfigError> {
+    let name = agent.as_str();
+    let value = table.and_then(|v| v.get(key));
+    if value.and_then(|v| v.as_bool()) == Some(false) {
+        return Ok(None);
+    }
+    let Some(value) = value.filter(|v| v.is_table()) else {
+        return Ok(defaults);
+    };
+
+    let (path, path_global) = match (
+        table_get_str(Some(value), "path"),
+        table_get_str(Some(value), "path_global"),
+        &defaults,
+    ) {
+        (path, path_global, Some(d)) => (
+            path.or_else(|| d.path.clone()),
+            path_global.or_else(|| d.path_global.clone()),
+        ),
+        (Some(path), path_global, None) => (Some(path.clone()), path_global.or(Some(path))),
+        (None, _, None) => {
+            return Err(ConfigError::Parse {
+                message: format!("target.{name}.{key}.path is required"),
+            });
+        }
+    };
+    let lists = match table_get_str(Some(value), "lists").as_deref() {
+        None => defaults.as_ref().map(|d| d.lists).unwrap_or_default(),
+        Some("csv") => ListStyle::Csv,
+        Some("yaml") => ListStyle::Yaml,
+        Some("map") => ListStyle::Map,
+        Some(other) => {
+            return Err(ConfigError::Parse {
+                message: format!(
+                    "target.{name}.{key}.lists must be \"csv\", \"yaml\" or \"map\", got \"{other}\""
+                ),
+            });
+        }
+    };
+
+    let mut fields = defaults
+        .as_ref()
+        .map(|d| d.fields.clone())
+        .unwrap_or_default();
+    if let Some(table) = value.get("fields").and_then(|v| v.as_table()) {
+        for (field, mapped) in table {
+            let mapped = match mapped {
+                toml::Value::String(to) => Some(to.clone()),
+                toml::Value::Boolean(false) => None,
+                _ => {
+                    return Err(ConfigError::Parse {
+                        message: format!(
+                            "target.{name}.{key}.fields.{field} must be a key name or false"
+                        ),
+                    });
+                }
+            };
+            fields.retain(|(k, _)| k != field);
+            fields.push((field.clone(), mapped));
+        }
+    }
+
+    let mut set = defaults.as_ref().map(|d| d.set.clone()).unwrap_or_default();
+    if let Some(table) = value.get("set").and_then(|v| v.as_table()) {
+        for (field, val) in table {
+            let val = match val {
+                toml::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            set.retain(|(k, _)| k != field);
+            set.push((field.clone(), val));
+        }
+    }
+
+    let unsupported = table_get_str_list(Some(value), "unsupported")
+        .or_else(|| defaults.map(|d| d.unsupported))
+        .unwrap_or_default();
+
+    Ok(Some(RenderTarget {
+        path,
+        path_global,
+        fields,
+        lists,
+        set,
+        unsupported,
+    }))
+}
+
+/// `mcp = false` turns MCP sync off for the target; `[target.x.mcp]` moves its config file.
+fn table_get_mcp(
+    table: Option<&toml::Value>,
+    agent: AgentName,
+) -> Result<Option<McpTarget>, ConfigError> {
+    let value = table.and_then(|v| v.get("mcp"));
+    if value.and_then(|v| v.as_bool()) == Some(false) {
+        return Ok(None);
+    }
+    let defaults = McpTarget::default_for(agent);
+    let Some(value) = value.filter(|v| v.is_table()) else {
+        return Ok(defaults);
+    };
+    let Some(defaults) = defaults else {
+        return Err(ConfigError::Parse {
+            message: format!("target.{agent}.mcp: {agent} has no MCP config format"),
+        });
+    };
+    Ok(Some(McpTarget {
+        path: table_get_str(Some(value), "path").unwrap_or(defaults.path),
+        path_global: table_get_str(Some(value), "path_global").unwrap_or(defaults.path_global),
+        format: defaults.format,
+    }))
+}
+
+/// The agent called `name`: a built-in one or a custom `[target.<name>]`.
+fn find_agent(targets: &HashMap<AgentName, TargetConfig>, name: &str) -> Option<AgentName> {
+    AgentName::builtin(name)
+        .or_else(|| targets.keys().copied().find(|agent| agent.as_str() == name))
+}
+
+fn parse_mcp_servers(
+    content: &str,
+    targets: &HashMap<AgentName, TargetConfig>,
+) -> Result<Vec<McpServer>, ConfigError> {
+    let parse_err = |message: String| ConfigError::Parse { message };
+    let table: toml::Table = content
+        .parse()
+        .map_err(|e: toml::de::Error| parse_err(format!("mcp.toml: {e}")))?;
+    let Some(servers) = table.get("servers") else {
+        return Ok(vec![]);
+    };
+    let servers = servers
+        .as_table()
+        .ok_or_else(|| parse_err("mcp.toml: servers must be a table".to_string()))?;
+
+    let string_map = |value: Option<&toml::Value>, key: &str, name: &str| {
+        let Some(value) = value.and_then(|v| v.get(key)) else {
+            return Ok(vec![]);
+        };
+        value
+            .as_table()
+            .and_then(|t| {
+                t.iter()
+                    .map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| parse_err(format!("servers.{name}.{key} must be a table of strings")))
+    };
+
+    servers
+        .iter()
+        .map(|(name, value)| {
+            let server = Some(value);
+            let command = table_get_str(server, "command");
+            let url = table_get_str(server, "url");
+            if command.is_some() == url.is_some() {
+                return Err(parse_err(format!(
+                    "servers.{name} needs exactly one of command or url"
+                )));
+            }
+            let agents = table_get_str_list(server, "agents")
+                .map(|names| {
+                    names
+                        .iter()
+                        .map(|agent| {
+                            find_agent(targets, agent).ok_or_else(|| {
+                                parse_err(format!(
+                                    "servers.{name}.agents: unknown agent \"{agent}\""
+                                ))
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?;
+            Ok(McpServer {
+                name: name.clone(),
+                command,
+                args: table_get_str_list(server, "args").unwrap_or_default(),
+                env: string_map(server, "env", name)?,
+                url,
+                headers: string_map(server, "headers", name)?,
+                agents,
+                enabled: table_get_bool(server, "enabled").unwrap_or(true),
+            })
+        })
+        .collect()
+}
+
+fn parse_mapping(
+    value: &toml::Value,
+    targets: &HashMap<AgentName, TargetConfig>,
+) -> Result<Mapping, ConfigError> {
+    let table = Some(value);
+    let parse_err = |message: String| ConfigError::Parse { message };
+
+    let source = table_get_str(table, "source")
+        .ok_or_else(|| parse_err("mapping.source is required".to_string()))?;
+    let name = table_get_str(table, "name").unwrap_or_else(|| source.clone());
+    let kind = match table_get_str(table, "kind").as_deref() {
+        None | Some("dir") => MappingKind::Dir,
+        Some("file") => MappingKind::File,
+        Some(other) => {
+            return Err(parse_err(format!(
+                "mapping.kind must be \"dir\" or \"file\", got \"{other}\""
+            )));
+        }
+    };
+    let collect = match table_get_str(table, "collect").as_deref() {
+        None | Some("auto") => CollectPolicy::Auto,
+        Some("never") => CollectPolicy::Never,
+        Some(other) => {
+            return Err(parse_err(format!(
+                "mapping.collect must be \"auto\" or \"never\", got \"{other}\""
+            )));
+        }
+    };
+
+    let dest_table = value
+        .get("dest")
+        .and_then(|v| v.as_table())
+        .ok_or_else(|| parse_err(format!("mapping \"{name}\" needs a [mapping.dest] table")))?;
+    let mut dest = Vec::new();
+    for (key, path) in dest_table {
+        let Some(agent) = find_agent(targets, key) else {
+            return Err(parse_err(format!(
+                "mapping \"{name}\": unknown agent \"{key}\" in dest"
+            )));
+        };
+        if let Some(path) = path.as_str() {
+            dest.push((agent, path.to_string()));
+        }
+    }
+    dest.sort_by_key(|(agent, _)| *agent);
+
+    Ok(Mapping {
+        name,
+        source,
+        kind,
+        collect,
+        dest,
+    })
+}
+
+/// Two mappings, or a mapping and a directory hana renders subagents or commands into, that
+/// share a destination (or nest one inside the other) would fight over its files.
+fn check_mapping_overlaps(
+    mappings: &[Mapping],
+    targets: &HashMap<AgentName, TargetConfig>,
+    source: &SourceConfig,
+) -> Result<(), ConfigError> {
+    let overlaps = |a: &str, b: &str| {
+        let (a, b) = (Path::new(a), Path::new(b));
+        a.starts_with(b) || b.starts_with(a)
+    };
+    let overlap_err =
+        |mapping: &Mapping, agent: AgentName, dest: &str, other: String| ConfigError::Parse {
+            message: format!(
+                "mapping \"{}\": {agent} dest {dest} overlaps {other}",
+                mapping.name
+            ),
+        };
+
+    for (i, mapping) in mappings.iter().enumerate() {
+        for (agent, dest) in &mapping.dest {
+            for other in &mappings[i + 1..] {
+                if let Some((_, taken)) = other.dest.iter().find(|(_, d)| overlaps(dest, d)) {
+                    return Err(overlap_err(
+                        mapping,
+                        *agent,
+                        dest,
+                        format!("mapping \"{}\" dest {taken}", other.name),
+                    ));
+                }
+            }
+
+            let Some(target) = targets.get(agent) else {
+                continue;
+            };
+            let rendered = [
+                ("subagents", source.subagents, &target.subagents),
+                ("commands", source.commands, &target.commands),
+            ];
+            for (feature, enabled, render) in rendered {
+                let Some(render) = render.as_ref().filter(|_| enabled) else {
+                    continue;
+                };
+                let taken = [&render.path, &render.path_global];
+                if taken.into_iter().flatten().any(|path| overlaps(dest, path)) {
+                    return Err(overlap_err(
+                        mapping,
+                        *agent,
+                        dest,
+                        format!("its {feature} directory"),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 fn table_get_bool(table: Option<&toml::Value>, key: &str) -> Option<bool> {
     table.and_then(|v| v.get(key)).and_then(|v| v.as_bool())
 }
@@ -339,10 +1814,58 @@ mod tests {
 
     #[test]
     fn test_agent_enum_iter() {
-        let names: Vec<&str> = Config::agent_names().map(|a| a.as_str()).collect();
+        let names: Vec<&str> = Config::default()
+            .agent_names()
+            .map(|a| a.as_str())
+            .collect();
         assert_eq!(names, vec!["claude", "codex", "pi", "opencode"]);
     }
 
+    #[test]
+    fn test_parse_custom_targets() {
+        let config = Config::parse(
+            "[target.zed]\ncustom = true\ninstructions = false\n\n[target.cursor]\ncustom = true\nskills_path = \".cursor/rules\"\n",
+        )
+        .unwrap();
+        let cursor = AgentName::new("cursor");
+        assert_eq!(AgentName::new("pi"), AgentName::Pi);
+        assert_eq!(cursor, AgentName::new("cursor"));
+        assert_eq!(cursor.as_str(), "cursor");
+
+        let names: Vec<&str> = config.agent_names().map(|a| a.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["claude", "codex", "pi", "opencode", "cursor", "zed"]
+        );
+        assert_eq!(
+            config.target_skills_path(cursor, false),
+            Some(".cursor/rules")
+        );
+        assert_eq!(
+            config.target_skills_path(cursor, true),
+            Some(".cursor/skills")
+        );
+        assert_eq!(
+            config.target_instruction_path(cursor, false),
+            Some("AGENTS.md")
+        );
+        assert_eq!(
+            config.target_instruction_path(cursor, true),
+            Some(".cursor/AGENTS.md")
+        );
+        assert!(config.targets[&cursor].subagents.is_none());
+        assert!(Config::parse("[target.claud]\nskills = false\n").is_err());
+        assert!(!config.targets[&AgentName::new("zed")].instructions);
+
+        let servers = parse_mcp_servers(
+            "[servers.docs]\nurl = \"https://docs\"\nagents = [\"cursor\"]\n",
+            &config.targets,
+        )
+        .unwrap();
+        assert!(servers[0].applies_to(cursor));
+        assert!(Config::parse("[target.cursor.mcp]\npath = \"mcp.json\"\n").is_err());
+    }
+
     #[test]
     fn test_parse_default_config() {
         let config = Config::parse(crate::init::PROJECT_CONFIG).unwrap();
@@ -350,11 +1873,11 @@ mod tests {
         assert_eq!(config.source.instruction_path, "AGENTS.md");
         assert_eq!(config.targets.len(), 4);
         assert_eq!(
-            config.target_skills_path("pi", false).unwrap(),
+            config.target_skills_path(AgentName::Pi, false).unwrap(),
             ".pi/skills"
         );
         assert_eq!(
-            config.target_skills_path("pi", true).unwrap(),
+            config.target_skills_path(AgentName::Pi, true).unwrap(),
             ".pi/agent/skills"
         );
     }
@@ -375,10 +1898,10 @@ skills = true
 instructions = false
 "#;
         let config = Config::parse(toml).unwrap();
-        assert!(!config.targets["codex"].skills);
-        assert!(config.targets["codex"].instructions);
-        assert!(config.targets["pi"].skills);
-        assert!(!config.targets["pi"].instructions);
+        assert!(!config.targets[&AgentName::Codex].skills);
+        assert!(config.targets[&AgentName::Codex].instructions);
+        assert!(config.targets[&AgentName::Pi].skills);
+        assert!(!config.targets[&AgentName::Pi].instructions);
     }
 
     #[test]
@@ -423,10 +1946,12 @@ skills_path = "custom/skills"
 "#;
         let config = Config::parse(toml).unwrap();
         assert_eq!(config.source.skills_path, "custom/skills");
-        assert!(config.targets["claude"].skills);
-        assert!(config.targets["pi"].instructions);
+        assert!(config.targets[&AgentName::Claude].skills);
+        assert!(config.targets[&AgentName::Pi].instructions);
         assert_eq!(
-            config.target_instruction_path("claude", true).unwrap(),
+            config
+                .target_instruction_path(AgentName::Claude, true)
+                .unwrap(),
             ".claude/CLAUDE.md"
         );
     }
@@ -442,19 +1967,21 @@ instruction_path_global = "~/global/pi/AGENTS.md"
 "#;
         let config = Config::parse(toml).unwrap();
         assert_eq!(
-            config.target_skills_path("pi", false).unwrap(),
+            config.target_skills_path(AgentName::Pi, false).unwrap(),
             "custom/pi/skills"
         );
         assert_eq!(
-            config.target_skills_path("pi", true).unwrap(),
+            config.target_skills_path(AgentName::Pi, true).unwrap(),
             "~/global/pi/skills"
         );
         assert_eq!(
-            config.target_instruction_path("pi", false).unwrap(),
+            config
+                .target_instruction_path(AgentName::Pi, false)
+                .unwrap(),
             "custom/pi/AGENTS.md"
         );
         assert_eq!(
-            config.target_instruction_path("pi", true).unwrap(),
+            config.target_instruction_path(AgentName::Pi, true).unwrap(),
             "~/global/pi/AGENTS.md"
         );
     }
@@ -475,7 +2002,101 @@ instructions = true
         let config = Config::parse(toml).unwrap();
         assert_eq!(config.source.skills_path, ".agents/skills");
         assert_eq!(config.source.skills_path_global, ".agents/skills");
-        assert!(!config.targets["pi"].skills);
+        assert!(!config.targets[&AgentName::Pi].skills);
+    }
+
+    #[test]
+    fn test_parse_skill_rules() {
+        let toml = r#"
+[skill.claude-hooks]
+targets = ["claude"]
+
+[skill.shared]
+exclude = ["pi", "codex"]
+"#;
+        let config = Config::parse(toml).unwrap();
+
+        let hooks = config.skill_rule("claude-hooks").unwrap();
+        assert!(hooks.allows(AgentName::Claude));
+        assert!(!hooks.allows(AgentName::Opencode));
+
+        let shared = config.skill_rule("shared").unwrap();
+        assert!(shared.allows(AgentName::Claude));
+        assert!(!shared.allows(AgentName::Pi));
+        assert!(config.skill_rule("other").is_none());
+    }
+
+    #[test]
+    fn test_parse_skill_rule_rejects_unknown_agent() {
+        let err = Config::parse("[skill.hooks]\ntargets = [\"claud\"]\n").unwrap_err();
+        assert!(err.to_string().contains("unknown agent \"claud\""));
+
+        let err = Config::parse("[skill.hooks]\nexclude = [\"pie\"]\n").unwrap_err();
+        assert!(err.to_string().contains("unknown agent \"pie\""));
+    }
+
+    #[test]
+    fn test_parse_skill_filters() {
+        let toml = r#"
+[source]
+include = ["team-*", "wip-*"]
+exclude = ["wip-*", "_*"]
+
+[target.claude]
+exclude = ["scratch"]
+"#;
+        let config = Config::parse(toml).unwrap();
+        let source = &config.source.filter;
+        assert!(source.matches("team-review"));
+        assert!(!source.matches("wip-draft"));
+        assert!(!source.matches("personal"));
+        assert!(!config.targets[&AgentName::Claude].filter.matches("scratch"));
+        assert!(
+            config.targets[&AgentName::Claude]
+                .filter
+                .matches("anything")
+        );
+        assert!(config.targets[&AgentName::Pi].filter.matches("scratch"));
+    }
+
+    #[test]
+    fn test_namespace_link_names() {
+        let config = Config::parse("[source]\nnamespaces = true\n").unwrap();
+        assert_eq!(
+            config.skill_link_name("frontend/react-review"),
+            "frontend--react-review"
+        );
+        assert_eq!(
+            config.skill_id_from_link_name("frontend--react-review"),
+            "frontend/react-review"
+        );
+        assert_eq!(config.skill_id_from_link_name("plain"), "plain");
+
+        let flat = Config::default();
+        assert_eq!(flat.skill_id_from_link_name("a--b"), "a--b");
+    }
+
+    #[test]
+    fn test_skill_filter_skips_hidden_dirs() {
+        let filter = SkillFilter::default();
+        assert!(filter.matches("my-skill"));
+        assert!(!filter.matches(".git"));
+        assert!(!filter.matches(".DS_Store"));
+    }
+
+    #[test]
+    fn test_skill_rule_merge_prefers_primary_targets() {
+        let primary = SkillRule {
+            targets: Some(vec![AgentName::Claude]),
+            exclude: vec![],
+        };
+        let fallback = SkillRule {
+            targets: Some(vec![AgentName::Pi]),
+            exclude: vec![AgentName::Opencode],
+        };
+        let merged = primary.merge(&fallback);
+        assert_eq!(merged.targets, Some(vec![AgentName::Claude]));
+        assert_eq!(merged.exclude, vec![AgentName::Opencode]);
     }
 
     #[test]
@@ -488,6 +2109,327 @@ instructions = true
         }
     }
 
+    #[test]
+    fn test_parse_git_ignore_links() {
+        assert!(!Config::default().git.ignore_links);
+        let config = Config::parse("[git]\nignore_links = true\n").unwrap();
+        assert!(config.git.ignore_links);
+    }
+
+    #[test]
+    fn test_parse_instruction_mode() {
+        assert_eq!(
+            Config::default().source.instruction_mode,
+            InstructionMode::Link
+        );
+        let config = Config::parse("[source]\ninstruction_mode = \"generate\"\n").unwrap();
+        assert_eq!(config.source.instruction_mode, InstructionMode::Generate);
+        assert!(Config::parse("[source]\ninstruction_mode = \"copy\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_includes_and_imports() {
+        let config = Config::default();
+        assert!(!config.source.expand_includes);
+        assert!(config.targets[&AgentName::Claude].imports);
+        assert!(!config.targets[&AgentName::Pi].imports);
+
+        let config =
+            Config::parse("[source]\nexpand_includes = true\n\n[target.claude]\nimports = false\n")
+                .unwrap();
+        assert!(config.source.expand_includes);
+        assert!(!config.targets[&AgentName::Claude].imports);
+    }
+
+    #[test]
+    fn test_parse_target_files() {
+        assert!(Config::default().targets[&AgentName::Pi].files.is_empty());
+
+        let config = Config::parse(
+            "[target.pi]\nfiles = [{ source = \"~/.agents/SYSTEM.md\", dest = \".pi/agent/SYSTEM.md\" }]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.targets[&AgentName::Pi].files,
+            vec![TargetFile {
+                source: "~/.agents/SYSTEM.md".to_string(),
+                dest: ".pi/agent/SYSTEM.md".to_string(),
+            }]
+        );
+        let resolved = config.resolve_target_files(AgentName::Pi, Path::new("/base"), false);
+        assert_eq!(resolved[0].1, PathBuf::from("/base/.pi/agent/SYSTEM.md"));
+        assert!(
+            config
+                .resolve_target_files(AgentName::Pi, Path::new("/base"), true)
+                .is_empty()
+        );
+
+        let config = Config::parse(
+            "[target.pi]\nfiles_global = [{ source = \"~/.agents/SYSTEM.md\", dest = \".pi/agent/SYSTEM.md\" }]\n",
+        )
+        .unwrap();
+        let resolved = config.resolve_target_files(AgentName::Pi, Path::new("/home/u"), true);
+        assert_eq!(resolved[0].1, PathBuf::from("/home/u/.pi/agent/SYSTEM.md"));
+
+        assert!(Config::parse("[target.pi]\nfiles = [{ source = \"a\" }]\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_mappings() {
+        assert!(Config::default().mappings.is_empty());
+
+        let config = Config::parse(
+            "[[mapping]]\nname = \"personas\"\nsource = \".agents/agents\"\n\n[mapping.dest]\nclaude = \".claude/agents\"\nopencode = \".opencode/agent\"\n\n[[mapping]]\nsource = \".agents/style.md\"\nkind = \"file\"\ncollect = \"never\"\ndest = { claude = \".claude/output-styles/team.md\" }\n",
+        )
+        .unwrap();
+        assert_eq!(config.mappings.len(), 2);
+        assert_eq!(config.mappings[0].name, "personas");
+        assert_eq!(config.mappings[0].kind, MappingKind::Dir);
+        assert_eq!(config.mappings[0].collect, CollectPolicy::Auto);
+        assert_eq!(
+            config.mappings[0].dest,
+            vec![
+                (AgentName::Claude, ".claude/agents".to_string()),
+                (AgentName::Opencode, ".opencode/agent".to_string()),
+            ]
+        );
+        assert_eq!(config.mappings[1].name, ".agents/style.md");
+        assert_eq!(config.mappings[1].kind, MappingKind::File);
+        assert_eq!(config.mappings[1].collect, CollectPolicy::Never);
+
+        assert!(Config::parse("[[mapping]]\nsource = \"a\"\n").is_err());
+        let custom = Config::parse(
+            "[target.cursor]\ncustom = true\n\n[[mapping]]\nsource = \"a\"\ndest = { cursor = \"b\", claude = \"c\" }\n",
+        )
+        .unwrap();
+        assert_eq!(
+            custom.mappings[0].dest,
+            vec![
+                (AgentName::Claude, "c".to_string()),
+                (AgentName::new("cursor"), "b".to_string()),
+            ]
+        );
+        assert!(Config::parse("[[mapping]]\nsource = \"a\"\ndest = { gemini = \"b\" }\n").is_err());
+        assert!(
+            Config::parse("[[mapping]]\nsource = \"a\"\nkind = \"glob\"\ndest = { pi = \"b\" }\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_subagents() {
+        let config = Config::default();
+        assert!(config.targets[&AgentName::Claude].subagents.is_some());
+        assert!(config.targets[&AgentName::Pi].subagents.is_none());
+        let opencode = config.targets[&AgentName::Opencode]
+            .subagents
+            .as_ref()
+            .unwrap();
+        assert_eq!(opencode.lists, ListStyle::Map);
+
+        let config = Config::parse(
+            "[target.claude]\nsubagents = false\n\n[target.pi.subagents]\npath = \".pi/agents\"\nlists = \"yaml\"\nset = { hidden = true }\n\n[target.pi.subagents.fields]\nmodel = false\ntools = \"allowed-tools\"\n",
+        )
+        .unwrap();
+        assert!(config.targets[&AgentName::Claude].subagents.is_none());
+        let pi = config.targets[&AgentName::Pi].subagents.as_ref().unwrap();
+        assert_eq!(pi.path_global.as_deref(), Some(".pi/agents"));
+        assert_eq!(pi.lists, ListStyle::Yaml);
+        assert_eq!(pi.set, vec![("hidden".to_string(), "true".to_string())]);
+        assert!(pi.fields.contains(&("model".to_string(), None)));
+        assert!(
+            pi.fields
+                .contains(&("tools".to_string(), Some("allowed-tools".to_string())))
+        );
+
+        assert!(Config::parse("[target.pi.subagents]\nlists = \"csv\"\n").is_err());
+    }
+
+    #[test]
+    fn test_subagents_are_opt_in() {
+        let base = Path::new("/base");
+        assert!(!Config::default().source.subagents);
+        assert!(
+            Config::default()
+                .resolve_subagent_targets(base, false)
+                .is_empty()
+        );
+
+        let config = Config::parse("[source]\nsubagents = true\n").unwrap();
+        let agents: Vec<_> = config
+            .resolve_subagent_targets(base, false)
+            .into_iter()
+            .map(|(agent, _, _)| agent)
+            .collect();
+        assert_eq!(agents, vec![AgentName::Claude, AgentName::Opencode]);
+    }
+
+    #[test]
+    fn test_mapping_over_subagents_dir_is_rejected() {
+        let personas = "[[mapping]]\nname = \"personas\"\nsource = \".agents/agents\"\ndest = { claude = \".claude/agents/\" }\n";
+        assert!(Config::parse(personas).is_ok());
+
+        let err = Config::parse(&format!("[source]\nsubagents = true\n\n{personas}")).unwrap_err();
+        assert!(err.to_string().contains("subagents directory"));
+
+        let prompts = "[[mapping]]\nname = \"prompts\"\nsource = \".agents/prompts\"\ndest = { claude = \".claude/commands/team\" }\n";
+        assert!(Config::parse(prompts).is_ok());
+        let err = Config::parse(&format!("[source]\ncommands = true\n\n{prompts}")).unwrap_err();
+        assert!(err.to_string().contains("commands directory"));
+    }
+
+    #[test]
+    fn test_overlapping_mappings_are_rejected() {
+        let err = Config::parse(
+            "[[mapping]]\nname = \"a\"\nsource = \".agents/a\"\ndest = { claude = \".claude/shared\" }\n\n[[mapping]]\nname = \"b\"\nsource = \".agents/b\"\ndest = { opencode = \".claude/shared/\" }\n",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("mapping \"b\" dest .claude/shared/")
+        );
+
+        // One destination inside another overlaps too
+        assert!(
+            Config::parse(
+                "[[mapping]]\nsource = \".agents/a\"\ndest = { claude = \".claude/docs\" }\n\n[[mapping]]\nsource = \".agents/b.md\"\nkind = \"file\"\ndest = { claude = \".claude/docs/b.md\" }\n",
+            )
+            .is_err()
+        );
+        assert!(
+            Config::parse(
+                "[[mapping]]\nsource = \".agents/a\"\ndest = { claude = \".claude/a\", opencode = \".opencode/a\" }\n\n[[mapping]]\nsource = \".agents/b\"\ndest = { claude = \".claude/b\" }\n",
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let config = Config::default();
+        assert!(!config.source.commands);
+        assert!(
+            config
+                .resolve_command_targets(Path::new("/p"), false)
+                .is_empty()
+        );
+
+        let config = Config::parse(
+            "[source]\ncommands = true\n\n[target.codex.commands]\npath = \".codex/prompts\"\nunsupported = [\"allowed-tools\"]\n\n[target.opencode]\ncommands = false\n",
+        )
+        .unwrap();
+        let targets = config.resolve_command_targets(Path::new("/p"), false);
+        let agents: Vec<&str> = targets.iter().map(|(a, _, _)| a.as_str()).collect();
+        assert_eq!(agents, vec!["claude", "codex", "pi"]);
+        assert_eq!(targets[1].1, Some(PathBuf::from("/p/.codex/prompts")));
+        assert_eq!(targets[1].2.unsupported, vec!["allowed-tools".to_string()]);
+        assert_eq!(
+            config.targets[&AgentName::Codex]
+                .commands
+                .as_ref()
+                .unwrap()
+                .path_global
+                .as_deref(),
+            Some(".codex/prompts")
+        );
+    }
+
+    #[test]
+    fn test_parse_mcp_servers() {
+        let targets = Config::default().targets;
+        let servers = parse_mcp_servers(
+            "[servers.github]\ncommand = \"npx\"\nargs = [\"-y\", \"gh\"]\nenv = { TOKEN = \"t\" }\nagents = [\"claude\"]\n\n[servers.docs]\nurl = \"https://docs\"\nenabled = false\n",
+            &targets,
+        )
+        .unwrap();
+        assert_eq!(servers[0].name, "docs");
+        assert!(!servers[0].applies_to(AgentName::Claude));
+        assert_eq!(servers[1].env, vec![("TOKEN".to_string(), "t".to_string())]);
+        assert!(servers[1].applies_to(AgentName::Claude));
+        assert!(!servers[1].applies_to(AgentName::Codex));
+        assert!(!servers[1].has_env_refs());
+        let mut secret = McpServer::remote("api", "https://api");
+        secret.headers = vec![("Authorization".into(), "Bearer ${env:API_TOKEN}".into())];
+        assert!(secret.has_env_refs());
+
+        assert!(parse_mcp_servers("[servers.x]\nargs = []\n", &targets).is_err());
+        assert!(
+            parse_mcp_servers("[servers.x]\ncommand = \"a\"\nurl = \"b\"\n", &targets).is_err()
+        );
+        assert!(
+            parse_mcp_servers(
+                "[servers.x]\ncommand = \"a\"\nagents = [\"vim\"]\n",
+                &targets
+            )
+            .is_err()
+        );
+
+        assert!(Config::default().targets[&AgentName::Pi].mcp.is_none());
+        let config = Config::parse("[target.claude]\nmcp = false\n").unwrap();
+        assert!(config.targets[&AgentName::Claude].mcp.is_none());
+        assert!(Config::parse("[target.pi.mcp]\npath = \"pi.json\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let toml = r#"
+[profile.review]
+skills = ["review-*", "lint"]
+instructions = "AGENTS.review.md"
+
+[profile.oncall]
+"#;
+        let mut config = Config::parse(toml).unwrap();
+        assert_eq!(
+            config.profiles.keys().collect::<Vec<_>>(),
+            vec!["oncall", "review"]
+        );
+        assert!(config.profile_allows("deploy"));
+
+        config.apply_profile("review").unwrap();
+        assert!(config.profile_allows("review-pr"));
+        assert!(!config.profile_allows("deploy"));
+        assert_eq!(config.source_instruction_path(false), "AGENTS.review.md");
+        assert_eq!(config.configured_instruction_path(false), "AGENTS.md");
+
+        config.apply_profile("oncall").unwrap();
+        assert!(config.profile_allows("deploy"));
+        assert_eq!(config.source_instruction_path(false), "AGENTS.md");
+        assert!(matches!(
+            config.apply_profile("nope"),
+            Err(ConfigError::UnknownProfile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve_workspace_repos() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        for repo in ["work/api", "work/web"] {
+            fs::create_dir_all(tmp.path().join(repo).join(".agents")).unwrap();
+            fs::write(tmp.path().join(repo).join(".agents/hana.toml"), "").unwrap();
+        }
+        fs::create_dir_all(tmp.path().join("work/scratch")).unwrap();
+        let config =
+            Config::parse("[workspace]\nrepos = [\"work/*\", \"work/api\", \"solo\"]\n").unwrap();
+
+        assert_eq!(
+            config.resolve_workspace_repos(tmp.path()),
+            vec![
+                tmp.path().join("work/api"),
+                tmp.path().join("work/web"),
+                tmp.path().join("solo")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_link_style() {
+        assert_eq!(Config::default().source.link_style, LinkStyle::Relative);
+        let config = Config::parse("[source]\nlink_style = \"absolute\"\n").unwrap();
+        assert_eq!(config.source.link_style, LinkStyle::Absolute);
+        assert!(Config::parse("[source]\nlink_style = \"hard\"\n").is_err());
+    }
+
     #[test]
     fn test_parse_invalid_toml() {
         let result = Config::parse("not valid [[[toml");
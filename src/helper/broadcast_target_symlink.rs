@@ -2,8 +2,8 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::config::AgentName;
-use crate::helper::relative_path::relative_path;
+use crate::config::{AgentName, LinkStyle};
+use crate::helper::link_target::link_target;
 
 #[derive(Debug, Default)]
 pub struct BroadcastOk {
@@ -17,10 +17,12 @@ pub struct BroadcastErr {
     pub failed: Vec<(AgentName, String)>,
 }
 
-/// Broadcast a single source as symlinks to multiple target directories.
+/// Broadcast a single source as symlinks named `link_name` to multiple target directories.
 pub fn broadcast_target_symlink(
     source: &Path,
+    link_name: &str,
     dest_dirs: &HashMap<AgentName, PathBuf>,
+    style: LinkStyle,
     dry_run: bool,
     force: bool,
 ) -> Result<BroadcastOk, BroadcastErr> {
@@ -28,24 +30,9 @@ pub fn broadcast_target_symlink(
     let mut conflicts = Vec::new();
     let mut failed = Vec::new();
 
-    let Some(source_name) = source.file_name() else {
-        failed.extend(
-            dest_dirs
-                .keys()
-                .copied()
-                .map(|agent| (agent, "cannot determine source name".to_string())),
-        );
-        failed.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
-        return Err(BroadcastErr {
-            linked,
-            conflicts,
-            failed,
-        });
-    };
-
     for (agent, dest_dir) in dest_dirs {
-        let dest = dest_dir.join(source_name);
-        match link_one(source, &dest, dry_run, force) {
+        let dest = dest_dir.join(link_name);
+        match link_one(source, &dest, style, dry_run, force) {
             LinkOutcome::Created => linked.push(*agent),
             LinkOutcome::AlreadyValid => {}
             LinkOutcome::Conflict => conflicts.push(*agent),
@@ -75,17 +62,17 @@ pub enum LinkOutcome {
     Failed(String),
 }
 
-pub fn link_one(source: &Path, dest: &Path, dry_run: bool, force: bool) -> LinkOutcome {
-    let rel_source = dest
-        .parent()
-        .map(|parent| relative_path(parent, source))
-        .unwrap_or_else(|| source.to_path_buf());
-
-    // Already a valid symlink — skip
-    if dest.is_symlink()
-        && let Ok(target) = fs::read_link(dest)
-        && target == rel_source
-    {
+pub fn link_one(
+    source: &Path,
+    dest: &Path,
+    style: LinkStyle,
+    dry_run: bool,
+    force: bool,
+) -> LinkOutcome {
+    let rel_source = link_target(dest, source, style);
+
+    // Already a symlink resolving to the source — skip (`hana repair` normalizes the style)
+    if dest.is_symlink() && points_to(dest, source) {
         return LinkOutcome::AlreadyValid;
     }
 
@@ -120,9 +107,18 @@ pub fn link_one(source: &Path, dest: &Path, dry_run: bool, force: bool) -> LinkO
     LinkOutcome::Created
 }
 
+/// Whether the symlink at `link` resolves to `source`.
+pub fn points_to(link: &Path, source: &Path) -> bool {
+    match (fs::canonicalize(link), fs::canonicalize(source)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::helper::relative_path::relative_path;
     use tempfile::TempDir;
 
     #[test]
@@ -141,7 +137,15 @@ mod tests {
             fs::create_dir_all(d).unwrap();
         }
 
-        let result = broadcast_target_symlink(&source, &dests, false, false).unwrap();
+        let result = broadcast_target_symlink(
+            &source,
+            "skill-a",
+            &dests,
+            LinkStyle::Relative,
+            false,
+            false,
+        )
+        .unwrap();
 
         assert_eq!(result.linked.len(), 2);
         assert!(result.linked.contains(&AgentName::Claude));
@@ -175,9 +179,42 @@ mod tests {
         std::os::unix::fs::symlink(&rel, dest_dir.join("skill-a")).unwrap();
 
         let dests = HashMap::from([(AgentName::Claude, dest_dir)]);
-        let result = broadcast_target_symlink(&source, &dests, false, false).unwrap();
+        let result = broadcast_target_symlink(
+            &source,
+            "skill-a",
+            &dests,
+            LinkStyle::Relative,
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert!(result.linked.is_empty());
+    }
+
+    #[test]
+    fn test_absolute_link_to_source_is_valid() {
+        let tmp = TempDir::new().unwrap();
+        let source = tmp.path().join("source/skill-a");
+        fs::create_dir_all(&source).unwrap();
+
+        let dest_dir = tmp.path().join("agent1");
+        fs::create_dir_all(&dest_dir).unwrap();
+        std::os::unix::fs::symlink(&source, dest_dir.join("skill-a")).unwrap();
+
+        let dests = HashMap::from([(AgentName::Claude, dest_dir.clone())]);
+        let result = broadcast_target_symlink(
+            &source,
+            "skill-a",
+            &dests,
+            LinkStyle::Relative,
+            false,
+            false,
+        )
+        .unwrap();
 
         assert!(result.linked.is_empty());
+        assert_eq!(fs::read_link(dest_dir.join("skill-a")).unwrap(), source);
     }
 
     #[test]
@@ -190,7 +227,15 @@ mod tests {
         fs::create_dir_all(dest_dir.join("skill-a")).unwrap();
 
         let dests = HashMap::from([(AgentName::Claude, dest_dir.clone())]);
-        let err = broadcast_target_symlink(&source, &dests, false, false).unwrap_err();
+        let err = broadcast_target_symlink(
+            &source,
+            "skill-a",
+            &dests,
+            LinkStyle::Relative,
+            false,
+            false,
+        )
+        .unwrap_err();
 
         assert!(err.linked.is_empty());
         assert_eq!(err.conflicts, vec![AgentName::Claude]);
@@ -207,7 +252,9 @@ mod tests {
         fs::create_dir_all(dest_dir.join("skill-a")).unwrap();
 
         let dests = HashMap::from([(AgentName::Claude, dest_dir.clone())]);
-        let result = broadcast_target_symlink(&source, &dests, false, true).unwrap();
+        let result =
+            broadcast_target_symlink(&source, "skill-a", &dests, LinkStyle::Relative, false, true)
+                .unwrap();
 
         assert_eq!(result.linked, vec![AgentName::Claude]);
         assert!(dest_dir.join("skill-a").is_symlink());
@@ -233,7 +280,9 @@ mod tests {
         fs::write(dest_dir.join("skill-a"), "existing").unwrap();
 
         let dests = HashMap::from([(AgentName::Claude, dest_dir.clone())]);
-        let result = broadcast_target_symlink(&source, &dests, false, true).unwrap();
+        let result =
+            broadcast_target_symlink(&source, "skill-a", &dests, LinkStyle::Relative, false, true)
+                .unwrap();
 
         assert_eq!(result.linked, vec![AgentName::Claude]);
         assert!(dest_dir.join("skill-a").is_symlink());
@@ -249,7 +298,9 @@ mod tests {
         fs::create_dir_all(&dest_dir).unwrap();
 
         let dests = HashMap::from([(AgentName::Claude, dest_dir.clone())]);
-        let result = broadcast_target_symlink(&source, &dests, true, false).unwrap();
+        let result =
+            broadcast_target_symlink(&source, "skill-a", &dests, LinkStyle::Relative, true, false)
+                .unwrap();
 
         assert_eq!(result.linked, vec![AgentName::Claude]);
         assert!(!dest_dir.join("skill-a").exists());
@@ -268,7 +319,15 @@ mod tests {
         std::os::unix::fs::symlink(&wrong, dest_dir.join("skill-a")).unwrap();
 
         let dests = HashMap::from([(AgentName::Claude, dest_dir.clone())]);
-        let result = broadcast_target_symlink(&source, &dests, false, false).unwrap();
+        let result = broadcast_target_symlink(
+            &source,
+            "skill-a",
+            &dests,
+            LinkStyle::Relative,
+            false,
+            false,
+        )
+        .unwrap();
 
         assert_eq!(result.linked, vec![AgentName::Claude]);
         assert_eq!(
@@ -291,12 +350,46 @@ mod tests {
         let dest_dir = tmp.path().join("deep/nested/agent");
 
         let dests = HashMap::from([(AgentName::Claude, dest_dir.clone())]);
-        let result = broadcast_target_symlink(&source, &dests, false, false).unwrap();
+        let result = broadcast_target_symlink(
+            &source,
+            "skill-a",
+            &dests,
+            LinkStyle::Relative,
+            false,
+            false,
+        )
+        .unwrap();
 
         assert_eq!(result.linked, vec![AgentName::Claude]);
         assert!(dest_dir.join("skill-a").is_symlink());
     }
 
+    #[test]
+    fn test_broadcast_with_custom_link_name() {
+        let tmp = TempDir::new().unwrap();
+        let source = tmp.path().join("source/frontend/react-review");
+        fs::create_dir_all(&source).unwrap();
+
+        let dest_dir = tmp.path().join("agent1");
+        let dests = HashMap::from([(AgentName::Claude, dest_dir.clone())]);
+        let result = broadcast_target_symlink(
+            &source,
+            "frontend--react-review",
+            &dests,
+            LinkStyle::Relative,
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result.linked, vec![AgentName::Claude]);
+        assert_eq!(
+            fs::canonicalize(dest_dir.join("frontend--react-review")).unwrap(),
+            fs::canonicalize(&source).unwrap()
+        );
+        assert!(!dest_dir.join("react-review").exists());
+    }
+
     #[test]
     fn test_partial_success_returns_err() {
         let tmp = TempDir::new().unwrap();
@@ -314,7 +407,15 @@ mod tests {
             (AgentName::Pi, conflict_dir.clone()),
         ]);
 
-        let err = broadcast_target_symlink(&source, &dests, false, false).unwrap_err();
+        let err = broadcast_target_symlink(
+            &source,
+            "skill-a",
+            &dests,
+            LinkStyle::Relative,
+            false,
+            false,
+        )
+        .unwrap_err();
 
         assert_eq!(err.linked, vec![AgentName::Claude]);
         assert_eq!(err.conflicts, vec![AgentName::Pi]);
@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::AgentName;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TargetEntryKind {
+    /// Symlink into the source skills directory
+    Managed,
+    /// Real directory that is not (yet) in the source
+    OrphanDir,
+    /// Symlink that resolves outside the source (plugin skill, another repo, …)
+    ForeignSymlink,
+    /// Symlink whose target does not exist
+    Broken,
+    /// Plain file
+    File,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TargetEntry {
+    pub agent: AgentName,
+    pub name: String,
+    pub path: PathBuf,
+    pub kind: TargetEntryKind,
+}
+
+/// Classify every entry in each agent's skills directory relative to the source directory.
+pub fn classify_target_entries(
+    source_dir: &Path,
+    target_dirs: &[(AgentName, PathBuf)],
+) -> Vec<TargetEntry> {
+    let source_canonical = fs::canonicalize(source_dir).ok();
+
+    let mut entries: Vec<TargetEntry> = target_dirs
+        .iter()
+        .flat_map(|(agent, dir)| {
+            fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e.ok())
+                .map(|e| {
+                    (
+                        *agent,
+                        e.file_name().to_string_lossy().to_string(),
+                        e.path(),
+                    )
+                })
+        })
+        .map(|(agent, name, path)| {
+            let kind = classify(&path, source_canonical.as_deref());
+            TargetEntry {
+                agent,
+                name,
+                path,
+                kind,
+            }
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        a.agent
+            .as_str()
+            .cmp(b.agent.as_str())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    entries
+}
+
+fn classify(path: &Path, source_canonical: Option<&Path>) -> TargetEntryKind {
+    if path.is_symlink() {
+        match fs::canonicalize(path) {
+            Err(_) => TargetEntryKind::Broken,
+            Ok(resolved) => match source_canonical {
+                Some(source) if resolved.starts_with(source) => TargetEntryKind::Managed,
+                _ => TargetEntryKind::ForeignSymlink,
+            },
+        }
+    } else if path.is_dir() {
+        TargetEntryKind::OrphanDir
+    } else {
+        TargetEntryKind::File
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_classifies_each_entry_kind() {
+        let tmp = TempDir::new().unwrap();
+        let source = tmp.path().join(".agents/skills");
+        fs::create_dir_all(source.join("my-skill")).unwrap();
+        let plugin = tmp.path().join("plugin/skill");
+        fs::create_dir_all(&plugin).unwrap();
+
+        let claude = tmp.path().join(".claude/skills");
+        fs::create_dir_all(claude.join("real")).unwrap();
+        symlink("../../.agents/skills/my-skill", claude.join("my-skill")).unwrap();
+        symlink(&plugin, claude.join("plugin-skill")).unwrap();
+        symlink("/nonexistent", claude.join("gone")).unwrap();
+        fs::write(claude.join("notes.txt"), "x").unwrap();
+
+        let entries = classify_target_entries(&source, &[(AgentName::Claude, claude)]);
+        let kinds: Vec<(&str, TargetEntryKind)> =
+            entries.iter().map(|e| (e.name.as_str(), e.kind)).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                ("gone", TargetEntryKind::Broken),
+                ("my-skill", TargetEntryKind::Managed),
+                ("notes.txt", TargetEntryKind::File),
+                ("plugin-skill", TargetEntryKind::ForeignSymlink),
+                ("real", TargetEntryKind::OrphanDir),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_target_dir_yields_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let entries = classify_target_entries(
+            &tmp.path().join("src"),
+            &[(AgentName::Pi, tmp.path().join("missing"))],
+        );
+        assert!(entries.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, MappingKind, TargetFeature};
+use crate::helper::broadcast_target_symlink::points_to;
+use crate::helper::classify_target_entries::{TargetEntryKind, classify_target_entries};
+use crate::helper::list_dir_entries::list_dir_entries;
+use crate::helper::render_instructions::parse_generated;
+use crate::helper::resolve_managed_files::resolve_managed_files;
+use crate::helper::resolve_target_destinations::resolve_target_destinations;
+
+/// Every path hana manages right now: skill links into the source, plus instruction links
+/// resolving to the source instruction file or generated instruction files, plus
+/// `[target.x] files`, generated subagent and command files, and `[[mapping]]` links. Sorted absolute paths.
+pub fn collect_managed_links(config: &Config, base_dir: &Path, global: bool) -> Vec<PathBuf> {
+    let source_dir = config.resolve_source_skills_path(base_dir, global);
+    let skill_dirs: Vec<_> =
+        resolve_target_destinations(config, base_dir, global, TargetFeature::Skills)
+            .into_iter()
+            .collect();
+    let skill_links = classify_target_entries(&source_dir, &skill_dirs)
+        .into_iter()
+        .filter(|entry| entry.kind == TargetEntryKind::Managed)
+        .map(|entry| entry.path);
+
+    let instruction_source = config.resolve_source_instruction_path(base_dir, global);
+    let instruction_links =
+        resolve_target_destinations(config, base_dir, global, TargetFeature::Instructions)
+            .into_values()
+            .filter(|dest| {
+                if dest.is_symlink() {
+                    points_to(dest, &instruction_source)
+                } else {
+                    fs::read_to_string(dest)
+                        .map(|content| parse_generated(&content).is_some())
+                        .unwrap_or(false)
+                }
+            });
+
+    let file_links = resolve_managed_files(config, base_dir, global)
+        .into_iter()
+        .filter(|file| file.dest.is_symlink() && points_to(&file.dest, &file.source))
+        .map(|file| file.dest);
+
+    let rendered_files = config
+        .resolve_subagent_targets(base_dir, global)
+        .into_iter()
+        .chain(config.resolve_command_targets(base_dir, global))
+        .filter_map(|(_, dest_dir, _)| dest_dir)
+        .flat_map(|dest_dir| list_dir_entries(&dest_dir, true))
+        .filter(|(_, path)| {
+            fs::read_to_string(path)
+                .map(|content| parse_generated(&content).is_some())
+                .unwrap_or(false)
+        })
+        .map(|(_, path)| path);
+
+    let mapping_links = config.mappings.iter().flat_map(|mapping| {
+        let source = mapping.resolve_source(base_dir);
+        config
+            .resolve_mapping_dests(mapping, base_dir)
+            .into_values()
+            .flat_map(|dest| match mapping.kind {
+                MappingKind::Dir => list_dir_entries(&source, false)
+                    .into_iter()
+                    .map(|(name, target)| (dest.join(name), target))
+                    .collect::<Vec<_>>(),
+                MappingKind::File => vec![(dest, source.clone())],
+            })
+            .filter(|(link, target)| link.is_symlink() && points_to(link, target))
+            .map(|(link, _)| link)
+            .collect::<Vec<_>>()
+    });
+
+    let mut links: Vec<PathBuf> = skill_links
+        .chain(instruction_links)
+        .chain(file_links)
+        .chain(rendered_files)
+        .chain(mapping_links)
+        .collect();
+    links.sort();
+    links.dedup();
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collects_skill_and_instruction_links() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/skills/my-skill")).unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "# Instructions").unwrap();
+        crate::sync::run(&Config::default(), tmp.path(), &Default::default());
+
+        let plugin = tmp.path().join("plugin");
+        fs::create_dir_all(&plugin).unwrap();
+        symlink(&plugin, tmp.path().join(".claude/skills/foreign")).unwrap();
+
+        let links = collect_managed_links(&Config::default(), tmp.path(), false);
+        assert_eq!(
+            links,
+            vec![
+                tmp.path().join(".claude/skills/my-skill"),
+                tmp.path().join(".opencode/skills/my-skill"),
+                tmp.path().join(".pi/skills/my-skill"),
+                tmp.path().join("CLAUDE.md"),
+            ]
+        );
+    }
+}
@@ -1,21 +1,201 @@
 use std::fs;
 use std::path::Path;
 
+use crate::config::SkillFilter;
+use crate::helper::load_ignore_rules::IgnoreRules;
 use crate::sync::SyncWarning;
 
-pub fn collect_source_skills(source_dir: &Path) -> Result<Vec<String>, SyncWarning> {
+/// List skill ids in the source directory.
+/// With `namespaced`, a directory without SKILL.md whose children hold skills is a
+/// category, and its skills are returned as `<category>/<skill>`.
+pub fn collect_source_skills(
+    source_dir: &Path,
+    filter: &SkillFilter,
+    ignore: &IgnoreRules,
+    namespaced: bool,
+) -> Result<Vec<String>, SyncWarning> {
     if !source_dir.exists() {
         return Ok(vec![]);
     }
-    match fs::read_dir(source_dir) {
-        Ok(entries) => Ok(entries
+    let entries = match fs::read_dir(source_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return Err(SyncWarning::IoFailed {
+                operation: format!("read source skills ({})", source_dir.display()),
+                detail: e.to_string(),
+            });
+        }
+    };
+
+    let mut skills = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !path.is_dir() || name.starts_with('.') || ignore.is_ignored(&name, true) {
+            continue;
+        }
+
+        if namespaced && is_category(&path) {
+            // Children go through the same checks as top-level skills, keyed by `<category>/<skill>`
+            let children = fs::read_dir(&path)
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().join("SKILL.md").is_file())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|leaf| !leaf.starts_with('.'))
+                .map(|leaf| format!("{name}/{leaf}"))
+                .filter(|id| filter.matches(id) && !ignore.is_ignored(id, true));
+            skills.extend(children);
+        } else if filter.matches(&name) {
+            skills.push(name);
+        }
+    }
+    Ok(skills)
+}
+
+/// A category is a directory that is not a skill itself but contains skills.
+fn is_category(dir: &Path) -> bool {
+    !dir.join("SKILL.md").exists()
+        && fs::read_dir(dir)
+            .into_iter()
+            .flatten()
             .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_dir())
-            .map(|e| e.file_name().to_string_lossy().to_string())
-            .collect()),
-        Err(e) => Err(SyncWarning::IoFailed {
-            operation: format!("read source skills ({})", source_dir.display()),
-            detail: e.to_string(),
-        }),
+            .any(|e| e.path().join("SKILL.md").is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collect_source_skills_applies_filter() {
+        let tmp = TempDir::new().unwrap();
+        for dir in ["team-review", "wip-draft", ".git", "node_modules"] {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+        }
+        fs::write(tmp.path().join("README.md"), "not a skill").unwrap();
+
+        let filter = SkillFilter {
+            include: vec![],
+            exclude: vec!["wip-*".to_string(), "node_modules".to_string()],
+        };
+        let skills =
+            collect_source_skills(tmp.path(), &filter, &IgnoreRules::default(), false).unwrap();
+
+        assert_eq!(skills, vec!["team-review".to_string()]);
+    }
+
+    #[test]
+    fn test_collect_source_skills_applies_ignore_rules() {
+        let tmp = TempDir::new().unwrap();
+        for dir in ["my-skill", "node_modules", "scratch"] {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+        }
+
+        let ignore = IgnoreRules::parse("node_modules/\nscratch\n");
+        let mut skills =
+            collect_source_skills(tmp.path(), &SkillFilter::default(), &ignore, false).unwrap();
+        skills.sort();
+
+        assert_eq!(skills, vec!["my-skill".to_string()]);
+    }
+
+    #[test]
+    fn test_collect_source_skills_namespaced() {
+        let tmp = TempDir::new().unwrap();
+        for dir in ["frontend/react-review", "frontend/css-lint", "plain"] {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+            fs::write(tmp.path().join(dir).join("SKILL.md"), "# Skill").unwrap();
+        }
+        fs::create_dir_all(tmp.path().join("plain/scripts")).unwrap();
+
+        let mut skills = collect_source_skills(
+            tmp.path(),
+            &SkillFilter::default(),
+            &IgnoreRules::default(),
+            true,
+        )
+        .unwrap();
+        skills.sort();
+        assert_eq!(
+            skills,
+            vec![
+                "frontend/css-lint".to_string(),
+                "frontend/react-review".to_string(),
+                "plain".to_string(),
+            ]
+        );
+
+        let flat = collect_source_skills(
+            tmp.path(),
+            &SkillFilter::default(),
+            &IgnoreRules::default(),
+            false,
+        )
+        .unwrap();
+        assert!(flat.contains(&"frontend".to_string()));
+    }
+
+    fn namespaced_tree() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        for dir in [
+            "frontend/react-review",
+            "frontend/.draft",
+            "backend/api-lint",
+        ] {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+            fs::write(tmp.path().join(dir).join("SKILL.md"), "# Skill").unwrap();
+        }
+        fs::create_dir_all(tmp.path().join("frontend/assets")).unwrap();
+        tmp
+    }
+
+    #[test]
+    fn test_collect_source_skills_namespaced_skips_hidden_children() {
+        let tmp = namespaced_tree();
+        let skills = collect_source_skills(
+            tmp.path(),
+            &SkillFilter::default(),
+            &IgnoreRules::default(),
+            true,
+        )
+        .unwrap();
+        assert!(!skills.iter().any(|s| s.contains(".draft")));
+    }
+
+    #[test]
+    fn test_collect_source_skills_namespaced_requires_skill_md() {
+        let tmp = namespaced_tree();
+        let skills = collect_source_skills(
+            tmp.path(),
+            &SkillFilter::default(),
+            &IgnoreRules::default(),
+            true,
+        )
+        .unwrap();
+        assert!(!skills.contains(&"frontend/assets".to_string()));
+        assert!(skills.contains(&"frontend/react-review".to_string()));
+    }
+
+    #[test]
+    fn test_collect_source_skills_namespaced_include_matches_id() {
+        let tmp = namespaced_tree();
+        let by_category = SkillFilter {
+            include: vec!["frontend/*".to_string()],
+            exclude: vec![],
+        };
+        let skills =
+            collect_source_skills(tmp.path(), &by_category, &IgnoreRules::default(), true).unwrap();
+        assert_eq!(skills, vec!["frontend/react-review".to_string()]);
+
+        let by_leaf = SkillFilter {
+            include: vec!["react-*".to_string()],
+            exclude: vec![],
+        };
+        let skills =
+            collect_source_skills(tmp.path(), &by_leaf, &IgnoreRules::default(), true).unwrap();
+        assert!(skills.is_empty());
     }
 }
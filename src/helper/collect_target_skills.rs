@@ -3,6 +3,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::config::{AgentName, Config, TargetFeature};
+use crate::helper::load_ignore_rules::load_ignore_rules;
 use crate::helper::resolve_target_destinations::resolve_target_destinations;
 
 pub fn collect_target_skills(
@@ -10,21 +11,27 @@ pub fn collect_target_skills(
     base_dir: &Path,
     global: bool,
 ) -> HashMap<AgentName, Vec<(String, PathBuf)>> {
+    let ignore = load_ignore_rules(&config.resolve_ignore_path(base_dir));
+
     resolve_target_destinations(config, base_dir, global, TargetFeature::Skills)
         .into_iter()
         .map(|(agent, agent_dir)| {
+            let filter = config.targets.get(&agent).map(|t| &t.filter);
             let skills = fs::read_dir(&agent_dir)
                 .ok()
                 .into_iter()
                 .flatten()
                 .filter_map(|e| e.ok())
                 .map(|entry| {
-                    (
-                        entry.file_name().to_string_lossy().to_string(),
-                        entry.path(),
-                    )
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    (config.skill_id_from_link_name(&name), entry.path())
                 })
                 .filter(|(_, path)| path.is_dir() && !path.is_symlink())
+                .filter(|(name, _)| {
+                    config.source.filter.matches(name)
+                        && filter.map(|f| f.matches(name)).unwrap_or(true)
+                        && !ignore.is_ignored(name, true)
+                })
                 .collect::<Vec<_>>();
             (agent, skills)
         })
@@ -41,7 +48,7 @@ mod tests {
     fn test_collect_skills_filters_non_dirs_symlinks_and_disabled_targets() {
         let tmp = TempDir::new().unwrap();
         let mut config = Config::default();
-        config.targets.get_mut("pi").unwrap().skills = false;
+        config.targets.get_mut(&AgentName::Pi).unwrap().skills = false;
 
         let claude_dir = tmp.path().join(".claude/skills");
         fs::create_dir_all(claude_dir.join("real-skill")).unwrap();
@@ -64,6 +71,76 @@ mod tests {
         assert_eq!(claude_skills[0].0, "real-skill");
     }
 
+    #[test]
+    fn test_collect_skills_applies_source_and_target_filters() {
+        let tmp = TempDir::new().unwrap();
+        let config = Config::parse(
+            r#"
+[source]
+exclude = ["wip-*"]
+
+[target.claude]
+exclude = ["tmp*"]
+"#,
+        )
+        .unwrap();
+
+        let claude_dir = tmp.path().join(".claude/skills");
+        for dir in ["keep", "wip-draft", "tmp123", ".cache"] {
+            fs::create_dir_all(claude_dir.join(dir)).unwrap();
+        }
+        fs::create_dir_all(tmp.path().join(".pi/skills/tmp-pi")).unwrap();
+
+        let result = collect_target_skills(&config, tmp.path(), false);
+
+        let claude: Vec<&str> = result[&AgentName::Claude]
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(claude, vec!["keep"]);
+        let pi: Vec<&str> = result[&AgentName::Pi]
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(pi, vec!["tmp-pi"]);
+    }
+
+    #[test]
+    fn test_collect_skills_respects_hanaignore() {
+        let tmp = TempDir::new().unwrap();
+        let config = Config::default();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(tmp.path().join(".agents/.hanaignore"), "claude-private-*\n").unwrap();
+
+        let claude_dir = tmp.path().join(".claude/skills");
+        fs::create_dir_all(claude_dir.join("claude-private-memory")).unwrap();
+        fs::create_dir_all(claude_dir.join("shared")).unwrap();
+
+        let result = collect_target_skills(&config, tmp.path(), false);
+
+        let claude: Vec<&str> = result[&AgentName::Claude]
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(claude, vec!["shared"]);
+    }
+
+    #[test]
+    fn test_collect_skills_maps_flattened_names_to_ids() {
+        let tmp = TempDir::new().unwrap();
+        let config = Config::parse("[source]\nnamespaces = true\n").unwrap();
+        fs::create_dir_all(tmp.path().join(".claude/skills/frontend--react-review")).unwrap();
+
+        let result = collect_target_skills(&config, tmp.path(), false);
+
+        let (id, path) = &result[&AgentName::Claude][0];
+        assert_eq!(id, "frontend/react-review");
+        assert_eq!(
+            path,
+            &tmp.path().join(".claude/skills/frontend--react-review")
+        );
+    }
+
     #[test]
     fn test_collect_skills_codex_excluded_same_as_source() {
         let tmp = TempDir::new().unwrap();
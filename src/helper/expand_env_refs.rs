@@ -0,0 +1,87 @@
+/// How an agent config file spells an environment variable reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EnvSyntax {
+    /// `${NAME}` (Claude Code)
+    Dollar,
+    /// `{env:NAME}` (OpenCode)
+    Braced,
+    /// The agent cannot expand references; the value is written in
+    Resolve,
+}
+
+/// Rewrite every `${env:NAME}` in `text` for the target syntax. With `Resolve`, values come from
+/// `lookup`; the error is the first name it cannot find. Values never appear in the error.
+pub fn expand_env_refs(
+    text: &str,
+    syntax: EnvSyntax,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    const OPEN: &str = "${env:";
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(OPEN) {
+        let after = &rest[start + OPEN.len()..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        let name = &after[..end];
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        out.push_str(&rest[..start]);
+        if !valid {
+            out.push_str(OPEN);
+            rest = after;
+            continue;
+        }
+        match syntax {
+            EnvSyntax::Dollar => out.push_str(&format!("${{{name}}}")),
+            EnvSyntax::Braced => out.push_str(&format!("{{env:{name}}}")),
+            EnvSyntax::Resolve => out.push_str(&lookup(name).ok_or_else(|| name.to_string())?),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        (name == "TOKEN").then(|| "s3cret".to_string())
+    }
+
+    #[test]
+    fn test_rewrites_per_syntax() {
+        let text = "Bearer ${env:TOKEN} for $HOME";
+        assert_eq!(
+            expand_env_refs(text, EnvSyntax::Dollar, &lookup).unwrap(),
+            "Bearer ${TOKEN} for $HOME"
+        );
+        assert_eq!(
+            expand_env_refs(text, EnvSyntax::Braced, &lookup).unwrap(),
+            "Bearer {env:TOKEN} for $HOME"
+        );
+        assert_eq!(
+            expand_env_refs(text, EnvSyntax::Resolve, &lookup).unwrap(),
+            "Bearer s3cret for $HOME"
+        );
+    }
+
+    #[test]
+    fn test_missing_and_malformed_refs() {
+        assert_eq!(
+            expand_env_refs("${env:NOPE}", EnvSyntax::Resolve, &lookup),
+            Err("NOPE".to_string())
+        );
+        assert_eq!(
+            expand_env_refs("${env:1X} ${env:open", EnvSyntax::Resolve, &lookup).unwrap(),
+            "${env:1X} ${env:open"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Result of expanding include directives.
+#[derive(Debug, Default, PartialEq)]
+pub struct Expanded {
+    pub content: String,
+    /// Include targets that could not be read (their lines are left untouched)
+    pub missing: Vec<String>,
+}
+
+/// The include path on this line, if it is a directive. `@path` lines only count with `at_imports`.
+fn include_target(line: &str, at_imports: bool) -> Option<&str> {
+    let trimmed = line.trim();
+    if let Some(path) = trimmed
+        .strip_prefix("<!--")
+        .and_then(|rest| rest.strip_suffix("-->"))
+        .map(str::trim)
+        .and_then(|rest| rest.strip_prefix("hana:include "))
+    {
+        return Some(path.trim());
+    }
+    if at_imports {
+        let path = trimmed.strip_prefix('@')?;
+        if !path.is_empty() && !path.contains(char::is_whitespace) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Whether `content` has any directive `expand_includes` would act on.
+pub fn has_includes(content: &str, at_imports: bool) -> bool {
+    content
+        .lines()
+        .any(|line| include_target(line, at_imports).is_some())
+}
+
+/// Replace `<!-- hana:include path -->` lines (and, with `at_imports`, lines that are only
+/// `@path`) with the referenced file, recursively. Paths are relative to the including file's
+/// directory; `~/` is the home directory. Cycles are left unexpanded.
+pub fn expand_includes(content: &str, dir: &Path, at_imports: bool) -> Expanded {
+    let mut missing = Vec::new();
+    let content = expand(content, dir, at_imports, &mut vec![], &mut missing);
+    Expanded { content, missing }
+}
+
+fn expand(
+    content: &str,
+    dir: &Path,
+    at_imports: bool,
+    stack: &mut Vec<PathBuf>,
+    missing: &mut Vec<String>,
+) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let Some(target) = include_target(line, at_imports) else {
+            out.push(line.to_string());
+            continue;
+        };
+        let path = resolve(dir, target);
+        let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        let included = match fs::read_to_string(&path) {
+            Ok(text) if !stack.contains(&canonical) => text,
+            Ok(_) => {
+                out.push(line.to_string());
+                continue;
+            }
+            Err(_) => {
+                // `@name` that is not a file is most likely prose (a mention), not an import
+                if line.trim_start().starts_with("<!--") {
+                    missing.push(target.to_string());
+                }
+                out.push(line.to_string());
+                continue;
+            }
+        };
+
+        stack.push(canonical);
+        let parent = path.parent().unwrap_or(dir).to_path_buf();
+        let expanded = expand(&included, &parent, at_imports, stack, missing);
+        stack.pop();
+        out.push(expanded.trim_end().to_string());
+    }
+
+    let mut joined = out.join("\n");
+    if content.ends_with('\n') {
+        joined.push('\n');
+    }
+    joined
+}
+
+fn resolve(dir: &Path, target: &str) -> PathBuf {
+    if let Some(rest) = target.strip_prefix("~/")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest);
+    }
+    dir.join(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_expands_comment_directive_recursively() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("docs")).unwrap();
+        fs::write(
+            tmp.path().join("docs/style.md"),
+            "## Style\n<!-- hana:include nested.md -->\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("docs/nested.md"), "Nested rule.\n").unwrap();
+
+        let result = expand_includes(
+            "# Base\n<!-- hana:include docs/style.md -->\nEnd.\n",
+            tmp.path(),
+            false,
+        );
+        assert_eq!(result.content, "# Base\n## Style\nNested rule.\nEnd.\n");
+        assert!(result.missing.is_empty());
+    }
+
+    #[test]
+    fn test_at_imports_only_when_requested() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("rules.md"), "Rule.\n").unwrap();
+        let source = "# Base\n@rules.md\nPing @alice about it.\n@not-a-file\n";
+
+        let kept = expand_includes(source, tmp.path(), false);
+        assert_eq!(kept.content, source);
+        assert!(!has_includes(source, false));
+
+        let expanded = expand_includes(source, tmp.path(), true);
+        assert_eq!(
+            expanded.content,
+            "# Base\nRule.\nPing @alice about it.\n@not-a-file\n"
+        );
+        assert!(expanded.missing.is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_cyclic_includes_are_left_alone() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("a.md"), "A\n<!-- hana:include b.md -->\n").unwrap();
+        fs::write(tmp.path().join("b.md"), "B\n<!-- hana:include a.md -->\n").unwrap();
+
+        let result = expand_includes(
+            "<!-- hana:include a.md -->\n<!-- hana:include gone.md -->\n",
+            tmp.path(),
+            false,
+        );
+        assert_eq!(
+            result.content,
+            "A\nB\n<!-- hana:include a.md -->\n<!-- hana:include gone.md -->\n"
+        );
+        assert_eq!(result.missing, vec!["gone.md".to_string()]);
+    }
+}
@@ -0,0 +1,58 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::helper::glob_match::glob_match;
+use crate::helper::list_dir_entries::list_dir_entries;
+
+/// Directories matching `pattern`, where any component may hold `*` or `?`. Wildcards never
+/// match hidden entries. A pattern without wildcards yields itself, existing or not.
+pub fn expand_path_glob(pattern: &Path) -> Vec<PathBuf> {
+    let mut matches = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let part = component.as_os_str().to_string_lossy();
+        let wild = matches!(component, Component::Normal(_)) && part.contains(['*', '?']);
+        matches = matches
+            .into_iter()
+            .flat_map(|dir| {
+                if wild {
+                    list_dir_entries(&dir, false)
+                        .into_iter()
+                        .filter(|(name, path)| glob_match(&part, name) && path.is_dir())
+                        .map(|(_, path)| path)
+                        .collect()
+                } else {
+                    vec![dir.join(component)]
+                }
+            })
+            .collect();
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_expands_wildcard_components() {
+        let tmp = TempDir::new().unwrap();
+        for dir in ["work/api", "work/web", "work/.cache", "other/api"] {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+        }
+        fs::write(tmp.path().join("work/notes.txt"), "").unwrap();
+
+        assert_eq!(
+            expand_path_glob(&tmp.path().join("work/*")),
+            vec![tmp.path().join("work/api"), tmp.path().join("work/web")]
+        );
+        assert_eq!(
+            expand_path_glob(&tmp.path().join("*/api")),
+            vec![tmp.path().join("other/api"), tmp.path().join("work/api")]
+        );
+        assert_eq!(
+            expand_path_glob(&tmp.path().join("missing")),
+            vec![tmp.path().join("missing")]
+        );
+    }
+}
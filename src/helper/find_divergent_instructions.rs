@@ -0,0 +1,93 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{AgentName, Config, TargetFeature};
+use crate::helper::render_instructions::parse_generated;
+use crate::helper::resolve_target_destinations::resolve_target_destinations;
+
+/// A hand-written agent instruction file whose content differs from the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivergentInstruction {
+    pub agent: AgentName,
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// Real (non-symlink, non-generated) instruction files whose content differs from the source.
+/// When the source is missing, every real file counts. Sorted by agent name, never enum order.
+pub fn find_divergent_instructions(
+    config: &Config,
+    base_dir: &Path,
+    global: bool,
+) -> Vec<DivergentInstruction> {
+    let source_path = config.resolve_source_instruction_path(base_dir, global);
+    let source = fs::read_to_string(&source_path).ok();
+    let dest_map =
+        resolve_target_destinations(config, base_dir, global, TargetFeature::Instructions);
+
+    let mut divergent: Vec<DivergentInstruction> = dest_map
+        .into_iter()
+        .filter(|(_, path)| !path.is_symlink() && path.is_file())
+        .filter_map(|(agent, path)| {
+            let content = fs::read_to_string(&path).ok()?;
+            if parse_generated(&content).is_some() || source.as_deref() == Some(content.as_str()) {
+                return None;
+            }
+            Some(DivergentInstruction {
+                agent,
+                path,
+                content,
+            })
+        })
+        .collect();
+    divergent.sort_by_key(|d| d.agent.as_str());
+    divergent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::render_instructions::generated_file;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_only_real_differing_files() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "# Source\n").unwrap();
+        fs::write(tmp.path().join("CLAUDE.md"), "# Claude edits\n").unwrap();
+        fs::write(tmp.path().join("PI.md"), "# Source\n").unwrap();
+
+        let config = Config::parse("[target.pi]\ninstruction_path = \"PI.md\"\n").unwrap();
+        let divergent = find_divergent_instructions(&config, tmp.path(), false);
+        assert_eq!(divergent.len(), 1);
+        assert_eq!(divergent[0].agent, AgentName::Claude);
+        assert_eq!(divergent[0].content, "# Claude edits\n");
+
+        fs::remove_file(tmp.path().join("CLAUDE.md")).unwrap();
+        symlink("AGENTS.md", tmp.path().join("CLAUDE.md")).unwrap();
+        assert!(find_divergent_instructions(&config, tmp.path(), false).is_empty());
+
+        fs::remove_file(tmp.path().join("CLAUDE.md")).unwrap();
+        fs::write(
+            tmp.path().join("CLAUDE.md"),
+            generated_file("AGENTS.md", "# Other\n"),
+        )
+        .unwrap();
+        assert!(find_divergent_instructions(&config, tmp.path(), false).is_empty());
+    }
+
+    #[test]
+    fn test_missing_source_counts_every_real_file() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("CLAUDE.md"), "# Same\n").unwrap();
+        fs::write(tmp.path().join("PI.md"), "# Same\n").unwrap();
+
+        let config = Config::parse("[target.pi]\ninstruction_path = \"PI.md\"\n").unwrap();
+        let agents: Vec<AgentName> = find_divergent_instructions(&config, tmp.path(), false)
+            .into_iter()
+            .map(|d| d.agent)
+            .collect();
+        assert_eq!(agents, vec![AgentName::Claude, AgentName::Pi]);
+    }
+}
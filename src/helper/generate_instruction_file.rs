@@ -0,0 +1,254 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::{AgentName, Config, InstructionMode};
+use crate::helper::expand_includes::{Expanded, expand_includes, has_includes};
+use crate::helper::render_instructions::{generated_file, parse_generated, render_instructions};
+
+pub enum GenerateOutcome {
+    Written,
+    AlreadyValid,
+    /// A hand-written file (no hana header) is in the way
+    Conflict,
+    /// The generated file was edited after hana wrote it
+    Edited,
+    Failed(String),
+}
+
+fn agent_imports(config: &Config, agent: AgentName) -> bool {
+    config
+        .targets
+        .get(&agent)
+        .map(|t| t.imports)
+        .unwrap_or(false)
+}
+
+/// Whether `agent` gets a generated file instead of a symlink: always in generate mode, and in
+/// link mode when `expand_includes` is on and the source has includes the agent cannot resolve.
+pub fn wants_generated(config: &Config, base_dir: &Path, global: bool, agent: AgentName) -> bool {
+    if config.source.instruction_mode == InstructionMode::Generate {
+        return true;
+    }
+    if !config.source.expand_includes {
+        return false;
+    }
+    let source_path = config.resolve_source_instruction_path(base_dir, global);
+    fs::read_to_string(source_path)
+        .map(|source| has_includes(&source, !agent_imports(config, agent)))
+        .unwrap_or(false)
+}
+
+/// The full file hana would write for `agent`: source with includes expanded, only-blocks
+/// resolved and the fragment appended, with header. `None` when the source cannot be read.
+pub fn expected_generated_file(
+    config: &Config,
+    base_dir: &Path,
+    global: bool,
+    agent: AgentName,
+) -> Option<Expanded> {
+    let source_path = config.resolve_source_instruction_path(base_dir, global);
+    let source = fs::read_to_string(&source_path).ok()?;
+    let source_dir = source_path.parent().unwrap_or(base_dir);
+    let fragment_path = config.resolve_fragment_path(base_dir, agent);
+    let fragment = fs::read_to_string(&fragment_path).ok();
+    let source_name = source_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let expand = |content: &str, dir: &Path| {
+        if config.source.expand_includes {
+            expand_includes(content, dir, !agent_imports(config, agent))
+        } else {
+            Expanded {
+                content: content.to_string(),
+                missing: vec![],
+            }
+        }
+    };
+    let source = expand(&source, source_dir);
+    let fragment = fragment.map(|f| expand(&f, fragment_path.parent().unwrap_or(base_dir)));
+
+    let body = render_instructions(
+        &source.content,
+        agent.as_str(),
+        fragment.as_ref().map(|f| f.content.as_str()),
+    );
+    let missing = source
+        .missing
+        .into_iter()
+        .chain(fragment.into_iter().flat_map(|f| f.missing))
+        .collect();
+    Some(Expanded {
+        content: generated_file(&source_name, &body),
+        missing,
+    })
+}
+
+/// Write a generated instruction file. Old symlinks are replaced; hand-written files and
+/// hand-edited generated files are only overwritten with `force`.
+pub fn generate_instruction_file(
+    dest: &Path,
+    content: &str,
+    dry_run: bool,
+    force: bool,
+) -> GenerateOutcome {
+    if dest.is_symlink() {
+        if !dry_run && let Err(e) = fs::remove_file(dest) {
+            return GenerateOutcome::Failed(e.to_string());
+        }
+    } else if dest.exists() {
+        let existing = match fs::read_to_string(dest) {
+            Ok(existing) => existing,
+            Err(e) => return GenerateOutcome::Failed(e.to_string()),
+        };
+        if existing == content {
+            return GenerateOutcome::AlreadyValid;
+        }
+        match parse_generated(&existing) {
+            Some(generated) if generated.is_edited() && !force => return GenerateOutcome::Edited,
+            Some(_) => {}
+            None if !force => return GenerateOutcome::Conflict,
+            None => {}
+        }
+    }
+
+    if !dry_run {
+        if let Some(parent) = dest.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                return GenerateOutcome::Failed(e.to_string());
+            }
+        }
+        if let Err(e) = fs::write(dest, content) {
+            return GenerateOutcome::Failed(e.to_string());
+        }
+    }
+    GenerateOutcome::Written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_expected_file_uses_fragment() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "# Base\n").unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/instructions")).unwrap();
+        fs::write(
+            tmp.path().join(".agents/instructions/claude.md"),
+            "Claude only.\n",
+        )
+        .unwrap();
+
+        let config = Config::default();
+        let claude = expected_generated_file(&config, tmp.path(), false, AgentName::Claude)
+            .unwrap()
+            .content;
+        assert!(claude.starts_with("<!-- hana:generated from AGENTS.md hash="));
+        assert!(claude.ends_with("# Base\n\nClaude only.\n"));
+
+        let pi = expected_generated_file(&config, tmp.path(), false, AgentName::Pi)
+            .unwrap()
+            .content;
+        assert!(!pi.contains("Claude only."));
+    }
+
+    #[test]
+    fn test_includes_decide_link_or_generate() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "# Base\n@docs/rules.md\n").unwrap();
+        fs::create_dir_all(tmp.path().join("docs")).unwrap();
+        fs::write(tmp.path().join("docs/rules.md"), "Rule.\n").unwrap();
+
+        assert!(!wants_generated(
+            &Config::default(),
+            tmp.path(),
+            false,
+            AgentName::Pi
+        ));
+
+        let config = Config::parse("[source]\nexpand_includes = true\n").unwrap();
+        assert!(!wants_generated(
+            &config,
+            tmp.path(),
+            false,
+            AgentName::Claude
+        ));
+        assert!(wants_generated(&config, tmp.path(), false, AgentName::Pi));
+
+        let pi = expected_generated_file(&config, tmp.path(), false, AgentName::Pi).unwrap();
+        assert!(pi.content.ends_with("# Base\nRule.\n"));
+        assert!(pi.missing.is_empty());
+    }
+
+    #[test]
+    fn test_replaces_symlink_and_refuses_edits() {
+        let tmp = TempDir::new().unwrap();
+        let dest = tmp.path().join("CLAUDE.md");
+        fs::write(tmp.path().join("AGENTS.md"), "# Base\n").unwrap();
+        symlink("AGENTS.md", &dest).unwrap();
+
+        let content = generated_file("AGENTS.md", "# Base\n");
+        assert!(matches!(
+            generate_instruction_file(&dest, &content, false, false),
+            GenerateOutcome::Written
+        ));
+        assert!(!dest.is_symlink());
+        assert!(matches!(
+            generate_instruction_file(&dest, &content, false, false),
+            GenerateOutcome::AlreadyValid
+        ));
+
+        fs::write(&dest, content.replace("# Base", "# Base (edited)")).unwrap();
+        let newer = generated_file("AGENTS.md", "# Base v2\n");
+        assert!(matches!(
+            generate_instruction_file(&dest, &newer, false, false),
+            GenerateOutcome::Edited
+        ));
+        assert!(matches!(
+            generate_instruction_file(&dest, &newer, false, true),
+            GenerateOutcome::Written
+        ));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), newer);
+    }
+
+    #[test]
+    fn test_hand_written_file_is_a_conflict() {
+        let tmp = TempDir::new().unwrap();
+        let dest = tmp.path().join("CLAUDE.md");
+        fs::write(&dest, "# Mine\n").unwrap();
+
+        let content = generated_file("AGENTS.md", "# Base\n");
+        assert!(matches!(
+            generate_instruction_file(&dest, &content, false, false),
+            GenerateOutcome::Conflict
+        ));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "# Mine\n");
+    }
+
+    #[test]
+    fn test_io_errors_fail() {
+        let tmp = TempDir::new().unwrap();
+        let content = generated_file("AGENTS.md", "# Base\n");
+
+        // Unreadable existing file is not treated as empty
+        let binary = tmp.path().join("CLAUDE.md");
+        fs::write(&binary, [0xff, 0xfe]).unwrap();
+        assert!(matches!(
+            generate_instruction_file(&binary, &content, false, true),
+            GenerateOutcome::Failed(_)
+        ));
+        assert_eq!(fs::read(&binary).unwrap(), vec![0xff, 0xfe]);
+
+        // Parent cannot be created
+        fs::write(tmp.path().join(".claude"), "file").unwrap();
+        let nested = tmp.path().join(".claude/CLAUDE.md");
+        assert!(matches!(
+            generate_instruction_file(&nested, &content, false, false),
+            GenerateOutcome::Failed(_)
+        ));
+    }
+}
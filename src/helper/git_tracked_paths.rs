@@ -0,0 +1,66 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Which of `paths` are tracked in the git index. Empty when git is unavailable or
+/// `base_dir` is not a repository.
+pub fn git_tracked_paths(base_dir: &Path, paths: &[PathBuf]) -> Vec<PathBuf> {
+    if paths.is_empty() {
+        return vec![];
+    }
+    let output = Command::new("git")
+        .args(["ls-files", "-z", "--"])
+        .args(paths)
+        .current_dir(base_dir)
+        .output();
+    let Ok(output) = output else {
+        return vec![];
+    };
+    if !output.status.success() {
+        return vec![];
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(|p| base_dir.join(p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lists_tracked_only() {
+        let tmp = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .status()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        fs::write(tmp.path().join("tracked.md"), "x").unwrap();
+        fs::write(tmp.path().join("untracked.md"), "x").unwrap();
+        git(&["add", "tracked.md"]);
+
+        let paths = vec![
+            tmp.path().join("tracked.md"),
+            tmp.path().join("untracked.md"),
+        ];
+        assert_eq!(
+            git_tracked_paths(tmp.path(), &paths),
+            vec![tmp.path().join("tracked.md")]
+        );
+    }
+
+    #[test]
+    fn test_not_a_repo() {
+        let tmp = TempDir::new().unwrap();
+        let paths = vec![tmp.path().join("a")];
+        assert!(git_tracked_paths(tmp.path(), &paths).is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+/// Match a name against a shell-style glob (`*` = any run of characters, `?` = one character).
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+            }
+            Some(c) if *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal() {
+        assert!(glob_match("my-skill", "my-skill"));
+        assert!(!glob_match("my-skill", "my-skill2"));
+    }
+
+    #[test]
+    fn test_star() {
+        assert!(glob_match("team-*", "team-review"));
+        assert!(glob_match("team-*", "team-"));
+        assert!(!glob_match("team-*", "teams"));
+        assert!(glob_match("*-wip", "feature-wip"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn test_question_mark() {
+        assert!(glob_match("v?", "v1"));
+        assert!(!glob_match("v?", "v12"));
+    }
+}
@@ -0,0 +1,210 @@
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DiffLine {
+    Same(String),
+    /// Only in the old text
+    Removed(String),
+    /// Only in the new text
+    Added(String),
+}
+
+/// Line diff of `old` → `new` via longest common subsequence, in linear space (Hirschberg).
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // Common ends cost nothing and are the bulk of a typical instruction file
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let mut out: Vec<DiffLine> = a[..prefix]
+        .iter()
+        .map(|l| DiffLine::Same(l.to_string()))
+        .collect();
+    diff_middle(
+        &a[prefix..a.len() - suffix],
+        &b[prefix..b.len() - suffix],
+        &mut out,
+    );
+    out.extend(
+        a[a.len() - suffix..]
+            .iter()
+            .map(|l| DiffLine::Same(l.to_string())),
+    );
+    out
+}
+
+/// Split `a` in half, find where the LCS crosses `b` from both ends, and recurse.
+fn diff_middle(a: &[&str], b: &[&str], out: &mut Vec<DiffLine>) {
+    match a {
+        [] => out.extend(b.iter().map(|l| DiffLine::Added(l.to_string()))),
+        _ if b.is_empty() => out.extend(a.iter().map(|l| DiffLine::Removed(l.to_string()))),
+        [line] => match b.iter().position(|l| l == line) {
+            Some(k) => {
+                out.extend(b[..k].iter().map(|l| DiffLine::Added(l.to_string())));
+                out.push(DiffLine::Same(line.to_string()));
+                out.extend(b[k + 1..].iter().map(|l| DiffLine::Added(l.to_string())));
+            }
+            None => {
+                out.push(DiffLine::Removed(line.to_string()));
+                out.extend(b.iter().map(|l| DiffLine::Added(l.to_string())));
+            }
+        },
+        _ => {
+            let mid = a.len() / 2;
+            let reversed: Vec<&str> = b.iter().rev().copied().collect();
+            let forward = lcs_lengths(a[..mid].iter(), b);
+            let backward = lcs_lengths(a[mid..].iter().rev(), &reversed);
+            let split = (0..=b.len())
+                .max_by_key(|&j| (forward[j] + backward[b.len() - j], std::cmp::Reverse(j)))
+                .unwrap_or(0);
+            diff_middle(&a[..mid], &b[..split], out);
+            diff_middle(&a[mid..], &b[split..], out);
+        }
+    }
+}
+
+/// LCS length of all of `a` against each prefix of `b`, keeping one row at a time.
+fn lcs_lengths<'a>(a: impl Iterator<Item = &'a &'a str>, b: &[&str]) -> Vec<usize> {
+    let mut row = vec![0usize; b.len() + 1];
+    for x in a {
+        let mut diagonal = 0;
+        for (j, y) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if x == y {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+/// Combine both sides. A run only one side has is kept as is; a run both sides changed is
+/// wrapped in git-style conflict markers. Returns the text and whether it has markers.
+pub fn merge_with_markers(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+) -> (String, bool) {
+    let mut out: Vec<String> = Vec::new();
+    let mut removed: Vec<String> = Vec::new();
+    let mut added: Vec<String> = Vec::new();
+    let mut conflicts = false;
+
+    let mut flush = |out: &mut Vec<String>, removed: &mut Vec<String>, added: &mut Vec<String>| {
+        if removed.is_empty() || added.is_empty() {
+            out.append(removed);
+            out.append(added);
+            return;
+        }
+        conflicts = true;
+        out.push(format!("<<<<<<< {old_label}"));
+        out.append(removed);
+        out.push("=======".to_string());
+        out.append(added);
+        out.push(format!(">>>>>>> {new_label}"));
+    };
+
+    for line in line_diff(old, new) {
+        match line {
+            DiffLine::Same(text) => {
+                flush(&mut out, &mut removed, &mut added);
+                out.push(text);
+            }
+            DiffLine::Removed(text) => removed.push(text),
+            DiffLine::Added(text) => added.push(text),
+        }
+    }
+    flush(&mut out, &mut removed, &mut added);
+
+    let mut merged = out.join("\n");
+    merged.push('\n');
+    (merged, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc\n", "a\nB\nc\nd\n");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a".into()),
+                DiffLine::Removed("b".into()),
+                DiffLine::Added("B".into()),
+                DiffLine::Same("c".into()),
+                DiffLine::Added("d".into()),
+            ]
+        );
+        assert!(
+            line_diff("x\n", "x\n")
+                .iter()
+                .all(|l| matches!(l, DiffLine::Same(_)))
+        );
+    }
+
+    #[test]
+    fn test_line_diff_finds_minimal_edits_in_long_files() {
+        let old: String = (0..2000).map(|i| format!("line {i}\n")).collect();
+        let new = old
+            .replace("line 700\n", "")
+            .replace("line 1500\n", "line 1500\nextra\n");
+        let diff = line_diff(&old, &new);
+        let changed: Vec<&DiffLine> = diff
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Same(_)))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![
+                &DiffLine::Removed("line 700".into()),
+                &DiffLine::Added("extra".into())
+            ]
+        );
+        assert_eq!(diff.len(), 2001);
+    }
+
+    #[test]
+    fn test_merge_with_markers() {
+        let merged = merge_with_markers("a\nb\nc\n", "a\nB\nc\n", "AGENTS.md", "CLAUDE.md");
+        assert_eq!(
+            merged,
+            (
+                "a\n<<<<<<< AGENTS.md\nb\n=======\nB\n>>>>>>> CLAUDE.md\nc\n".to_string(),
+                true
+            )
+        );
+        assert_eq!(
+            merge_with_markers("same\n", "same\n", "a", "b"),
+            ("same\n".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_merge_applies_one_sided_runs() {
+        let source = "# Rules\nshared\n";
+        let appended = "# Rules\nshared\n\n## Claude\nuse subagents\n";
+        assert_eq!(
+            merge_with_markers(source, appended, "AGENTS.md", "CLAUDE.md"),
+            (appended.to_string(), false)
+        );
+
+        // Lines only the source has are kept too
+        let (merged, conflicts) =
+            merge_with_markers("a\nsource only\nb\n", "a\nb\nagent only\n", "x", "y");
+        assert_eq!(merged, "a\nsource only\nb\nagent only\n");
+        assert!(!conflicts);
+    }
+}
@@ -0,0 +1,59 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Whether the symlink at `link` points at `dir` or something inside it, judged from the link
+/// text alone so that broken links can be checked too.
+pub fn link_points_into(link: &Path, dir: &Path) -> bool {
+    let Ok(target) = fs::read_link(link) else {
+        return false;
+    };
+    let parent = link.parent().unwrap_or(Path::new(""));
+    let parent = fs::canonicalize(parent).unwrap_or_else(|_| parent.to_path_buf());
+    let target = normalize(&parent.join(target));
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| normalize(dir));
+    target.starts_with(dir)
+}
+
+/// Resolve `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_broken_links_by_target() {
+        let tmp = TempDir::new().unwrap();
+        let source = tmp.path().join(".agents/prompts");
+        let dest = tmp.path().join(".claude/prompts");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&dest).unwrap();
+
+        symlink("../../.agents/prompts/gone.md", dest.join("relative.md")).unwrap();
+        symlink(source.join("gone.md"), dest.join("absolute.md")).unwrap();
+        symlink(
+            tmp.path().join("elsewhere/gone.md"),
+            dest.join("foreign.md"),
+        )
+        .unwrap();
+
+        assert!(link_points_into(&dest.join("relative.md"), &source));
+        assert!(link_points_into(&dest.join("absolute.md"), &source));
+        assert!(!link_points_into(&dest.join("foreign.md"), &source));
+        assert!(!link_points_into(&dest, &source));
+    }
+}
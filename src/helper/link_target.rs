@@ -0,0 +1,34 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::LinkStyle;
+use crate::helper::relative_path::relative_path;
+
+/// The text a managed symlink at `dest` should contain to reach `source`.
+pub fn link_target(dest: &Path, source: &Path, style: LinkStyle) -> PathBuf {
+    match style {
+        LinkStyle::Absolute => source.to_path_buf(),
+        LinkStyle::Relative => dest
+            .parent()
+            .map(|parent| relative_path(parent, source))
+            .unwrap_or_else(|| source.to_path_buf()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_and_absolute() {
+        let dest = Path::new("/p/.claude/skills/my-skill");
+        let source = Path::new("/p/.agents/skills/my-skill");
+        assert_eq!(
+            link_target(dest, source, LinkStyle::Relative),
+            PathBuf::from("../../.agents/skills/my-skill")
+        );
+        assert_eq!(
+            link_target(dest, source, LinkStyle::Absolute),
+            PathBuf::from("/p/.agents/skills/my-skill")
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Non-hidden entries of `dir` (files and directories), sorted by name. With `real_only`,
+/// symlinks are skipped. A missing directory has no entries.
+pub fn list_dir_entries(dir: &Path, real_only: bool) -> Vec<(String, PathBuf)> {
+    let mut entries: Vec<(String, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                entry.path(),
+            )
+        })
+        .filter(|(name, _)| !name.starts_with('.'))
+        .filter(|(_, path)| !real_only || !path.is_symlink())
+        .collect();
+    entries.sort();
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lists_files_and_dirs_but_not_hidden() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("b.md"), "").unwrap();
+        fs::create_dir_all(tmp.path().join("a")).unwrap();
+        fs::write(tmp.path().join(".hidden"), "").unwrap();
+        symlink(tmp.path().join("b.md"), tmp.path().join("c.md")).unwrap();
+
+        let names = |real_only| -> Vec<String> {
+            list_dir_entries(tmp.path(), real_only)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+        assert_eq!(names(false), vec!["a", "b.md", "c.md"]);
+        assert_eq!(names(true), vec!["a", "b.md"]);
+        assert!(list_dir_entries(&tmp.path().join("missing"), false).is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use std::path::Path;
+
+/// `KEY=value` pairs from a dotenv file. `#` comments, blank lines and an `export ` prefix are
+/// allowed; matching outer quotes are stripped. A missing file yields nothing.
+pub fn load_env_file(path: &Path) -> std::io::Result<Vec<(String, String)>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = [('"', '"'), ('\'', '\'')]
+                .iter()
+                .find_map(|(open, close)| {
+                    value
+                        .strip_prefix(*open)
+                        .and_then(|v| v.strip_suffix(*close))
+                })
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parses_dotenv_lines() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(".env");
+        fs::write(
+            &path,
+            "# keys\nGITHUB_TOKEN=abc\nexport QUOTED = \"a b\"\nSINGLE='x=y'\n\nbroken line\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load_env_file(&path).unwrap(),
+            vec![
+                ("GITHUB_TOKEN".to_string(), "abc".to_string()),
+                ("QUOTED".to_string(), "a b".to_string()),
+                ("SINGLE".to_string(), "x=y".to_string()),
+            ]
+        );
+        assert!(
+            load_env_file(&tmp.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(load_env_file(tmp.path()).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::Path;
+
+use crate::helper::glob_match::glob_match;
+
+#[derive(Debug, Clone, PartialEq)]
+struct IgnorePattern {
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+/// Patterns from a `.hanaignore` file (gitignore syntax).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IgnoreRules {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreRules {
+    pub fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                IgnorePattern {
+                    glob: line.trim_start_matches('/').to_string(),
+                    negated,
+                    dir_only,
+                    anchored,
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check a path relative to the scanned directory. The last matching pattern wins.
+    pub fn is_ignored(&self, rel_path: &str, is_dir: bool) -> bool {
+        let basename = rel_path.rsplit('/').next().unwrap_or(rel_path);
+        self.patterns
+            .iter()
+            .rev()
+            .filter(|p| is_dir || !p.dir_only)
+            .find(|p| {
+                if p.anchored {
+                    glob_match(&p.glob, rel_path)
+                } else {
+                    glob_match(&p.glob, basename)
+                }
+            })
+            .map(|p| !p.negated)
+            .unwrap_or(false)
+    }
+}
+
+/// Load `.hanaignore` rules. A missing file means nothing is ignored.
+pub fn load_ignore_rules(path: &Path) -> IgnoreRules {
+    fs::read_to_string(path)
+        .map(|content| IgnoreRules::parse(&content))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_basename_and_dir_only_patterns() {
+        let rules = IgnoreRules::parse("# comment\nnode_modules/\n*.tmp\n\n");
+        assert!(rules.is_ignored("node_modules", true));
+        assert!(!rules.is_ignored("node_modules", false));
+        assert!(rules.is_ignored("scratch.tmp", true));
+        assert!(rules.is_ignored("frontend/old.tmp", true));
+        assert!(!rules.is_ignored("my-skill", true));
+    }
+
+    #[test]
+    fn test_negation_last_match_wins() {
+        let rules = IgnoreRules::parse("private-*\n!private-shared\n");
+        assert!(rules.is_ignored("private-notes", true));
+        assert!(!rules.is_ignored("private-shared", true));
+    }
+
+    #[test]
+    fn test_anchored_pattern() {
+        let rules = IgnoreRules::parse("/frontend/draft-*\n");
+        assert!(rules.is_ignored("frontend/draft-1", true));
+        assert!(!rules.is_ignored("backend/draft-1", true));
+        assert!(!rules.is_ignored("draft-1", true));
+    }
+
+    #[test]
+    fn test_missing_file_ignores_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let rules = load_ignore_rules(&tmp.path().join(".hanaignore"));
+        assert!(rules.is_empty());
+        assert!(!rules.is_ignored("anything", true));
+    }
+}
@@ -0,0 +1,107 @@
+/// Marker lines around a hana-owned block in a `#`-commented file (hooks, .gitignore).
+fn markers(id: &str) -> (String, String) {
+    (
+        format!("# >>> hana {id} >>>"),
+        format!("# <<< hana {id} <<<"),
+    )
+}
+
+/// Find the block's line range `[start, end]` (inclusive of both markers).
+fn find_block(lines: &[&str], id: &str) -> Option<(usize, usize)> {
+    let (begin, end) = markers(id);
+    let start = lines.iter().position(|l| l.trim_end() == begin)?;
+    let stop = lines[start..].iter().position(|l| l.trim_end() == end)? + start;
+    Some((start, stop))
+}
+
+pub fn has_managed_block(content: &str, id: &str) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    find_block(&lines, id).is_some()
+}
+
+/// Insert or replace the block, leaving everything else untouched. New blocks are appended.
+pub fn upsert_managed_block(content: &str, id: &str, body: &str) -> String {
+    let (begin, end) = markers(id);
+    let block: Vec<String> = std::iter::once(begin)
+        .chain(body.lines().map(str::to_string))
+        .chain(std::iter::once(end))
+        .collect();
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = match find_block(&lines, id) {
+        Some((start, stop)) => lines[..start]
+            .iter()
+            .map(|l| l.to_string())
+            .chain(block)
+            .chain(lines[stop + 1..].iter().map(|l| l.to_string()))
+            .collect(),
+        None => {
+            let mut out: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            if out.last().is_some_and(|l| !l.trim().is_empty()) {
+                out.push(String::new());
+            }
+            out.extend(block);
+            out
+        }
+    };
+    out.push(String::new());
+    out.join("\n")
+}
+
+/// Remove the block and the blank line that separated it. `None` when there is no block.
+pub fn remove_managed_block(content: &str, id: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let (start, stop) = find_block(&lines, id)?;
+
+    let mut before: Vec<&str> = lines[..start].to_vec();
+    let mut after: &[&str] = &lines[stop + 1..];
+    if before.last().is_some_and(|l| l.trim().is_empty()) {
+        before.pop();
+    } else if after.first().is_some_and(|l| l.trim().is_empty()) {
+        after = &after[1..];
+    }
+    let rest: Vec<&str> = before.into_iter().chain(after.iter().copied()).collect();
+
+    if rest.is_empty() {
+        Some(String::new())
+    } else {
+        Some(format!("{}\n", rest.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_then_replace() {
+        let original = "#!/bin/sh\nmake lint\n";
+        let once = upsert_managed_block(original, "sync", "hana sync");
+        assert_eq!(
+            once,
+            "#!/bin/sh\nmake lint\n\n# >>> hana sync >>>\nhana sync\n# <<< hana sync <<<\n"
+        );
+
+        let twice = upsert_managed_block(&once, "sync", "hana sync --force");
+        assert!(twice.contains("hana sync --force"));
+        assert_eq!(twice.matches(">>> hana sync >>>").count(), 1);
+        assert!(twice.starts_with("#!/bin/sh\nmake lint\n"));
+    }
+
+    #[test]
+    fn test_remove_restores_original() {
+        let original = "#!/bin/sh\nmake lint\n";
+        let installed = upsert_managed_block(original, "sync", "hana sync");
+        assert!(has_managed_block(&installed, "sync"));
+        assert_eq!(remove_managed_block(&installed, "sync").unwrap(), original);
+        assert!(remove_managed_block(original, "sync").is_none());
+    }
+
+    #[test]
+    fn test_blocks_with_different_ids_coexist() {
+        let content = upsert_managed_block("", "a", "one");
+        let content = upsert_managed_block(&content, "b", "two");
+        let content = remove_managed_block(&content, "a").unwrap();
+        assert_eq!(content, "# >>> hana b >>>\ntwo\n# <<< hana b <<<\n");
+    }
+}
@@ -0,0 +1,614 @@
+use std::fs;
+use std::io::Read;
+use std::ops::Range;
+use std::path::Path;
+
+use json_comments::{CommentSettings, StripComments};
+use serde_json::{Map, Value};
+
+use crate::config::{McpFormat, McpServer};
+use crate::helper::expand_env_refs::expand_env_refs;
+
+/// One agent config file holding MCP servers. Only the servers entry is rewritten; the rest of
+/// the file, comments included, is written back byte for byte.
+#[derive(Debug)]
+pub struct McpConfigFile {
+    doc: Doc,
+}
+
+#[derive(Debug)]
+enum Doc {
+    Json(JsonDoc),
+    Toml(toml_edit::DocumentMut),
+}
+
+/// A JSON or JSONC document: its original text, where the servers object sits in it, and the
+/// servers themselves.
+#[derive(Debug)]
+struct JsonDoc {
+    text: String,
+    /// Key servers live under (`mcpServers`, `mcp`)
+    key: &'static str,
+    layout: Layout,
+    servers: Map<String, Value>,
+    changed: bool,
+}
+
+/// Byte offsets in a JSON document's top-level object.
+#[derive(Debug)]
+struct Layout {
+    /// The value under the servers key; `None` when the key is absent
+    span: Option<Range<usize>>,
+    /// End of the last member's value, where a separating comma goes
+    last_member_end: Option<usize>,
+    /// The closing `}`
+    close: usize,
+    /// The closing `}` starts its own line
+    close_on_own_line: bool,
+    /// Indentation of one level, taken from the first member
+    indent: String,
+}
+
+const CODEX_KEY: &str = "mcp_servers";
+
+impl McpConfigFile {
+    /// Read `path`; a missing file is an empty document.
+    pub fn load(path: &Path, format: McpFormat) -> Result<Self, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+        Self::parse(&content, format)
+    }
+
+    /// JSON files may contain `//` and `/* */` comments (opencode.json is JSONC).
+    pub fn parse(content: &str, format: McpFormat) -> Result<Self, String> {
+        let doc = match format {
+            McpFormat::ClaudeJson => Doc::Json(JsonDoc::parse(content, "mcpServers")?),
+            McpFormat::OpencodeJson => Doc::Json(JsonDoc::parse(content, "mcp")?),
+            McpFormat::CodexToml => Doc::Toml(
+                content
+                    .parse::<toml_edit::DocumentMut>()
+                    .map_err(|e| e.to_string())?,
+            ),
+        };
+        Ok(Self { doc })
+    }
+
+    /// The entry for `name`, as JSON for comparison.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match &self.doc {
+            Doc::Json(json) => json.servers.get(name).cloned(),
+            Doc::Toml(doc) => doc
+                .get(CODEX_KEY)?
+                .as_table_like()?
+                .get(name)
+                .and_then(item_to_json),
+        }
+    }
+
+    pub fn set(&mut self, name: &str, entry: &Value) {
+        match &mut self.doc {
+            Doc::Json(json) => {
+                json.servers.insert(name.to_string(), entry.clone());
+                json.changed = true;
+            }
+            Doc::Toml(doc) => {
+                let servers = doc.entry(CODEX_KEY).or_insert_with(|| {
+                    let mut table = toml_edit::Table::new();
+                    table.set_implicit(true);
+                    toml_edit::Item::Table(table)
+                });
+                if let Some(servers) = servers.as_table_like_mut() {
+                    servers.insert(name, json_to_table(entry));
+                }
+            }
+        }
+    }
+
+    /// Drop `name`; `false` when it was not there.
+    pub fn remove(&mut self, name: &str) -> bool {
+        match &mut self.doc {
+            Doc::Json(json) => {
+                let removed = json.servers.shift_remove(name).is_some();
+                json.changed |= removed;
+                removed
+            }
+            Doc::Toml(doc) => doc
+                .get_mut(CODEX_KEY)
+                .and_then(|servers| servers.as_table_like_mut())
+                .is_some_and(|servers| servers.remove(name).is_some()),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        match &self.doc {
+            Doc::Json(json) => json.render(),
+            Doc::Toml(doc) => doc.to_string(),
+        }
+    }
+
+    /// Write through a temporary file next to `path`, so an interrupted write never leaves a
+    /// truncated config behind.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = path.with_file_name(format!(".{file_name}.hana-tmp"));
+        fs::write(&tmp, self.render())?;
+        fs::rename(&tmp, path).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+    }
+}
+
+impl JsonDoc {
+    fn parse(content: &str, key: &'static str) -> Result<Self, String> {
+        let text = if content.trim().is_empty() {
+            "{}\n".to_string()
+        } else {
+            content.to_string()
+        };
+        // Comments become spaces, so offsets in `plain` are offsets in `text`
+        let mut plain = String::new();
+        StripComments::with_settings(CommentSettings::c_style(), text.as_bytes())
+            .read_to_string(&mut plain)
+            .map_err(|e| e.to_string())?;
+        let Value::Object(mut top) = serde_json::from_str(&plain).map_err(|e| e.to_string())?
+        else {
+            return Err("top level is not a JSON object".to_string());
+        };
+        let servers = match top.remove(key) {
+            None => Map::new(),
+            Some(Value::Object(servers)) => servers,
+            Some(_) => return Err(format!("`{key}` is not an object")),
+        };
+        Ok(Self {
+            layout: Layout::scan(&plain, key),
+            text,
+            key,
+            servers,
+            changed: false,
+        })
+    }
+
+    fn render(&self) -> String {
+        if !self.changed {
+            return self.text.clone();
+        }
+        let layout = &self.layout;
+        let value = indent_pretty(&Value::Object(self.servers.clone()), &layout.indent);
+        let mut out = self.text.clone();
+        match &layout.span {
+            Some(span) => out.replace_range(span.clone(), &value),
+            None => {
+                let member = format!(
+                    "{}{}: {value}\n",
+                    layout.indent,
+                    Value::String(self.key.to_string())
+                );
+                if layout.close_on_own_line {
+                    let line_start = out[..layout.close].rfind('\n').map_or(0, |i| i + 1);
+                    out.insert_str(line_start, &member);
+                } else {
+                    out.insert_str(layout.close, &format!("\n{member}"));
+                }
+                if let Some(end) = layout.last_member_end {
+                    out.insert(end, ',');
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Layout {
+    /// Walk the members of the top-level object of comment-free, valid JSON.
+    fn scan(plain: &str, key: &str) -> Self {
+        let bytes = plain.as_bytes();
+        let mut span = None;
+        let mut first_member = None;
+        let mut last_member_end = None;
+        let mut i = skip_ws(bytes, 0) + 1;
+        loop {
+            i = skip_ws(bytes, i);
+            match bytes.get(i) {
+                Some(b',') => {
+                    i += 1;
+                    continue;
+                }
+                Some(b'"') => {}
+                _ => break,
+            }
+            first_member.get_or_insert(i);
+            let key_end = skip_value(bytes, i);
+            let name: String = serde_json::from_str(&plain[i..key_end]).unwrap_or_default();
+            let value_start = skip_ws(bytes, skip_ws(bytes, key_end) + 1);
+            let value_end = skip_value(bytes, value_start);
+            if name == key {
+                span = Some(value_start..value_end);
+            }
+            last_member_end = Some(value_end);
+            i = value_end;
+        }
+        let close = i.min(plain.len());
+        let line_start = plain[..close].rfind('\n');
+        let indent = first_member
+            .and_then(|first| {
+                let line_start = plain[..first].rfind('\n')? + 1;
+                Some(plain[line_start..first].to_string())
+            })
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or_else(|| "  ".to_string());
+        Self {
+            span,
+            last_member_end,
+            close,
+            close_on_own_line: line_start
+                .is_some_and(|start| plain[start..close].trim().is_empty()),
+            indent,
+        }
+    }
+}
+
+fn skip_ws(bytes: &[u8], mut i: usize) -> usize {
+    while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+        i += 1;
+    }
+    i
+}
+
+/// End of the string, number, literal, object or array starting at `start`.
+fn skip_value(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut i = start;
+    while let Some(&b) = bytes.get(i) {
+        if in_string {
+            match b {
+                b'\\' => i += 1,
+                b'"' => {
+                    in_string = false;
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                _ => {}
+            }
+        } else {
+            match b {
+                b'"' => in_string = true,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' if depth == 0 => return i,
+                b'}' | b']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                b',' if depth == 0 => return i,
+                _ if depth == 0 && b.is_ascii_whitespace() => return i,
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Pretty-printed with `indent` per level, nested one level below the top-level members.
+fn indent_pretty(value: &Value, indent: &str) -> String {
+    let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+    pretty
+        .lines()
+        .enumerate()
+        .map(|(n, line)| {
+            let body = line.trim_start_matches(' ');
+            let depth = (line.len() - body.len()) / 2 + usize::from(n > 0);
+            format!("{}{body}", indent.repeat(depth))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The entry hana writes for `server` in an agent's format.
+pub fn render_mcp_entry(server: &McpServer, format: McpFormat) -> Value {
+    let strings = |items: &mut dyn Iterator<Item = &String>| {
+        Value::Array(items.map(|s| Value::String(s.clone())).collect())
+    };
+    let map = |pairs: &[(String, String)]| {
+        Value::Object(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+                .collect(),
+        )
+    };
+    let mut entry = Map::new();
+    let mut push = |key: &str, value: Value| {
+        entry.insert(key.to_string(), value);
+    };
+
+    match (format, &server.command, &server.url) {
+        (McpFormat::OpencodeJson, Some(command), _) => {
+            push("type", "local".into());
+            push(
+                "command",
+                strings(&mut std::iter::once(command).chain(&server.args)),
+            );
+            if !server.env.is_empty() {
+                push("environment", map(&server.env));
+            }
+        }
+        (_, Some(command), _) => {
+            push("command", command.as_str().into());
+            if !server.args.is_empty() {
+                push("args", strings(&mut server.args.iter()));
+            }
+            if !server.env.is_empty() {
+                push("env", map(&server.env));
+            }
+        }
+        (_, None, url) => {
+            let url = url.as_deref().unwrap_or_default();
+            let headers_key = match format {
+                McpFormat::ClaudeJson => {
+                    push("type", "http".into());
+                    "headers"
+                }
+                McpFormat::OpencodeJson => {
+                    push("type", "remote".into());
+                    "headers"
+                }
+                McpFormat::CodexToml => "http_headers",
+            };
+            push("url", url.into());
+            if !server.headers.is_empty() {
+                push(headers_key, map(&server.headers));
+            }
+        }
+    }
+    Value::Object(entry)
+}
+
+/// The entry for `server` after rewriting its `${env:NAME}` placeholders for the agent. The
+/// error is the name of a variable the agent needs resolved but `env` does not define.
+pub fn expected_mcp_entry(
+    server: &McpServer,
+    format: McpFormat,
+    env: &[(String, String)],
+) -> Result<Value, String> {
+    let lookup = |name: &str| {
+        env.iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.clone())
+            .or_else(|| std::env::var(name).ok())
+    };
+    let server =
+        server.try_map_strings(|text| expand_env_refs(text, format.env_syntax(), &lookup))?;
+    Ok(render_mcp_entry(&server, format))
+}
+
+fn item_to_json(item: &toml_edit::Item) -> Option<Value> {
+    match item {
+        toml_edit::Item::Value(value) => Some(value_to_json(value)),
+        toml_edit::Item::Table(table) => Some(Value::Object(
+            table
+                .iter()
+                .filter_map(|(k, v)| Some((k.to_string(), item_to_json(v)?)))
+                .collect(),
+        )),
+        toml_edit::Item::None | toml_edit::Item::ArrayOfTables(_) => None,
+    }
+}
+
+fn value_to_json(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        toml_edit::Value::Datetime(d) => Value::String(d.value().to_string()),
+        toml_edit::Value::Array(items) => Value::Array(items.iter().map(value_to_json).collect()),
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(k, v)| (k.to_string(), value_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// A server entry becomes its own `[mcp_servers.<name>]` table; nested objects stay inline.
+fn json_to_table(entry: &Value) -> toml_edit::Item {
+    let mut table = toml_edit::Table::new();
+    if let Value::Object(fields) = entry {
+        for (key, value) in fields {
+            if let Some(value) = json_to_value(value) {
+                table.insert(key, toml_edit::Item::Value(value));
+            }
+        }
+    }
+    toml_edit::Item::Table(table)
+}
+
+fn json_to_value(value: &Value) -> Option<toml_edit::Value> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64()?.into(),
+        },
+        Value::String(s) => s.as_str().into(),
+        Value::Array(items) => {
+            toml_edit::Value::Array(items.iter().filter_map(json_to_value).collect())
+        }
+        Value::Object(fields) => {
+            let mut table = toml_edit::InlineTable::new();
+            for (key, value) in fields {
+                if let Some(value) = json_to_value(value) {
+                    table.insert(key, value);
+                }
+            }
+            toml_edit::Value::InlineTable(table)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn github() -> McpServer {
+        McpServer {
+            args: vec!["-y".to_string(), "server-github".to_string()],
+            env: vec![("TOKEN".to_string(), "abc".to_string())],
+            ..McpServer::command("github", "npx")
+        }
+    }
+
+    fn round_trip(format: McpFormat, original: &str, expected: &str) {
+        let mut file = McpConfigFile::parse(original, format).unwrap();
+        assert_eq!(file.render(), original);
+        let entry = render_mcp_entry(&github(), format);
+        file.set("github", &entry);
+        let written = file.render();
+        assert_eq!(written, expected);
+
+        let reread = McpConfigFile::parse(&written, format).unwrap();
+        assert_eq!(reread.get("github").unwrap(), entry);
+
+        let mut file = reread;
+        assert!(file.remove("github"));
+        assert!(!file.remove("github"));
+        assert!(file.get("github").is_none());
+    }
+
+    #[test]
+    fn test_claude_json_round_trip() {
+        round_trip(
+            McpFormat::ClaudeJson,
+            "{\n  \"numStartups\": 3,\n  \"mcpServers\": {\n    \"mine\": {\n      \"command\": \"x\"\n    }\n  }\n}\n",
+            "{\n  \"numStartups\": 3,\n  \"mcpServers\": {\n    \"mine\": {\n      \"command\": \"x\"\n    },\n    \"github\": {\n      \"command\": \"npx\",\n      \"args\": [\n        \"-y\",\n        \"server-github\"\n      ],\n      \"env\": {\n        \"TOKEN\": \"abc\"\n      }\n    }\n  }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_opencode_json_round_trip() {
+        round_trip(
+            McpFormat::OpencodeJson,
+            "{\n  \"$schema\": \"https://opencode.ai/config.json\"\n}",
+            "{\n  \"$schema\": \"https://opencode.ai/config.json\",\n  \"mcp\": {\n    \"github\": {\n      \"type\": \"local\",\n      \"command\": [\n        \"npx\",\n        \"-y\",\n        \"server-github\"\n      ],\n      \"environment\": {\n        \"TOKEN\": \"abc\"\n      }\n    }\n  }\n}",
+        );
+    }
+
+    #[test]
+    fn test_codex_toml_round_trip_keeps_comments() {
+        round_trip(
+            McpFormat::CodexToml,
+            "# my settings\nmodel = \"o3\" # fast\n\n[mcp_servers.mine]\ncommand = \"x\"\n",
+            "# my settings\nmodel = \"o3\" # fast\n\n[mcp_servers.mine]\ncommand = \"x\"\n\n[mcp_servers.github]\ncommand = \"npx\"\nargs = [\"-y\", \"server-github\"]\nenv = { TOKEN = \"abc\" }\n",
+        );
+    }
+
+    #[test]
+    fn test_jsonc_only_the_servers_value_is_rewritten() {
+        let original = "{\n\t// editor settings\n\t\"$schema\":\"https://opencode.ai/config.json\", /* keep */\n\t\"mcp\": {\"mine\": {\"type\": \"local\", \"command\": [\"x\"]}},\n\t\"theme\":   \"dark\"   // trailing\n}\n";
+        let mut file = McpConfigFile::parse(original, McpFormat::OpencodeJson).unwrap();
+        assert_eq!(file.render(), original);
+        file.set(
+            "github",
+            &render_mcp_entry(&github(), McpFormat::OpencodeJson),
+        );
+
+        let written = file.render();
+        let (before, after) = original.split_once("{\"mine\"").unwrap();
+        let after = after.split_once("}},").unwrap().1;
+        assert!(written.starts_with(before));
+        assert!(written.ends_with(after));
+        assert!(written.contains("\t\"mcp\": {\n\t\t\"mine\": {\n\t\t\t\"type\": \"local\","));
+        assert!(written.contains("\n\t\t\"github\": {\n\t\t\t\"type\": \"local\","));
+        assert!(written.contains("\n\t},\n\t\"theme\""));
+        let reread = McpConfigFile::parse(&written, McpFormat::OpencodeJson).unwrap();
+        assert!(reread.get("mine").is_some() && reread.get("github").is_some());
+
+        // A missing key is added after the last member, leaving its comment in place
+        let mut file = McpConfigFile::parse(
+            "{\n    \"theme\": \"dark\" // keep\n}\n",
+            McpFormat::OpencodeJson,
+        )
+        .unwrap();
+        file.set("x", &Value::Object(Map::new()));
+        assert_eq!(
+            file.render(),
+            "{\n    \"theme\": \"dark\", // keep\n    \"mcp\": {\n        \"x\": {}\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_save_replaces_the_file() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("nested/.mcp.json");
+        let mut file = McpConfigFile::load(&path, McpFormat::ClaudeJson).unwrap();
+        file.set(
+            "github",
+            &render_mcp_entry(&github(), McpFormat::ClaudeJson),
+        );
+        file.save(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), file.render());
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_remote_entries_and_invalid_files() {
+        let server = McpServer {
+            command: None,
+            args: vec![],
+            env: vec![],
+            url: Some("https://mcp.example.com".to_string()),
+            headers: vec![("Authorization".to_string(), "Bearer t".to_string())],
+            ..github()
+        };
+        let codex = render_mcp_entry(&server, McpFormat::CodexToml);
+        assert!(codex.get("http_headers").is_some());
+        assert_eq!(
+            render_mcp_entry(&server, McpFormat::OpencodeJson).get("type"),
+            Some(&Value::String("remote".to_string()))
+        );
+
+        assert!(McpConfigFile::parse("[1]", McpFormat::ClaudeJson).is_err());
+        assert!(McpConfigFile::parse("{\"mcp\": []}", McpFormat::OpencodeJson).is_err());
+        assert!(McpConfigFile::parse("model = ", McpFormat::CodexToml).is_err());
+        assert_eq!(
+            McpConfigFile::parse("", McpFormat::ClaudeJson)
+                .unwrap()
+                .render(),
+            "{}\n"
+        );
+    }
+
+    #[test]
+    fn test_env_placeholders_per_agent() {
+        let server = McpServer {
+            env: vec![("TOKEN".to_string(), "${env:HANA_TEST_TOKEN}".to_string())],
+            ..github()
+        };
+        let env = vec![("HANA_TEST_TOKEN".to_string(), "s3cret".to_string())];
+        let token = |format| {
+            expected_mcp_entry(&server, format, &env)
+                .unwrap()
+                .to_string()
+        };
+        assert!(token(McpFormat::ClaudeJson).contains("\"${HANA_TEST_TOKEN}\""));
+        assert!(token(McpFormat::OpencodeJson).contains("\"{env:HANA_TEST_TOKEN}\""));
+        assert!(token(McpFormat::CodexToml).contains("\"s3cret\""));
+        assert_eq!(
+            expected_mcp_entry(&server, McpFormat::CodexToml, &[]),
+            Err("HANA_TEST_TOKEN".to_string())
+        );
+    }
+}
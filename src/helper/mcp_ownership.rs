@@ -0,0 +1,104 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::config::AgentName;
+
+const HEADER: &str = "# MCP servers hana wrote into agent config files, as `<agent> <server>`.\n";
+
+/// The MCP server entries hana wrote, per agent. Sync only updates or removes entries listed
+/// here, so servers the user added by hand under the same files are never touched.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct McpOwnership {
+    entries: BTreeSet<(String, String)>,
+}
+
+impl McpOwnership {
+    /// Read `path`; a missing file owns nothing.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        let entries = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (agent, server) = line.split_once(' ')?;
+                Some((agent.to_string(), server.trim().to_string()))
+            })
+            .collect();
+        Ok(Self { entries })
+    }
+
+    /// Write the set back; an empty set removes the file.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if self.entries.is_empty() {
+            return match fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let mut content = HEADER.to_string();
+        for (agent, server) in &self.entries {
+            content.push_str(&format!("{agent} {server}\n"));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)
+    }
+
+    pub fn owns(&self, agent: AgentName, server: &str) -> bool {
+        self.entries
+            .contains(&(agent.as_str().to_string(), server.to_string()))
+    }
+
+    /// Servers hana wrote for `agent`.
+    pub fn servers(&self, agent: AgentName) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|(a, _)| a == agent.as_str())
+            .map(|(_, server)| server.clone())
+            .collect()
+    }
+
+    pub fn insert(&mut self, agent: AgentName, server: &str) {
+        self.entries
+            .insert((agent.as_str().to_string(), server.to_string()));
+    }
+
+    pub fn remove(&mut self, agent: AgentName, server: &str) {
+        self.entries
+            .remove(&(agent.as_str().to_string(), server.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip_and_empty_set_removes_file() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(".agents/.hanamcp");
+        let mut owned = McpOwnership::load(&path).unwrap();
+        owned.insert(AgentName::Claude, "github");
+        owned.insert(AgentName::Codex, "github");
+        owned.save(&path).unwrap();
+
+        let mut reread = McpOwnership::load(&path).unwrap();
+        assert_eq!(reread, owned);
+        assert!(reread.owns(AgentName::Claude, "github"));
+        assert!(!reread.owns(AgentName::Opencode, "github"));
+        assert_eq!(reread.servers(AgentName::Codex), vec!["github".to_string()]);
+
+        reread.remove(AgentName::Claude, "github");
+        reread.remove(AgentName::Codex, "github");
+        reread.save(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
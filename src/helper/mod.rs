@@ -1,6 +1,31 @@
 pub mod broadcast_target_symlink;
+pub mod classify_target_entries;
+pub mod collect_managed_links;
 pub mod collect_source_skills;
 pub mod collect_target_skills;
+pub mod expand_env_refs;
+pub mod expand_includes;
+pub mod expand_path_glob;
+pub mod find_divergent_instructions;
+pub mod generate_instruction_file;
+pub mod git_tracked_paths;
+pub mod glob_match;
+pub mod line_diff;
+pub mod link_points_into;
+pub mod link_target;
+pub mod list_dir_entries;
+pub mod load_env_file;
+pub mod load_ignore_rules;
+pub mod managed_block;
+pub mod mcp_config_file;
+pub mod mcp_ownership;
 pub mod move_target_skills;
+pub mod parse_frontmatter;
+pub mod plan_rendered_files;
 pub mod relative_path;
+pub mod render_definition;
+pub mod render_instructions;
+pub mod resolve_managed_files;
+pub mod resolve_skill_rule;
 pub mod resolve_target_destinations;
+pub mod update_gitignore_block;
@@ -68,13 +68,11 @@ pub fn move_target_skills(
             .collect();
 
         if sources.len() > 1 {
-            let agent_names: Vec<String> = sources
-                .iter()
-                .map(|(a, _)| a.as_str().to_string())
-                .collect();
+            let mut agents: Vec<AgentName> = sources.iter().map(|(a, _)| *a).collect();
+            agents.sort_by_key(|a| a.as_str());
             warnings.push(SyncWarning::SkillConflict {
                 name: name.clone(),
-                agents: agent_names,
+                agents,
             });
             continue;
         }
@@ -87,7 +85,8 @@ pub fn move_target_skills(
         let dest = source_dir.join(name.as_str());
 
         if dest.exists() {
-            let should_remove = force || (dest.is_dir() && !dest.is_symlink() && is_dir_empty(&dest));
+            let should_remove =
+                force || (dest.is_dir() && !dest.is_symlink() && is_dir_empty(&dest));
             if should_remove {
                 if !dry_run {
                     let remove_result = if dest.is_symlink() {
@@ -108,13 +107,22 @@ pub fn move_target_skills(
             } else {
                 warnings.push(SyncWarning::SourceSkillConflict {
                     skill: name.clone(),
-                    agent: agent.as_str().to_string(),
+                    agent: *agent,
                 });
                 continue;
             }
         }
 
         if !dry_run {
+            if let Some(parent) = dest.parent() {
+                if let Err(e) = fs::create_dir_all(parent) {
+                    warnings.push(SyncWarning::IoFailed {
+                        operation: format!("create skill category ({name})"),
+                        detail: e.to_string(),
+                    });
+                    continue;
+                }
+            }
             if let Err(e) = fs::rename(path, &dest) {
                 warnings.push(SyncWarning::IoFailed {
                     operation: format!("collect skill ({name}, {agent})"),
@@ -196,7 +204,7 @@ mod tests {
         assert!(move_result.warnings.iter().any(|w| matches!(
             w,
             SyncWarning::SourceSkillConflict { skill, agent }
-                if skill == "my-skill" && agent == "pi"
+                if skill == "my-skill" && *agent == AgentName::Pi
         )));
         assert!(pi_skill.is_dir());
         assert!(!pi_skill.is_symlink());
@@ -265,8 +273,8 @@ mod tests {
             w,
             SyncWarning::SkillConflict { name, agents }
                 if name == "dup-skill"
-                    && agents.contains(&"pi".to_string())
-                    && agents.contains(&"claude".to_string())
+                    && agents.contains(&AgentName::Pi)
+                    && agents.contains(&AgentName::Claude)
         )));
         assert!(!source_dir.join("dup-skill").exists());
         assert!(pi_skill.is_dir());
@@ -331,6 +339,30 @@ mod tests {
         assert!(!pi_skill.exists());
     }
 
+    #[test]
+    fn test_mv_skills_into_namespace_category() {
+        let tmp = TempDir::new().unwrap();
+        let source_dir = tmp.path().join(".agents/skills");
+        fs::create_dir_all(&source_dir).unwrap();
+
+        let claude_skill = tmp.path().join(".claude/skills/frontend--react-review");
+        fs::create_dir_all(&claude_skill).unwrap();
+        fs::write(claude_skill.join("SKILL.md"), "# Review").unwrap();
+
+        let collected_by_agent = HashMap::from([(
+            AgentName::Claude,
+            vec![("frontend/react-review".to_string(), claude_skill.clone())],
+        )]);
+
+        let tasks = move_target_skills(&collected_by_agent, &source_dir, false, false)
+            .unwrap()
+            .tasks;
+
+        assert_eq!(tasks[0].skill, "frontend/react-review");
+        assert!(source_dir.join("frontend/react-review/SKILL.md").is_file());
+        assert!(!claude_skill.exists());
+    }
+
     #[test]
     fn test_is_dir_empty_with_nested_empty_dirs() {
         let tmp = TempDir::new().unwrap();
@@ -0,0 +1,231 @@
+/// A value in a markdown frontmatter block (a small YAML subset).
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontmatterValue {
+    Scalar(String),
+    List(Vec<String>),
+    Map(Vec<(String, FrontmatterValue)>),
+}
+
+impl FrontmatterValue {
+    /// List items; a scalar is treated as a one-element list.
+    pub fn as_list(&self) -> Option<Vec<String>> {
+        match self {
+            Self::Scalar(s) if s.is_empty() => Some(vec![]),
+            Self::Scalar(s) => Some(vec![s.clone()]),
+            Self::List(items) => Some(items.clone()),
+            Self::Map(_) => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&FrontmatterValue> {
+        match self {
+            Self::Map(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FrontmatterValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Scalar(s) => write!(f, "{s}"),
+            Self::List(items) => write!(f, "[{}]", items.join(", ")),
+            Self::Map(fields) => {
+                let parts: Vec<String> = fields.iter().map(|(k, v)| format!("{k}: {v}")).collect();
+                write!(f, "{{{}}}", parts.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Frontmatter {
+    pub fields: Vec<(String, FrontmatterValue)>,
+}
+
+impl Frontmatter {
+    pub fn get(&self, key: &str) -> Option<&FrontmatterValue> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+}
+
+/// Split a markdown document into its `---` delimited frontmatter and body.
+/// Returns `None` when the document has no frontmatter block.
+pub fn parse_frontmatter(content: &str) -> Option<(Frontmatter, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+
+    let mut offset = 0;
+    let mut lines = Vec::new();
+    let mut body = None;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let trimmed = line.trim_end_matches(['\n', '\r']);
+        if trimmed == "---" {
+            body = Some(&rest[offset..]);
+            break;
+        }
+        lines.push(trimmed);
+    }
+    let body = body?;
+
+    let mut pos = 0;
+    let fields = parse_block(&lines, &mut pos, 0);
+    Some((Frontmatter { fields }, body))
+}
+
+fn parse_block(lines: &[&str], pos: &mut usize, indent: usize) -> Vec<(String, FrontmatterValue)> {
+    let mut fields = Vec::new();
+
+    while let Some(line) = next_content_line(lines, pos) {
+        let line_indent = indent_of(line);
+        if line_indent < indent {
+            break;
+        }
+        *pos += 1;
+        if line_indent > indent {
+            continue;
+        }
+
+        let Some((key, rest)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let key = unquote(key.trim());
+        let rest = rest.trim();
+
+        let value = if rest == "|" || rest == ">" || rest == "|-" || rest == ">-" {
+            let mut parts = Vec::new();
+            while let Some(next) = next_content_line(lines, pos) {
+                if indent_of(next) <= indent {
+                    break;
+                }
+                parts.push(next.trim());
+                *pos += 1;
+            }
+            let sep = if rest.starts_with('|') { "\n" } else { " " };
+            FrontmatterValue::Scalar(parts.join(sep))
+        } else if !rest.is_empty() {
+            parse_inline(rest)
+        } else {
+            match next_content_line(lines, pos) {
+                Some(next) if next.trim_start().starts_with("- ") && indent_of(next) >= indent => {
+                    let item_indent = indent_of(next);
+                    let mut items = Vec::new();
+                    while let Some(item) = next_content_line(lines, pos) {
+                        if indent_of(item) != item_indent {
+                            break;
+                        }
+                        let Some(item) = item.trim_start().strip_prefix("- ") else {
+                            break;
+                        };
+                        items.push(unquote(item.trim()));
+                        *pos += 1;
+                    }
+                    FrontmatterValue::List(items)
+                }
+                Some(next) if indent_of(next) > indent => {
+                    FrontmatterValue::Map(parse_block(lines, pos, indent_of(next)))
+                }
+                _ => FrontmatterValue::Scalar(String::new()),
+            }
+        };
+
+        fields.push((key, value));
+    }
+
+    fields
+}
+
+fn next_content_line<'a>(lines: &[&'a str], pos: &mut usize) -> Option<&'a str> {
+    while let Some(line) = lines.get(*pos) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            *pos += 1;
+            continue;
+        }
+        return Some(line);
+    }
+    None
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn parse_inline(raw: &str) -> FrontmatterValue {
+    match raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+        Some(inner) => FrontmatterValue::List(
+            inner
+                .split(',')
+                .map(|item| unquote(item.trim()))
+                .filter(|item| !item.is_empty())
+                .collect(),
+        ),
+        None => FrontmatterValue::Scalar(unquote(raw)),
+    }
+}
+
+fn unquote(raw: &str) -> String {
+    let stripped = raw
+        .strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .or_else(|| raw.strip_prefix('\'').and_then(|r| r.strip_suffix('\'')));
+    stripped.unwrap_or(raw).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_frontmatter() {
+        assert!(parse_frontmatter("# Skill\n").is_none());
+        assert!(parse_frontmatter("---\nname: x\n").is_none());
+    }
+
+    #[test]
+    fn test_scalars_and_body() {
+        let (fm, body) =
+            parse_frontmatter("---\nname: my-skill\ndescription: \"Does things\"\n---\n# Body\n")
+                .unwrap();
+        assert_eq!(
+            fm.get("name").unwrap(),
+            &FrontmatterValue::Scalar("my-skill".to_string())
+        );
+        assert_eq!(
+            fm.get("description").unwrap(),
+            &FrontmatterValue::Scalar("Does things".to_string())
+        );
+        assert_eq!(body, "# Body\n");
+    }
+
+    #[test]
+    fn test_nested_map_with_lists() {
+        let content = "---\nname: hooks\nhana:\n  targets: [claude]\n  exclude:\n    - pi\n    - 'codex'\n---\n";
+        let (fm, _) = parse_frontmatter(content).unwrap();
+        let hana = fm.get("hana").unwrap();
+        assert_eq!(
+            hana.get("targets").unwrap().as_list(),
+            Some(vec!["claude".to_string()])
+        );
+        assert_eq!(
+            hana.get("exclude").unwrap().as_list(),
+            Some(vec!["pi".to_string(), "codex".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_block_scalar() {
+        let content = "---\ndescription: >\n  first line\n  second line\nname: x\n---\n";
+        let (fm, _) = parse_frontmatter(content).unwrap();
+        assert_eq!(
+            fm.get("description").unwrap(),
+            &FrontmatterValue::Scalar("first line second line".to_string())
+        );
+        assert_eq!(
+            fm.get("name").unwrap(),
+            &FrontmatterValue::Scalar("x".to_string())
+        );
+    }
+}
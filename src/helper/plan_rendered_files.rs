@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{AgentName, RenderTarget};
+use crate::helper::list_dir_entries::list_dir_entries;
+use crate::helper::render_definition::expected_rendered_file;
+
+/// What sync should do with one definition for one agent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderPlan {
+    Write {
+        path: PathBuf,
+        content: String,
+    },
+    /// The agent cannot take this definition; the reason is shown to the user
+    Unsupported(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedRender {
+    /// Source file name, e.g. `review.md`
+    pub name: String,
+    pub agent: AgentName,
+    pub plan: RenderPlan,
+}
+
+/// One plan per `*.md` in `source_dir` and target, source-major. Unreadable sources are skipped.
+pub fn plan_rendered_files(
+    source_dir: &Path,
+    targets: &[(AgentName, Option<PathBuf>, &RenderTarget)],
+) -> Vec<PlannedRender> {
+    list_dir_entries(source_dir, false)
+        .into_iter()
+        .filter(|(name, path)| name.ends_with(".md") && path.is_file())
+        .filter_map(|(name, path)| Some((name, fs::read_to_string(path).ok()?)))
+        .flat_map(|(name, source)| {
+            targets
+                .iter()
+                .map(|(agent, dest_dir, target)| {
+                    let plan = match dest_dir {
+                        None => RenderPlan::Unsupported("no directory at this level".to_string()),
+                        Some(dir) => match expected_rendered_file(&name, &source, target) {
+                            Ok(content) => RenderPlan::Write {
+                                path: dir.join(&name),
+                                content,
+                            },
+                            Err(reason) => RenderPlan::Unsupported(reason),
+                        },
+                    };
+                    PlannedRender {
+                        name: name.clone(),
+                        agent: *agent,
+                        plan,
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_plans_per_source_and_agent() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("review.md"),
+            "---\ndescription: Review\nmodel: opus\n---\nGo.\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("notes.txt"), "skip").unwrap();
+        let config = Config::parse("[source]\ncommands = true\n").unwrap();
+        let targets = config.resolve_command_targets(tmp.path(), false);
+
+        let plans = plan_rendered_files(tmp.path(), &targets);
+
+        let summary: Vec<(&str, bool)> = plans
+            .iter()
+            .map(|p| (p.agent.as_str(), matches!(p.plan, RenderPlan::Write { .. })))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("claude", true),
+                ("codex", false),
+                ("pi", false),
+                ("opencode", true)
+            ]
+        );
+        assert_eq!(
+            plans[1].plan,
+            RenderPlan::Unsupported("no directory at this level".to_string())
+        );
+    }
+}
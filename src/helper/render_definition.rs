@@ -0,0 +1,179 @@
+use crate::config::{ListStyle, RenderTarget};
+use crate::helper::parse_frontmatter::{Frontmatter, FrontmatterValue, parse_frontmatter};
+use crate::helper::render_instructions::generated_frontmatter_file;
+
+/// Translate a canonical definition (subagent, command) into one agent's dialect: frontmatter keys are
+/// renamed or dropped per the table, lists written in the target's style, `set` fields added.
+/// The body is kept as-is; the output always starts with a frontmatter block.
+pub fn render_definition(source: &str, target: &RenderTarget) -> String {
+    let (frontmatter, body) = parse_frontmatter(source).unwrap_or((Frontmatter::default(), source));
+
+    let mut fields: Vec<(String, FrontmatterValue)> = frontmatter
+        .fields
+        .into_iter()
+        .filter_map(
+            |(key, value)| match target.fields.iter().find(|(from, _)| *from == key) {
+                Some((_, Some(to))) => Some((to.clone(), value)),
+                Some((_, None)) => None,
+                None => Some((key, value)),
+            },
+        )
+        .collect();
+    for (key, value) in &target.set {
+        let value = FrontmatterValue::Scalar(value.clone());
+        match fields.iter_mut().find(|(k, _)| k == key) {
+            Some(existing) => existing.1 = value,
+            None => fields.push((key.clone(), value)),
+        }
+    }
+
+    let mut out = String::from("---\n");
+    for (key, value) in &fields {
+        write_field(&mut out, key, value, target.lists, 0);
+    }
+    out.push_str("---\n");
+    out.push_str(body);
+    out
+}
+
+/// The full generated file for one agent, with header, or why the agent cannot take it.
+pub fn expected_rendered_file(
+    name: &str,
+    source: &str,
+    target: &RenderTarget,
+) -> Result<String, String> {
+    if let Some((frontmatter, _)) = parse_frontmatter(source)
+        && let Some(key) = target
+            .unsupported
+            .iter()
+            .find(|key| frontmatter.get(key).is_some())
+    {
+        return Err(format!("`{key}` has no equivalent"));
+    }
+    Ok(generated_frontmatter_file(
+        name,
+        &render_definition(source, target),
+    ))
+}
+
+fn write_field(
+    out: &mut String,
+    key: &str,
+    value: &FrontmatterValue,
+    lists: ListStyle,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    match value {
+        FrontmatterValue::Scalar(text) if text.contains('\n') => {
+            out.push_str(&format!("{indent}{key}: |\n"));
+            for line in text.lines() {
+                out.push_str(&format!("{indent}  {line}\n"));
+            }
+        }
+        FrontmatterValue::Scalar(text) => {
+            out.push_str(&format!("{indent}{key}: {}\n", scalar(text)))
+        }
+        FrontmatterValue::List(items) => match lists {
+            ListStyle::Csv => out.push_str(&format!("{indent}{key}: {}\n", items.join(", "))),
+            ListStyle::Yaml => {
+                out.push_str(&format!("{indent}{key}:\n"));
+                for item in items {
+                    out.push_str(&format!("{indent}  - {}\n", scalar(item)));
+                }
+            }
+            ListStyle::Map => {
+                out.push_str(&format!("{indent}{key}:\n"));
+                for item in items {
+                    out.push_str(&format!("{indent}  {item}: true\n"));
+                }
+            }
+        },
+        FrontmatterValue::Map(entries) => {
+            out.push_str(&format!("{indent}{key}:\n"));
+            for (k, v) in entries {
+                write_field(out, k, v, lists, depth + 1);
+            }
+        }
+    }
+}
+
+/// Quote scalars YAML would otherwise misread (`: `, leading `#`, `[`, …).
+fn scalar(text: &str) -> String {
+    let needs_quotes = text.contains(": ")
+        || text.contains(" #")
+        || text.starts_with([
+            '#', '[', '{', '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`',
+        ]);
+    if needs_quotes {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AgentName, Config};
+
+    const REVIEWER: &str = "---\nname: reviewer\ndescription: Reviews code: carefully\ntools: [Read, Grep]\nmodel: sonnet\n---\nYou review code.\n";
+
+    fn target(agent: AgentName) -> RenderTarget {
+        Config::default().targets[&agent].subagents.clone().unwrap()
+    }
+
+    #[test]
+    fn test_claude_uses_csv_lists() {
+        assert_eq!(
+            render_definition(REVIEWER, &target(AgentName::Claude)),
+            "---\nname: reviewer\ndescription: \"Reviews code: carefully\"\ntools: Read, Grep\nmodel: sonnet\n---\nYou review code.\n"
+        );
+    }
+
+    #[test]
+    fn test_opencode_drops_name_and_maps_tools() {
+        assert_eq!(
+            render_definition(REVIEWER, &target(AgentName::Opencode)),
+            "---\ndescription: \"Reviews code: carefully\"\ntools:\n  Read: true\n  Grep: true\nmodel: sonnet\nmode: subagent\n---\nYou review code.\n"
+        );
+    }
+
+    #[test]
+    fn test_renames_and_yaml_lists() {
+        let target = RenderTarget {
+            path: None,
+            path_global: None,
+            fields: vec![
+                ("tools".to_string(), Some("allowed-tools".to_string())),
+                ("model".to_string(), None),
+            ],
+            lists: ListStyle::Yaml,
+            set: vec![],
+            unsupported: vec![],
+        };
+        assert_eq!(
+            render_definition(REVIEWER, &target),
+            "---\nname: reviewer\ndescription: \"Reviews code: carefully\"\nallowed-tools:\n  - Read\n  - Grep\n---\nYou review code.\n"
+        );
+        assert_eq!(
+            render_definition("No frontmatter\n", &target),
+            "---\n---\nNo frontmatter\n"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_field_is_reported() {
+        let pi = Config::default().targets[&AgentName::Pi]
+            .commands
+            .clone()
+            .unwrap();
+        assert_eq!(
+            expected_rendered_file("review.md", REVIEWER, &pi),
+            Err("`model` has no equivalent".to_string())
+        );
+        let file = expected_rendered_file("review.md", "---\ndescription: Review\n---\nGo.\n", &pi)
+            .unwrap();
+        assert!(file.starts_with("---\n# hana:generated from review.md"));
+    }
+}
@@ -0,0 +1,177 @@
+use std::borrow::Cow;
+
+const HEADER_PREFIX: &str = "<!-- hana:generated";
+/// Header variant for files that must start with frontmatter (subagents, commands)
+const FRONTMATTER_HEADER_PREFIX: &str = "# hana:generated";
+
+/// Render one agent's instruction body: `<!-- hana:only a, b -->` … `<!-- hana:end -->` blocks
+/// are kept only for the listed agents, then the agent's fragment (if any) is appended.
+pub fn render_instructions(source: &str, agent: &str, fragment: Option<&str>) -> String {
+    let mut scopes: Vec<bool> = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+
+    for line in source.lines() {
+        if let Some(agents) = directive(line, "only") {
+            scopes.push(agents.split(',').any(|a| a.trim() == agent));
+            continue;
+        }
+        if directive(line, "end").is_some() {
+            scopes.pop();
+            continue;
+        }
+        if scopes.iter().all(|visible| *visible) {
+            lines.push(line);
+        }
+    }
+
+    let mut body = lines.join("\n").trim_end().to_string();
+    if let Some(fragment) = fragment.map(str::trim).filter(|f| !f.is_empty()) {
+        body.push_str("\n\n");
+        body.push_str(fragment);
+    }
+    body.push('\n');
+    body
+}
+
+/// `<!-- hana:<name> args -->` → `Some("args")`.
+fn directive<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    line.trim()
+        .strip_prefix("<!--")?
+        .strip_suffix("-->")?
+        .trim()
+        .strip_prefix("hana:")?
+        .strip_prefix(name)
+        .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+        .map(str::trim)
+}
+
+/// Full file contents: a header recording the body hash, then the body.
+pub fn generated_file(source_name: &str, body: &str) -> String {
+    format!(
+        "{HEADER_PREFIX} from {source_name} hash={} — edit the source, not this file -->\n\n{body}",
+        content_hash(body)
+    )
+}
+
+/// Like `generated_file`, for a document whose first line must stay `---`: the header is
+/// a YAML comment on the second line, and the hash covers the document without it.
+pub fn generated_frontmatter_file(source_name: &str, document: &str) -> String {
+    let rest = document.strip_prefix("---\n").unwrap_or(document);
+    format!(
+        "---\n{FRONTMATTER_HEADER_PREFIX} from {source_name} hash={} — edit the source, not this file\n{rest}",
+        content_hash(document)
+    )
+}
+
+/// A previously generated file, split into the hash recorded at generation time and the body.
+#[derive(Debug, PartialEq)]
+pub struct GeneratedFile<'a> {
+    pub hash: &'a str,
+    pub body: Cow<'a, str>,
+}
+
+impl GeneratedFile<'_> {
+    /// The body no longer matches the recorded hash: someone edited the generated file.
+    pub fn is_edited(&self) -> bool {
+        content_hash(&self.body) != self.hash
+    }
+}
+
+pub fn parse_generated(content: &str) -> Option<GeneratedFile<'_>> {
+    let (header, rest) = content.split_once('\n')?;
+    if header.starts_with(HEADER_PREFIX) {
+        return Some(GeneratedFile {
+            hash: header_hash(header)?,
+            body: Cow::Borrowed(rest.strip_prefix('\n').unwrap_or(rest)),
+        });
+    }
+
+    let (header, rest) = rest.split_once('\n')?;
+    if content.starts_with("---\n") && header.starts_with(FRONTMATTER_HEADER_PREFIX) {
+        return Some(GeneratedFile {
+            hash: header_hash(header)?,
+            body: Cow::Owned(format!("---\n{rest}")),
+        });
+    }
+    None
+}
+
+fn header_hash(header: &str) -> Option<&str> {
+    header.split_once("hash=")?.1.split_whitespace().next()
+}
+
+/// FNV-1a (64-bit) as 16 hex digits. Stable across platforms and releases.
+pub fn content_hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "# Project\n\nShared rules.\n\n<!-- hana:only claude -->\nUse hooks.\n<!-- hana:end -->\n\n<!-- hana:only pi, codex -->\nPi notes.\n<!-- hana:end -->\n";
+
+    #[test]
+    fn test_only_blocks_per_agent() {
+        let claude = render_instructions(SOURCE, "claude", None);
+        assert!(claude.contains("Use hooks."));
+        assert!(!claude.contains("Pi notes."));
+        assert!(!claude.contains("hana:"));
+
+        let pi = render_instructions(SOURCE, "pi", None);
+        assert!(pi.contains("Pi notes."));
+        assert!(!pi.contains("Use hooks."));
+    }
+
+    #[test]
+    fn test_fragment_is_appended() {
+        let body = render_instructions("# Base\n", "claude", Some("## Claude only\n"));
+        assert_eq!(body, "# Base\n\n## Claude only\n");
+        assert_eq!(
+            render_instructions("# Base\n", "claude", Some("  \n")),
+            "# Base\n"
+        );
+    }
+
+    #[test]
+    fn test_generated_round_trip_and_edit_detection() {
+        let body = render_instructions(SOURCE, "claude", None);
+        let file = generated_file("AGENTS.md", &body);
+
+        let parsed = parse_generated(&file).unwrap();
+        assert_eq!(parsed.body, body);
+        assert!(!parsed.is_edited());
+
+        let edited = file.replace("Use hooks.", "Use hooks carefully.");
+        assert!(parse_generated(&edited).unwrap().is_edited());
+
+        assert!(parse_generated("# Hand written\n").is_none());
+    }
+
+    #[test]
+    fn test_frontmatter_header_keeps_frontmatter_first() {
+        let document = "---\nname: reviewer\n---\nReview code.\n";
+        let file = generated_frontmatter_file("reviewer.md", document);
+        assert!(file.starts_with("---\n# hana:generated from reviewer.md hash="));
+        assert!(file.ends_with("name: reviewer\n---\nReview code.\n"));
+
+        let parsed = parse_generated(&file).unwrap();
+        assert_eq!(parsed.body, document);
+        assert!(!parsed.is_edited());
+        assert!(
+            parse_generated(&file.replace("Review", "Skim"))
+                .unwrap()
+                .is_edited()
+        );
+        assert!(parse_generated(document).is_none());
+    }
+
+    #[test]
+    fn test_content_hash_is_fnv1a() {
+        assert_eq!(content_hash(""), "cbf29ce484222325");
+        assert_eq!(content_hash("a"), "af63dc4c8601ec8c");
+    }
+}
@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::{AgentName, Config};
+
+/// One `[target.x] files` entry, resolved for its agent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagedFile {
+    pub agent: AgentName,
+    pub source: PathBuf,
+    pub dest: PathBuf,
+    /// `dest` as written in hana.toml, for display
+    pub display: String,
+}
+
+/// Extra files of every target at this level, in config order. Listing files is the opt-in;
+/// they do not depend on the target's `skills` or `instructions`.
+pub fn resolve_managed_files(config: &Config, base_dir: &Path, global: bool) -> Vec<ManagedFile> {
+    config
+        .agent_names()
+        .flat_map(|agent| {
+            let target = &config.targets[&agent];
+            let raw = if global {
+                &target.files_global
+            } else {
+                &target.files
+            };
+            config
+                .resolve_target_files(agent, base_dir, global)
+                .into_iter()
+                .zip(raw)
+                .map(move |((source, dest), file)| ManagedFile {
+                    agent,
+                    source,
+                    dest,
+                    display: file.dest.clone(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_do_not_depend_on_instructions() {
+        let config = Config::parse(
+            "[target.pi]\nfiles = [{ source = \"SYSTEM.md\", dest = \".pi/SYSTEM.md\" }]\n\n[target.claude]\ninstructions = false\nfiles = [{ source = \"a.md\", dest = \"b.md\" }]\n",
+        )
+        .unwrap();
+
+        let files = resolve_managed_files(&config, Path::new("/base"), false);
+        assert_eq!(
+            files,
+            vec![
+                ManagedFile {
+                    agent: AgentName::Claude,
+                    source: PathBuf::from("/base/a.md"),
+                    dest: PathBuf::from("/base/b.md"),
+                    display: "b.md".to_string(),
+                },
+                ManagedFile {
+                    agent: AgentName::Pi,
+                    source: PathBuf::from("/base/SYSTEM.md"),
+                    dest: PathBuf::from("/base/.pi/SYSTEM.md"),
+                    display: ".pi/SYSTEM.md".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_global_files() {
+        let config = Config::parse(
+            "[target.pi]\nfiles = [{ source = \"a.md\", dest = \"b.md\" }]\nfiles_global = [{ source = \"~/.agents/SYSTEM.md\", dest = \".pi/agent/SYSTEM.md\" }]\n",
+        )
+        .unwrap();
+
+        let files = resolve_managed_files(&config, Path::new("/home/u"), true);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].dest, PathBuf::from("/home/u/.pi/agent/SYSTEM.md"));
+        assert_eq!(files[0].display, ".pi/agent/SYSTEM.md");
+    }
+}
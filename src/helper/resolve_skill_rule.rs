@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::{Config, SkillRule};
+use crate::helper::parse_frontmatter::parse_frontmatter;
+
+/// Resolve the broadcast rule for a skill.
+/// `[skill.<name>]` in hana.toml wins over the `hana:` block in SKILL.md frontmatter.
+/// A skill outside the active profile goes to no agent.
+/// Also returns the frontmatter agent names that match no target; they are left out of the rule.
+pub fn resolve_skill_rule(
+    config: &Config,
+    skill_dir: &Path,
+    skill: &str,
+) -> (SkillRule, Vec<String>) {
+    if !config.profile_allows(skill) {
+        let nowhere = SkillRule {
+            targets: Some(vec![]),
+            exclude: vec![],
+        };
+        return (nowhere, vec![]);
+    }
+    let (frontmatter_rule, unknown) =
+        read_frontmatter_rule(config, &skill_dir.join("SKILL.md")).unwrap_or_default();
+    let rule = match config.skill_rule(skill) {
+        Some(rule) => rule.merge(&frontmatter_rule),
+        None => frontmatter_rule,
+    };
+    (rule, unknown)
+}
+
+fn read_frontmatter_rule(config: &Config, skill_md: &Path) -> Option<(SkillRule, Vec<String>)> {
+    let content = fs::read_to_string(skill_md).ok()?;
+    let (frontmatter, _) = parse_frontmatter(&content)?;
+    let hana = frontmatter.get("hana")?;
+    let mut unknown = Vec::new();
+    let mut agents = |key: &str| {
+        let names = hana.get(key).and_then(|v| v.as_list())?;
+        let (known, missing): (Vec<_>, Vec<_>) = names
+            .into_iter()
+            .map(|name| config.find_agent(&name).ok_or(name))
+            .partition(Result::is_ok);
+        unknown.extend(missing.into_iter().filter_map(Result::err));
+        Some(known.into_iter().filter_map(Result::ok).collect::<Vec<_>>())
+    };
+    let targets = agents("targets");
+    let exclude = agents("exclude").unwrap_or_default();
+    Some((SkillRule { targets, exclude }, unknown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AgentName;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rule_from_frontmatter() {
+        let tmp = TempDir::new().unwrap();
+        let skill_dir = tmp.path().join("hooks");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nname: hooks\nhana:\n  targets: [claude]\n---\n# Hooks\n",
+        )
+        .unwrap();
+
+        let (rule, _) = resolve_skill_rule(&Config::default(), &skill_dir, "hooks");
+        assert!(rule.allows(AgentName::Claude));
+        assert!(!rule.allows(AgentName::Opencode));
+    }
+
+    #[test]
+    fn test_config_rule_overrides_frontmatter() {
+        let tmp = TempDir::new().unwrap();
+        let skill_dir = tmp.path().join("hooks");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nhana:\n  targets: [claude]\n  exclude: [pi]\n---\n",
+        )
+        .unwrap();
+
+        let config = Config::parse("[skill.hooks]\ntargets = [\"opencode\", \"pi\"]\n").unwrap();
+        let (rule, _) = resolve_skill_rule(&config, &skill_dir, "hooks");
+        assert!(rule.allows(AgentName::Opencode));
+        assert!(!rule.allows(AgentName::Claude));
+        assert!(!rule.allows(AgentName::Pi));
+    }
+
+    #[test]
+    fn test_unknown_frontmatter_agents_are_reported() {
+        let tmp = TempDir::new().unwrap();
+        let skill_dir = tmp.path().join("hooks");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nhana:\n  targets: [claud, opencode]\n  exclude: [pie]\n---\n",
+        )
+        .unwrap();
+
+        let (rule, unknown) = resolve_skill_rule(&Config::default(), &skill_dir, "hooks");
+        assert_eq!(unknown, vec!["claud".to_string(), "pie".to_string()]);
+        assert_eq!(rule.targets, Some(vec![AgentName::Opencode]));
+        assert!(rule.exclude.is_empty());
+
+        let config = Config::parse("[target.cursor]\ncustom = true\n").unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nhana:\n  targets: [cursor]\n---\n",
+        )
+        .unwrap();
+        let (rule, unknown) = resolve_skill_rule(&config, &skill_dir, "hooks");
+        assert!(unknown.is_empty());
+        assert_eq!(
+            rule.targets,
+            Some(vec![config.find_agent("cursor").unwrap()])
+        );
+    }
+
+    #[test]
+    fn test_no_rule_allows_everything() {
+        let tmp = TempDir::new().unwrap();
+        let (rule, _) = resolve_skill_rule(&Config::default(), tmp.path(), "missing");
+        assert!(AgentName::iter().all(|agent| rule.allows(agent)));
+    }
+
+    #[test]
+    fn test_skills_outside_profile_go_nowhere() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = Config::parse("[profile.review]\nskills = [\"review-*\"]\n").unwrap();
+        config.apply_profile("review").unwrap();
+        let (rule, _) = resolve_skill_rule(&config, tmp.path(), "deploy");
+        assert!(AgentName::iter().all(|agent| !rule.allows(agent)));
+        let (rule, _) = resolve_skill_rule(&config, tmp.path(), "review-pr");
+        assert!(AgentName::iter().all(|agent| rule.allows(agent)));
+    }
+}
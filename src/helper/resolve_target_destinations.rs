@@ -21,15 +21,21 @@ pub fn resolve_target_destinations(
         .filter_map(|agent| {
             let dest_path = match feature {
                 TargetFeature::Skills => {
-                    config.resolve_target_skills_path(agent.as_str(), base_dir, global)?
+                    config.resolve_target_skills_path(agent, base_dir, global)?
                 }
                 TargetFeature::Instructions => {
-                    config.resolve_target_instruction_path(agent.as_str(), base_dir, global)?
+                    config.resolve_target_instruction_path(agent, base_dir, global)?
                 }
             };
             if dest_path == source_path {
                 return None;
             }
+            // Under a profile, agents reading the `[source]` file directly keep their real file
+            if feature == TargetFeature::Instructions
+                && dest_path == config.resolve_configured_instruction_path(base_dir, global)
+            {
+                return None;
+            }
 
             Some((agent, dest_path))
         })
@@ -46,7 +52,7 @@ mod tests {
     fn test_resolve_target_destinations_filters_disabled_and_source() {
         let tmp = TempDir::new().unwrap();
         let mut config = Config::default();
-        config.targets.get_mut("pi").unwrap().skills = false;
+        config.targets.get_mut(&AgentName::Pi).unwrap().skills = false;
 
         let destinations =
             resolve_target_destinations(&config, tmp.path(), false, TargetFeature::Skills);
@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::helper::managed_block::{remove_managed_block, upsert_managed_block};
+use crate::sync::SyncWarning;
+
+const BLOCK_ID: &str = "links";
+
+/// Rewrite hana's `.gitignore` block so it lists exactly `links` (anchored, relative to the
+/// repo root). An empty list removes the block. Returns whether the file changed.
+pub fn update_gitignore_block(
+    base_dir: &Path,
+    links: &[PathBuf],
+    dry_run: bool,
+) -> Result<bool, SyncWarning> {
+    let path = base_dir.join(".gitignore");
+    let existing = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(SyncWarning::IoFailed {
+                operation: format!("read {}", path.display()),
+                detail: e.to_string(),
+            });
+        }
+    };
+
+    let entries: Vec<String> = links
+        .iter()
+        .filter_map(|link| link.strip_prefix(base_dir).ok())
+        .map(|rel| format!("/{}", rel.display()))
+        .collect();
+
+    let updated = if entries.is_empty() {
+        remove_managed_block(&existing, BLOCK_ID).unwrap_or_else(|| existing.clone())
+    } else {
+        upsert_managed_block(&existing, BLOCK_ID, &entries.join("\n"))
+    };
+    if updated == existing {
+        return Ok(false);
+    }
+
+    if !dry_run {
+        let result = if updated.is_empty() {
+            fs::remove_file(&path)
+        } else {
+            fs::write(&path, &updated)
+        };
+        result.map_err(|e| SyncWarning::IoFailed {
+            operation: format!("update {}", path.display()),
+            detail: e.to_string(),
+        })?;
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_writes_and_removes_block() {
+        let tmp = TempDir::new().unwrap();
+        let gitignore = tmp.path().join(".gitignore");
+        fs::write(&gitignore, "target/\n").unwrap();
+        let links = vec![
+            tmp.path().join(".claude/skills/my-skill"),
+            tmp.path().join("CLAUDE.md"),
+        ];
+
+        assert!(update_gitignore_block(tmp.path(), &links, false).unwrap());
+        assert_eq!(
+            fs::read_to_string(&gitignore).unwrap(),
+            "target/\n\n# >>> hana links >>>\n/.claude/skills/my-skill\n/CLAUDE.md\n# <<< hana links <<<\n"
+        );
+        assert!(!update_gitignore_block(tmp.path(), &links, false).unwrap());
+
+        assert!(update_gitignore_block(tmp.path(), &[], false).unwrap());
+        assert_eq!(fs::read_to_string(&gitignore).unwrap(), "target/\n");
+    }
+
+    #[test]
+    fn test_no_links_no_file() {
+        let tmp = TempDir::new().unwrap();
+        assert!(!update_gitignore_block(tmp.path(), &[], false).unwrap());
+        assert!(!tmp.path().join(".gitignore").exists());
+    }
+
+    #[test]
+    fn test_unreadable_gitignore_is_left_alone() {
+        let tmp = TempDir::new().unwrap();
+        let gitignore = tmp.path().join(".gitignore");
+        fs::write(&gitignore, [0xff, 0xfe, b'\n']).unwrap();
+        let links = vec![tmp.path().join("CLAUDE.md")];
+
+        let result = update_gitignore_block(tmp.path(), &links, false);
+        assert!(matches!(result, Err(SyncWarning::IoFailed { .. })));
+        assert_eq!(fs::read(&gitignore).unwrap(), vec![0xff, 0xfe, b'\n']);
+    }
+}
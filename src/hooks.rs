@@ -0,0 +1,434 @@
+//! `hana hooks`: git hooks that keep agents in sync across checkouts and merges.
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::helper::managed_block::{has_managed_block, remove_managed_block, upsert_managed_block};
+
+const BLOCK_ID: &str = "hooks";
+const SHEBANG: &str = "#!/bin/sh";
+/// Suffix of an existing hook hana moved aside to chain it
+const ORIG_SUFFIX: &str = "hana-orig";
+/// Runs the moved-aside hook first, with the same arguments, and stops on its failure
+const CHAIN: &str = "if [ -x \"$0.hana-orig\" ]; then\n  \"$0.hana-orig\" \"$@\" || exit $?\nfi";
+
+/// Hooks hana manages and the script each one runs.
+const HOOKS: [(&str, &str); 3] = [
+    (
+        "post-checkout",
+        // $3 = 1 for branch checkouts, 0 for file checkouts
+        "if [ \"$3\" = \"1\" ] && command -v hana >/dev/null 2>&1; then\n  hana sync\nfi",
+    ),
+    (
+        "post-merge",
+        "if command -v hana >/dev/null 2>&1; then\n  hana sync\nfi",
+    ),
+    (
+        "pre-commit",
+        "if command -v hana >/dev/null 2>&1; then\n  hana status --check || exit 1\nfi",
+    ),
+];
+
+// --- Options ---
+
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct HooksOptions {
+    pub dry_run: bool,
+}
+
+// --- Ok ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct HooksOk {
+    /// Resolved hooks directory (honours `core.hooksPath`)
+    pub hooks_dir: PathBuf,
+    pub changes: Vec<(String, HookChange)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HookChange {
+    /// New hook file written by hana
+    Created,
+    /// Existing hook moved to `<hook>.hana-orig` and run from hana's new hook
+    Chained,
+    /// Existing hana block rewritten
+    Updated,
+    /// Already up to date / nothing to remove
+    Unchanged,
+    /// hana block removed, the rest of the hook kept
+    Removed,
+    /// Hook file deleted (it only held the hana block)
+    Deleted,
+    /// hana's hook replaced by the original it chained
+    Restored,
+}
+
+// --- Error ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HooksError {
+    /// Not inside a git work tree (or git is not installed)
+    NotGitRepo { path: PathBuf, detail: String },
+    /// Reading or writing a hook failed
+    Io { path: PathBuf, detail: String },
+}
+
+impl std::fmt::Display for HooksError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotGitRepo { path, detail } => {
+                write!(f, "not a git repository ({}): {detail}", path.display())
+            }
+            Self::Io { path, detail } => write!(f, "{}: {detail}", path.display()),
+        }
+    }
+}
+
+// --- pub fn install / uninstall ---
+
+/// Add hana's block to each hook, creating the hook if needed. A hook hana did not write is
+/// moved to `<hook>.hana-orig` and run first from a new hook: appended lines would not run
+/// after an `exit`/`exec`, or in a non-sh hook.
+pub fn install(base_dir: &Path, opts: &HooksOptions) -> Result<HooksOk, HooksError> {
+    let hooks_dir = resolve_hooks_dir(base_dir)?;
+    let mut changes = Vec::new();
+
+    for (name, body) in HOOKS {
+        let path = hooks_dir.join(name);
+        let orig = orig_path(&path);
+        let existing = read_hook(&path)?;
+        let chained_body = format!("{CHAIN}\n{body}");
+
+        let (content, change) = match &existing {
+            None => (
+                upsert_managed_block(&format!("{SHEBANG}\n"), BLOCK_ID, body),
+                HookChange::Created,
+            ),
+            Some(content) if has_managed_block(content, BLOCK_ID) => {
+                let body = if orig.exists() { &chained_body } else { body };
+                let updated = upsert_managed_block(content, BLOCK_ID, body);
+                let change = if updated == *content {
+                    HookChange::Unchanged
+                } else {
+                    HookChange::Updated
+                };
+                (updated, change)
+            }
+            Some(_) if orig.exists() => {
+                return Err(HooksError::Io {
+                    path: orig,
+                    detail: format!("already exists, cannot chain {name}"),
+                });
+            }
+            Some(_) => (
+                upsert_managed_block(&format!("{SHEBANG}\n"), BLOCK_ID, &chained_body),
+                HookChange::Chained,
+            ),
+        };
+
+        if !opts.dry_run {
+            match change {
+                HookChange::Created => write_hook(&path, &content, true)?,
+                HookChange::Updated => write_hook(&path, &content, false)?,
+                HookChange::Chained => {
+                    // A rename keeps the original's bytes and mode for uninstall
+                    fs::rename(&path, &orig).map_err(|e| HooksError::Io {
+                        path: path.clone(),
+                        detail: e.to_string(),
+                    })?;
+                    write_hook(&path, &content, true)?;
+                }
+                _ => {}
+            }
+        }
+        changes.push((name.to_string(), change));
+    }
+
+    Ok(HooksOk { hooks_dir, changes })
+}
+
+/// Remove hana's block from each hook. Hooks hana created are deleted again, and chained
+/// originals are moved back.
+pub fn uninstall(base_dir: &Path, opts: &HooksOptions) -> Result<HooksOk, HooksError> {
+    let hooks_dir = resolve_hooks_dir(base_dir)?;
+    let mut changes = Vec::new();
+
+    for (name, _) in HOOKS {
+        let path = hooks_dir.join(name);
+        let orig = orig_path(&path);
+        let Some(content) = read_hook(&path)? else {
+            changes.push((name.to_string(), HookChange::Unchanged));
+            continue;
+        };
+        let Some(rest) = remove_managed_block(&content, BLOCK_ID) else {
+            changes.push((name.to_string(), HookChange::Unchanged));
+            continue;
+        };
+
+        let only_shebang = rest.trim().is_empty() || rest.trim() == SHEBANG;
+        let change = if orig.exists() {
+            HookChange::Restored
+        } else if only_shebang {
+            HookChange::Deleted
+        } else {
+            HookChange::Removed
+        };
+        if !opts.dry_run {
+            let result = match change {
+                HookChange::Restored => fs::rename(&orig, &path),
+                HookChange::Deleted => fs::remove_file(&path),
+                _ => fs::write(&path, rest),
+            };
+            result.map_err(|e| HooksError::Io {
+                path: path.clone(),
+                detail: e.to_string(),
+            })?;
+        }
+        changes.push((name.to_string(), change));
+    }
+
+    Ok(HooksOk { hooks_dir, changes })
+}
+
+fn orig_path(hook: &Path) -> PathBuf {
+    hook.with_extension(ORIG_SUFFIX)
+}
+
+/// `git rev-parse --git-path hooks` follows `core.hooksPath`, worktrees and submodules.
+fn resolve_hooks_dir(base_dir: &Path) -> Result<PathBuf, HooksError> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .current_dir(base_dir)
+        .output()
+        .map_err(|e| HooksError::NotGitRepo {
+            path: base_dir.to_path_buf(),
+            detail: e.to_string(),
+        })?;
+    if !output.status.success() {
+        return Err(HooksError::NotGitRepo {
+            path: base_dir.to_path_buf(),
+            detail: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(base_dir.join(path))
+}
+
+fn read_hook(path: &Path) -> Result<Option<String>, HooksError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read_to_string(path)
+        .map(Some)
+        .map_err(|e| HooksError::Io {
+            path: path.to_path_buf(),
+            detail: e.to_string(),
+        })
+}
+
+/// Only hooks hana creates are made executable; an existing hook keeps the mode its owner chose.
+fn write_hook(path: &Path, content: &str, created: bool) -> Result<(), HooksError> {
+    let io_err = |e: std::io::Error| HooksError::Io {
+        path: path.to_path_buf(),
+        detail: e.to_string(),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_err)?;
+    }
+    fs::write(path, content).map_err(io_err)?;
+    if !created {
+        return Ok(());
+    }
+    let mut perms = fs::metadata(path).map_err(io_err)?.permissions();
+    perms.set_mode(perms.mode() | 0o755);
+    fs::set_permissions(path, perms).map_err(io_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=hana", "-c", "user.email=hana@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    fn git_init(dir: &Path) {
+        git(dir, &["init", "-q"]);
+    }
+
+    #[test]
+    fn test_install_and_uninstall_fresh_repo() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        let hooks = tmp.path().join(".git/hooks");
+
+        let result = install(tmp.path(), &HooksOptions::default()).unwrap();
+        assert!(
+            result
+                .changes
+                .iter()
+                .all(|(_, c)| *c == HookChange::Created)
+        );
+        let post_merge = fs::read_to_string(hooks.join("post-merge")).unwrap();
+        assert!(post_merge.starts_with("#!/bin/sh\n"));
+        assert!(post_merge.contains("hana sync"));
+        let mode = fs::metadata(hooks.join("post-merge"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o111, 0o111);
+
+        let again = install(tmp.path(), &HooksOptions::default()).unwrap();
+        assert!(
+            again
+                .changes
+                .iter()
+                .all(|(_, c)| *c == HookChange::Unchanged)
+        );
+
+        let removed = uninstall(tmp.path(), &HooksOptions::default()).unwrap();
+        assert!(
+            removed
+                .changes
+                .iter()
+                .all(|(_, c)| *c == HookChange::Deleted)
+        );
+        assert!(!hooks.join("post-merge").exists());
+    }
+
+    #[test]
+    fn test_chains_existing_hook_and_restores_it() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        git(tmp.path(), &["config", "core.hooksPath", ".githooks"]);
+        let hooks = tmp.path().join(".githooks");
+        fs::create_dir_all(&hooks).unwrap();
+        let marker = tmp.path().join("ran");
+        let original = format!(
+            "#!/bin/sh\necho \"pre-commit $#\" >> '{}'\nexit 0\n",
+            marker.display()
+        );
+        let hook = hooks.join("pre-commit");
+        fs::write(&hook, &original).unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o750)).unwrap();
+
+        let result = install(tmp.path(), &HooksOptions::default()).unwrap();
+        assert!(
+            result
+                .changes
+                .contains(&("pre-commit".to_string(), HookChange::Chained))
+        );
+        assert!(
+            fs::read_to_string(&hook)
+                .unwrap()
+                .contains("hana status --check")
+        );
+        assert_eq!(
+            fs::read_to_string(hooks.join("pre-commit.hana-orig")).unwrap(),
+            original
+        );
+
+        // git runs hana's hook, which still runs the original
+        git(tmp.path(), &["commit", "-q", "--allow-empty", "-m", "one"]);
+        assert_eq!(fs::read_to_string(&marker).unwrap(), "pre-commit 0\n");
+
+        let again = install(tmp.path(), &HooksOptions::default()).unwrap();
+        assert!(
+            again
+                .changes
+                .contains(&("pre-commit".to_string(), HookChange::Unchanged))
+        );
+
+        let removed = uninstall(tmp.path(), &HooksOptions::default()).unwrap();
+        assert!(
+            removed
+                .changes
+                .contains(&("pre-commit".to_string(), HookChange::Restored))
+        );
+        assert_eq!(fs::read_to_string(&hook).unwrap(), original);
+        let mode = fs::metadata(&hook).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+        assert!(!hooks.join("pre-commit.hana-orig").exists());
+        assert!(!hooks.join("post-merge").exists());
+    }
+
+    #[test]
+    fn test_chain_refuses_to_overwrite_orig() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        let hooks = tmp.path().join(".git/hooks");
+        fs::write(hooks.join("post-merge"), "#!/bin/sh\n").unwrap();
+        fs::write(hooks.join("post-merge.hana-orig"), "#!/bin/sh\n").unwrap();
+
+        assert!(matches!(
+            install(tmp.path(), &HooksOptions::default()),
+            Err(HooksError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_update_keeps_hook_mode() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        let hook = tmp.path().join(".git/hooks/post-merge");
+        fs::write(
+            &hook,
+            "#!/bin/sh\n# >>> hana hooks >>>\nold\n# <<< hana hooks <<<\n",
+        )
+        .unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let result = install(tmp.path(), &HooksOptions::default()).unwrap();
+        assert!(
+            result
+                .changes
+                .contains(&("post-merge".to_string(), HookChange::Updated))
+        );
+        assert!(fs::read_to_string(&hook).unwrap().contains("hana sync"));
+        let mode = fs::metadata(&hook).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o644);
+    }
+
+    #[test]
+    fn test_honours_core_hooks_path() {
+        let tmp = TempDir::new().unwrap();
+        git_init(tmp.path());
+        Command::new("git")
+            .args(["config", "core.hooksPath", ".githooks"])
+            .current_dir(tmp.path())
+            .status()
+            .unwrap();
+
+        let result = install(tmp.path(), &HooksOptions::default()).unwrap();
+        assert!(result.hooks_dir.ends_with(".githooks"));
+        assert!(tmp.path().join(".githooks/post-checkout").exists());
+        assert!(!tmp.path().join(".git/hooks/post-checkout").exists());
+    }
+
+    #[test]
+    fn test_dry_run_and_not_a_repo() {
+        let tmp = TempDir::new().unwrap();
+        assert!(matches!(
+            install(tmp.path(), &HooksOptions::default()),
+            Err(HooksError::NotGitRepo { .. })
+        ));
+
+        git_init(tmp.path());
+        let result = install(tmp.path(), &HooksOptions { dry_run: true }).unwrap();
+        assert_eq!(result.changes.len(), 3);
+        assert!(!tmp.path().join(".git/hooks/post-merge").exists());
+    }
+}
@@ -1,8 +1,12 @@
+//! `hana init`: write a starter hana.toml.
+
 use std::fs;
 use std::path::{Path, PathBuf};
 
 // --- Options ---
 
+#[derive(Debug, Default)]
+#[non_exhaustive]
 pub struct InitOptions {
     pub global: bool,
     pub force: bool,
@@ -12,6 +16,7 @@ pub struct InitOptions {
 // --- Ok ---
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum InitOk {
     /// Config file created
     Created { path: PathBuf },
@@ -22,6 +27,7 @@ pub enum InitOk {
 // --- Error ---
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum InitError {
     /// Config file already exists (without --force)
     AlreadyExists { path: PathBuf },
@@ -111,6 +117,16 @@ skills_path = ".agents/skills"
 skills_path_global = "~/.agents/skills"
 instruction_path = "AGENTS.md"
 instruction_path_global = "~/.agents/AGENTS.md"
+subagents = false
+subagents_path = ".agents/agents"
+subagents_path_global = "~/.agents/agents"
+commands = false
+commands_path = ".agents/commands"
+commands_path_global = "~/.agents/commands"
+mcp_path = ".agents/mcp.toml"
+mcp_path_global = "~/.agents/mcp.toml"
+env_path = ".agents/.env"
+env_path_global = "~/.agents/.env"
 
 [target.claude]
 skills = true
@@ -153,6 +169,16 @@ skills_path = ".agents/skills"
 skills_path_global = "~/.agents/skills"
 instruction_path = "AGENTS.md"
 instruction_path_global = "~/.agents/AGENTS.md"
+subagents = false
+subagents_path = ".agents/agents"
+subagents_path_global = "~/.agents/agents"
+commands = false
+commands_path = ".agents/commands"
+commands_path_global = "~/.agents/commands"
+mcp_path = ".agents/mcp.toml"
+mcp_path_global = "~/.agents/mcp.toml"
+env_path = ".agents/.env"
+env_path_global = "~/.agents/.env"
 
 [target.claude]
 skills = true
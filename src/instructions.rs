@@ -0,0 +1,393 @@
+//! `hana instructions`: compare and reconcile diverged agent instruction files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{AgentName, Config};
+use crate::helper::broadcast_target_symlink::{LinkOutcome, link_one};
+use crate::helper::find_divergent_instructions::find_divergent_instructions;
+use crate::helper::generate_instruction_file::wants_generated;
+use crate::helper::line_diff::{line_diff, merge_with_markers};
+
+// --- Options ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Resolution {
+    /// Combine both, keeping runs only one side has and marking runs both changed git-style.
+    /// With conflicts the result goes to `<source>.hana-merge` and both files stay as they are
+    Merge,
+    /// Keep the source; the agent file is moved aside as `<file>.hana-backup`
+    Keep,
+    /// Replace the source with the agent file; the old source is kept as `<source>.hana-backup`
+    Adopt,
+}
+
+impl Resolution {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::Keep => "keep",
+            Self::Adopt => "adopt",
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ResolveOptions {
+    pub dry_run: bool,
+    pub global: bool,
+    pub agent: AgentName,
+    pub choice: Resolution,
+}
+
+impl ResolveOptions {
+    /// Settle `agent` with `choice`, for real and at the project level.
+    pub fn new(agent: AgentName, choice: Resolution) -> Self {
+        Self {
+            dry_run: false,
+            global: false,
+            agent,
+            choice,
+        }
+    }
+}
+
+// --- Ok ---
+
+pub use crate::helper::line_diff::DiffLine;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Divergence {
+    pub agent: AgentName,
+    pub path: PathBuf,
+    /// Source → agent file
+    pub diff: Vec<DiffLine>,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct DiffOk {
+    pub source: PathBuf,
+    pub source_exists: bool,
+    pub divergences: Vec<Divergence>,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ResolveOk {
+    pub agent: AgentName,
+    pub path: PathBuf,
+    pub source: PathBuf,
+    pub choice: Resolution,
+    /// Where the agent file (`keep`) or the old source (`adopt`) was saved
+    pub backup: Option<PathBuf>,
+    /// The merge has conflict markers to clean up
+    pub has_markers: bool,
+    /// Conflicting merge result, written next to the source instead of over it
+    pub merged: Option<PathBuf>,
+    /// The agent path was relinked; otherwise `hana sync` regenerates it (or, with a pending
+    /// merge, the agent file is left alone)
+    pub relinked: bool,
+}
+
+// --- Error ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ResolveError {
+    /// The agent has no hand-written instruction file that differs from the source
+    NotDivergent {
+        agent: AgentName,
+    },
+    /// `keep` with no source file to keep
+    NoSource {
+        path: PathBuf,
+    },
+    Io {
+        path: PathBuf,
+        detail: String,
+    },
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotDivergent { agent } => {
+                write!(
+                    f,
+                    "{agent} has no instruction file that differs from the source"
+                )
+            }
+            Self::NoSource { path } => {
+                write!(f, "{} does not exist; nothing to keep", path.display())
+            }
+            Self::Io { path, detail } => write!(f, "{}: {detail}", path.display()),
+        }
+    }
+}
+
+// --- pub fn diff ---
+
+/// Every hand-written agent instruction file that differs from the source, with a line diff.
+pub fn diff(config: &Config, base_dir: &Path, global: bool) -> DiffOk {
+    let source = config.resolve_source_instruction_path(base_dir, global);
+    let source_text = fs::read_to_string(&source).ok();
+
+    let divergences = find_divergent_instructions(config, base_dir, global)
+        .into_iter()
+        .map(|d| Divergence {
+            diff: line_diff(source_text.as_deref().unwrap_or(""), &d.content),
+            agent: d.agent,
+            path: d.path,
+        })
+        .collect();
+
+    DiffOk {
+        source_exists: source_text.is_some(),
+        source,
+        divergences,
+    }
+}
+
+// --- pub fn resolve ---
+
+/// Settle one divergent agent file against the source, then point the agent back at it.
+pub fn resolve(
+    config: &Config,
+    base_dir: &Path,
+    opts: &ResolveOptions,
+) -> Result<ResolveOk, ResolveError> {
+    let source = config.resolve_source_instruction_path(base_dir, opts.global);
+    let divergent = find_divergent_instructions(config, base_dir, opts.global)
+        .into_iter()
+        .find(|d| d.agent == opts.agent)
+        .ok_or(ResolveError::NotDivergent { agent: opts.agent })?;
+    let source_text = fs::read_to_string(&source).ok();
+    let io_err = |path: &Path, e: std::io::Error| ResolveError::Io {
+        path: path.to_path_buf(),
+        detail: e.to_string(),
+    };
+
+    let mut has_markers = false;
+    let new_source = match (opts.choice, &source_text) {
+        (Resolution::Keep, None) => return Err(ResolveError::NoSource { path: source }),
+        (Resolution::Keep, Some(_)) => None,
+        (Resolution::Adopt, _) | (Resolution::Merge, None) => Some(divergent.content.clone()),
+        (Resolution::Merge, Some(text)) => {
+            let (merged, conflicts) = merge_with_markers(
+                text,
+                &divergent.content,
+                &file_name(&source),
+                &file_name(&divergent.path),
+            );
+            has_markers = conflicts;
+            Some(merged)
+        }
+    };
+    let merged = has_markers.then(|| with_suffix(&source, "hana-merge"));
+    let backup = match opts.choice {
+        Resolution::Keep => Some(with_suffix(&divergent.path, "hana-backup")),
+        Resolution::Adopt if source_text.is_some() => Some(with_suffix(&source, "hana-backup")),
+        _ => None,
+    };
+    let relinked = merged.is_none() && !wants_generated(config, base_dir, opts.global, opts.agent);
+
+    if !opts.dry_run {
+        if let (Some(merged), Some(text)) = (&merged, &new_source) {
+            fs::write(merged, text).map_err(|e| io_err(merged, e))?;
+        } else {
+            if let Some(text) = &new_source {
+                if let Some(parent) = source.parent() {
+                    fs::create_dir_all(parent).map_err(|e| io_err(parent, e))?;
+                }
+                if let (Resolution::Adopt, Some(backup)) = (opts.choice, &backup) {
+                    fs::copy(&source, backup).map_err(|e| io_err(backup, e))?;
+                }
+                fs::write(&source, text).map_err(|e| io_err(&source, e))?;
+            }
+            match &backup {
+                Some(backup) if opts.choice == Resolution::Keep => {
+                    fs::rename(&divergent.path, backup)
+                }
+                _ => fs::remove_file(&divergent.path),
+            }
+            .map_err(|e| io_err(&divergent.path, e))?;
+        }
+
+        if relinked
+            && let LinkOutcome::Failed(detail) = link_one(
+                &source,
+                &divergent.path,
+                config.source.link_style,
+                false,
+                false,
+            )
+        {
+            return Err(ResolveError::Io {
+                path: divergent.path,
+                detail,
+            });
+        }
+    }
+
+    Ok(ResolveOk {
+        agent: divergent.agent,
+        path: divergent.path,
+        source,
+        choice: opts.choice,
+        backup,
+        has_markers,
+        merged,
+        relinked,
+    })
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    PathBuf::from(format!("{}.{suffix}", path.display()))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Config) {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "# Rules\nshared\n").unwrap();
+        fs::write(tmp.path().join("CLAUDE.md"), "# Rules\nclaude edit\n").unwrap();
+        (tmp, Config::default())
+    }
+
+    fn opts(choice: Resolution) -> ResolveOptions {
+        ResolveOptions {
+            dry_run: false,
+            global: false,
+            agent: AgentName::Claude,
+            choice,
+        }
+    }
+
+    #[test]
+    fn test_diff_lists_divergent_files() {
+        let (tmp, config) = setup();
+        let result = diff(&config, tmp.path(), false);
+        assert!(result.source_exists);
+        assert_eq!(result.divergences.len(), 1);
+        assert_eq!(result.divergences[0].agent, AgentName::Claude);
+        assert!(
+            result.divergences[0]
+                .diff
+                .contains(&DiffLine::Removed("shared".into()))
+        );
+        assert!(
+            result.divergences[0]
+                .diff
+                .contains(&DiffLine::Added("claude edit".into()))
+        );
+    }
+
+    #[test]
+    fn test_adopt_replaces_source_and_relinks() {
+        let (tmp, config) = setup();
+        let result = resolve(&config, tmp.path(), &opts(Resolution::Adopt)).unwrap();
+        assert!(result.relinked);
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap(),
+            "# Rules\nclaude edit\n"
+        );
+        assert_eq!(
+            result.backup,
+            Some(tmp.path().join("AGENTS.md.hana-backup"))
+        );
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("AGENTS.md.hana-backup")).unwrap(),
+            "# Rules\nshared\n"
+        );
+        assert!(tmp.path().join("CLAUDE.md").is_symlink());
+        assert!(diff(&config, tmp.path(), false).divergences.is_empty());
+    }
+
+    #[test]
+    fn test_keep_backs_up_agent_file() {
+        let (tmp, config) = setup();
+        let result = resolve(&config, tmp.path(), &opts(Resolution::Keep)).unwrap();
+        assert_eq!(
+            fs::read_to_string(result.backup.unwrap()).unwrap(),
+            "# Rules\nclaude edit\n"
+        );
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap(),
+            "# Rules\nshared\n"
+        );
+        assert!(tmp.path().join("CLAUDE.md").is_symlink());
+    }
+
+    #[test]
+    fn test_merge_writes_markers_next_to_source() {
+        let (tmp, config) = setup();
+        let result = resolve(&config, tmp.path(), &opts(Resolution::Merge)).unwrap();
+        assert!(result.has_markers);
+        assert!(!result.relinked);
+        let merged = tmp.path().join("AGENTS.md.hana-merge");
+        assert_eq!(result.merged.as_deref(), Some(merged.as_path()));
+        assert_eq!(
+            fs::read_to_string(&merged).unwrap(),
+            "# Rules\n<<<<<<< AGENTS.md\nshared\n=======\nclaude edit\n>>>>>>> CLAUDE.md\n"
+        );
+        // Source and agent file are untouched until the conflict is resolved
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap(),
+            "# Rules\nshared\n"
+        );
+        assert!(!tmp.path().join("CLAUDE.md").is_symlink());
+    }
+
+    #[test]
+    fn test_merge_without_conflicts_updates_source_and_relinks() {
+        let (tmp, config) = setup();
+        fs::write(
+            tmp.path().join("CLAUDE.md"),
+            "# Rules\nshared\n\n## Claude\nclaude edit\n",
+        )
+        .unwrap();
+        let result = resolve(&config, tmp.path(), &opts(Resolution::Merge)).unwrap();
+        assert!(!result.has_markers);
+        assert!(result.merged.is_none());
+        assert!(result.relinked);
+        assert!(!tmp.path().join("AGENTS.md.hana-merge").exists());
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap(),
+            "# Rules\nshared\n\n## Claude\nclaude edit\n"
+        );
+        assert!(tmp.path().join("CLAUDE.md").is_symlink());
+    }
+
+    #[test]
+    fn test_dry_run_and_unknown_agent() {
+        let (tmp, config) = setup();
+        let dry = ResolveOptions {
+            dry_run: true,
+            ..opts(Resolution::Adopt)
+        };
+        resolve(&config, tmp.path(), &dry).unwrap();
+        assert!(!tmp.path().join("CLAUDE.md").is_symlink());
+
+        let pi = ResolveOptions {
+            agent: AgentName::Pi,
+            ..opts(Resolution::Adopt)
+        };
+        assert!(matches!(
+            resolve(&config, tmp.path(), &pi),
+            Err(ResolveError::NotDivergent { .. })
+        ));
+    }
+}
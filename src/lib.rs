@@ -0,0 +1,51 @@
+//! Keep AI coding agent skills, instructions, subagents, commands and MCP servers in sync
+//! from a single source (`.agents/`), using symlinks where agents can read the source and
+//! generated files where they cannot.
+//!
+//! The `hana` binary is a thin wrapper over this crate. Every command is a module whose
+//! `run` takes a [`Config`], the base directory (project root, or `~` for the global level)
+//! and options, and returns plain data. Nothing is printed; problems that do not stop a run
+//! come back as warnings in the result.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use hanacli::{Config, status, sync};
+//!
+//! let base_dir = Path::new(".");
+//! let config = Config::load_level(base_dir)?;
+//!
+//! let result = sync::run(&config, base_dir, &sync::SyncOptions::default());
+//! for warning in &result.warnings {
+//!     eprintln!("{warning}");
+//! }
+//! let status = status::run(&config, base_dir, false);
+//! println!("{} entries out of sync", status.problem_count());
+//! # Ok::<(), hanacli::ConfigError>(())
+//! ```
+//!
+//! # Stability
+//!
+//! The modules below follow semver. Every public struct and enum is `#[non_exhaustive]`, so
+//! new fields and variants can arrive in minor releases: match enums with a wildcard arm and
+//! read struct fields by name. Build [`Config`] with `Config::load_level` / `Config::parse`,
+//! and other values with `Default` or their constructor (`TargetConfig::default_for`,
+//! `McpServer::command`, `Mapping::new`, `instructions::ResolveOptions::new`, …), then set
+//! fields. Internals are private.
+
+pub mod config;
+pub mod hooks;
+pub mod init;
+pub mod instructions;
+pub mod profile;
+pub mod repair;
+pub mod status;
+pub mod sync;
+pub mod watch;
+pub mod workspace;
+
+mod helper;
+
+pub use config::{AgentName, Config, ConfigError};
+pub use status::{StatusOk, SyncState};
+pub use sync::{SyncOk, SyncOptions, SyncWarning};
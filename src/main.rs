@@ -1,18 +1,17 @@
-mod config;
-mod helper;
-mod init;
-mod status;
-mod sync;
 mod tui;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use owo_colors::OwoColorize;
 
-use config::Config;
-use init::InitOk;
-use sync::SyncOk;
+use hanacli::config::{existing_levels, resolve_base_dir};
+use hanacli::init::InitOk;
+use hanacli::sync::SyncOk;
+use hanacli::{
+    AgentName, Config, ConfigError, hooks, init, instructions, profile, repair, status, sync,
+    watch, workspace,
+};
 
 #[derive(Parser)]
 #[command(
@@ -55,13 +54,170 @@ enum Commands {
         /// Preview without making changes
         #[arg(short, long)]
         dry_run: bool,
+
+        /// Remove symlinks in agent skill directories that point outside the source
+        #[arg(long)]
+        prune_foreign: bool,
+
+        /// Sync the global config, then the project one
+        #[arg(long, conflicts_with = "global")]
+        all: bool,
+    },
+
+    /// Rewrite managed symlinks to the configured link style and fix wrong targets
+    Repair {
+        /// Target global config (~/.agents/hana.toml)
+        #[arg(short, long)]
+        global: bool,
+
+        /// Preview without making changes
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+
+    /// Watch agent skill directories and sync whenever they change
+    Watch {
+        /// Target global config (~/.agents/hana.toml)
+        #[arg(short, long)]
+        global: bool,
+
+        /// Quiet period in milliseconds before a burst of changes is synced
+        #[arg(long, default_value_t = watch::DEFAULT_DEBOUNCE_MS)]
+        debounce: u64,
     },
 
     /// Show current sync status
     Status {
+        /// Show details for a single skill
+        skill: Option<String>,
+
         /// Target global config (~/.agents/hana.toml)
         #[arg(short, long)]
         global: bool,
+
+        /// Print nothing and exit 0 when everything is synced; otherwise print status and exit 1
+        #[arg(long)]
+        check: bool,
+
+        /// Show global and project skills together, and which copy each agent loads
+        #[arg(long, conflicts_with_all = ["global", "skill"])]
+        all: bool,
+    },
+
+    /// Switch to a profile's skill set and instruction file, then resync them
+    Use {
+        /// Profile to activate
+        #[arg(required_unless_present = "clear")]
+        profile: Option<String>,
+
+        /// Go back to every skill and the configured instruction file
+        #[arg(long, conflicts_with = "profile")]
+        clear: bool,
+
+        /// Target global config (~/.agents/hana.toml)
+        #[arg(short, long)]
+        global: bool,
+
+        /// Preview without making changes
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+
+    /// Check or sync every repo listed in [workspace] of the global config
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceAction,
+    },
+
+    /// Manage git hooks that keep agents in sync
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+    },
+
+    /// Compare and reconcile agent instruction files that diverged from the source
+    Instructions {
+        #[command(subcommand)]
+        action: InstructionsAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum InstructionsAction {
+    /// Show how each hand-edited agent instruction file differs from the source
+    Diff {
+        /// Target global config (~/.agents/hana.toml)
+        #[arg(short, long)]
+        global: bool,
+    },
+
+    /// Merge, keep or adopt one agent's instruction file, then relink it
+    Resolve {
+        /// Agent whose file to resolve (e.g. claude)
+        agent: String,
+
+        /// merge: combine both, marking regions both changed; keep: back up the agent file; adopt: agent file becomes the source
+        #[arg(value_enum)]
+        choice: ResolveChoice,
+
+        /// Target global config (~/.agents/hana.toml)
+        #[arg(short, long)]
+        global: bool,
+
+        /// Preview without making changes
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ResolveChoice {
+    Merge,
+    Keep,
+    Adopt,
+}
+
+impl From<ResolveChoice> for instructions::Resolution {
+    fn from(choice: ResolveChoice) -> Self {
+        match choice {
+            ResolveChoice::Merge => Self::Merge,
+            ResolveChoice::Keep => Self::Keep,
+            ResolveChoice::Adopt => Self::Adopt,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum WorkspaceAction {
+    /// Show what is out of sync in each repo; exits 1 if any repo is
+    Status,
+
+    /// Sync each repo, then show what is still out of sync; exits 1 if any repo is
+    Sync {
+        /// Overwrite existing files
+        #[arg(short, long)]
+        force: bool,
+
+        /// Preview without making changes
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HooksAction {
+    /// Add post-checkout, post-merge and pre-commit hooks
+    Install {
+        /// Preview without making changes
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+
+    /// Remove hana from the hooks, restoring what was there before
+    Uninstall {
+        /// Preview without making changes
+        #[arg(short, long)]
+        dry_run: bool,
     },
 }
 
@@ -73,23 +229,102 @@ fn main() {
             global,
             force,
             dry_run,
-        } => run_init(init::InitOptions {
-            global,
-            force,
-            dry_run,
-        }),
+        } => {
+            let mut opts = init::InitOptions::default();
+            opts.global = global;
+            opts.force = force;
+            opts.dry_run = dry_run;
+            run_init(opts)
+        }
 
         Commands::Sync {
             global,
             force,
             dry_run,
-        } => run_sync(sync::SyncOptions {
+            prune_foreign,
+            all,
+        } => {
+            let mut opts = sync::SyncOptions::default();
+            opts.global = global;
+            opts.force = force;
+            opts.dry_run = dry_run;
+            opts.prune_foreign = prune_foreign;
+            if all {
+                run_sync_all(opts)
+            } else {
+                run_sync(opts)
+            }
+        }
+
+        Commands::Repair { global, dry_run } => {
+            let mut opts = repair::RepairOptions::default();
+            opts.global = global;
+            opts.dry_run = dry_run;
+            run_repair(opts)
+        }
+
+        Commands::Watch { global, debounce } => {
+            let mut opts = watch::WatchOptions::default();
+            opts.global = global;
+            opts.debounce = std::time::Duration::from_millis(debounce);
+            run_watch(opts)
+        }
+
+        Commands::Status {
+            skill,
+            global,
+            check,
+            all,
+        } => {
+            if all {
+                run_status_all(check)
+            } else {
+                run_status(global, skill.as_deref(), check)
+            }
+        }
+
+        Commands::Use {
+            profile,
+            clear: _,
             global,
-            force,
             dry_run,
-        }),
+        } => {
+            let mut opts = profile::UseOptions::default();
+            opts.profile = profile;
+            opts.global = global;
+            opts.dry_run = dry_run;
+            run_use(opts)
+        }
+
+        Commands::Workspace { action } => {
+            let mut opts = workspace::WorkspaceOptions::default();
+            if let WorkspaceAction::Sync { force, dry_run } = action {
+                opts.sync = true;
+                opts.dry_run = dry_run;
+                opts.force = force;
+            }
+            run_workspace(opts)
+        }
 
-        Commands::Status { global } => run_status(global),
+        Commands::Hooks { action } => {
+            let (install, dry_run) = match action {
+                HooksAction::Install { dry_run } => (true, dry_run),
+                HooksAction::Uninstall { dry_run } => (false, dry_run),
+            };
+            let mut opts = hooks::HooksOptions::default();
+            opts.dry_run = dry_run;
+            run_hooks(install, opts)
+        }
+
+        Commands::Instructions { action } => match action {
+            InstructionsAction::Diff { global } => run_instructions_diff(global),
+            InstructionsAction::Resolve {
+                agent,
+                choice,
+                global,
+                dry_run,
+            } => run_instructions_resolve(&agent, choice.into(), global, dry_run),
+        },
     };
 
     if exit_code != 0 {
@@ -97,12 +332,15 @@ fn main() {
     }
 }
 
-fn resolve_base_dir(global: bool) -> Result<PathBuf, String> {
-    if global {
-        dirs::home_dir().ok_or_else(|| "could not determine home directory".to_string())
-    } else {
-        Ok(std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
-    }
+/// Load hana.toml with the profile chosen by `hana use` applied. Errors are printed.
+fn load_config(base_dir: &Path) -> Result<Config, i32> {
+    Config::load_level(base_dir).map_err(|e| {
+        eprintln!("{} {e}", "error:".red().bold());
+        if !matches!(e, ConfigError::UnknownProfile { .. }) {
+            eprintln!("  run {} to create the config first.", "hana init".bold());
+        }
+        1
+    })
 }
 
 // ── init ──
@@ -135,6 +373,7 @@ fn run_init(opts: init::InitOptions) -> i32 {
             println!("{}", content.dimmed());
             0
         }
+        Ok(_) => 0,
         Err(e) => {
             eprintln!("{} {e}", "error:".red().bold());
             1
@@ -153,14 +392,9 @@ fn run_sync(opts: sync::SyncOptions) -> i32 {
         }
     };
 
-    let config_path = base_dir.join(".agents/hana.toml");
-    let config = match Config::load(&config_path) {
+    let config = match load_config(&base_dir) {
         Ok(c) => c,
-        Err(e) => {
-            eprintln!("{} {e}", "error:".red().bold());
-            eprintln!("  run {} to create the config first.", "hana init".bold());
-            return 1;
-        }
+        Err(code) => return code,
     };
 
     let result = sync::run(&config, &base_dir, &opts);
@@ -170,31 +404,90 @@ fn run_sync(opts: sync::SyncOptions) -> i32 {
     0
 }
 
+/// The levels that have a hana.toml, global first. Errors are printed.
+fn all_levels() -> Result<Vec<(bool, PathBuf)>, i32> {
+    match existing_levels() {
+        Ok(levels) if !levels.is_empty() => Ok(levels),
+        Ok(_) => {
+            eprintln!(
+                "{} no hana.toml in ~/.agents or ./.agents",
+                "error:".red().bold()
+            );
+            eprintln!("  run {} to create the config first.", "hana init".bold());
+            Err(1)
+        }
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            Err(1)
+        }
+    }
+}
+
+fn run_sync_all(opts: sync::SyncOptions) -> i32 {
+    let levels = match all_levels() {
+        Ok(levels) => levels,
+        Err(code) => return code,
+    };
+    // A level that fails to load is reported and the other one still synced
+    let mut code = 0;
+    for (global, base_dir) in levels {
+        let command = if global { "sync --global" } else { "sync" };
+        print!("{}", tui::header(command, opts.dry_run));
+        let config = match load_config(&base_dir) {
+            Ok(c) => c,
+            Err(failed) => {
+                code = failed;
+                continue;
+            }
+        };
+        let mut opts = opts;
+        opts.global = global;
+        let result = sync::run(&config, &base_dir, &opts);
+        print_sync_result(&result);
+    }
+    code
+}
+
 fn print_sync_result(result: &SyncOk) {
     let has_skills = !result.skills_collected.is_empty() || !result.skills_linked.is_empty();
     let has_instructions = result.instructions_collected.is_some()
         || !result.instructions_linked.is_empty()
+        || !result.instructions_generated.is_empty()
         || !result.instructions_skipped.is_empty();
-    let has_cleanup = !result.cleaned.is_empty();
+    let has_files = !result.files_linked.is_empty() || !result.files_collected.is_empty();
+    let has_subagents =
+        !result.subagents_generated.is_empty() || !result.subagents_unsupported.is_empty();
+    let has_commands =
+        !result.commands_generated.is_empty() || !result.commands_unsupported.is_empty();
+    let has_mcp = !result.mcp_updated.is_empty() || !result.mcp_removed.is_empty();
+    let has_mappings = !result.mappings_linked.is_empty() || !result.mappings_collected.is_empty();
+    let has_cleanup = !result.cleaned.is_empty()
+        || !result.skills_unlinked.is_empty()
+        || !result.pruned.is_empty()
+        || !result.subagents_removed.is_empty()
+        || !result.commands_removed.is_empty();
     let has_warnings = !result.warnings.is_empty();
 
     // Skills
     if has_skills {
         let mut table_rows: Vec<Vec<String>> = Vec::new();
 
-        for (name, agent) in &result.skills_collected {
+        for collected in &result.skills_collected {
             table_rows.push(vec![
                 tui::label_collected("collected"),
-                name.bold().to_string(),
-                format!("← {}", agent),
+                collected.skill.bold().to_string(),
+                format!("← {}", collected.agent),
             ]);
         }
 
         if !result.skills_linked.is_empty() {
             let mut by_skill: std::collections::HashMap<&str, Vec<&str>> =
                 std::collections::HashMap::new();
-            for (skill, agent) in &result.skills_linked {
-                by_skill.entry(skill).or_default().push(agent);
+            for linked in &result.skills_linked {
+                by_skill
+                    .entry(&linked.skill)
+                    .or_default()
+                    .push(linked.agent.as_str());
             }
             let mut skills: Vec<_> = by_skill.into_iter().collect();
             skills.sort_by_key(|(name, _)| *name);
@@ -213,20 +506,29 @@ fn print_sync_result(result: &SyncOk) {
 
     // Instructions
     if has_instructions {
+        let source = result.instructions_source.as_str();
         let mut table_rows: Vec<Vec<String>> = Vec::new();
 
-        if let Some((file, agent)) = &result.instructions_collected {
+        if let Some(collected) = &result.instructions_collected {
             table_rows.push(vec![
                 tui::label_collected("collected"),
-                file.bold().to_string(),
-                format!("→ {} (from {})", "AGENTS.md".bold(), agent),
+                collected.file.bold().to_string(),
+                format!("→ {} (from {})", source.bold(), collected.agent),
             ]);
         }
 
         for agent in &result.instructions_linked {
             table_rows.push(vec![
                 tui::label_symlinked("symlinked"),
-                "AGENTS.md".bold().to_string(),
+                source.bold().to_string(),
+                format!("→ {agent}"),
+            ]);
+        }
+
+        for agent in &result.instructions_generated {
+            table_rows.push(vec![
+                tui::label_collected("generated"),
+                source.bold().to_string(),
                 format!("→ {agent}"),
             ]);
         }
@@ -234,8 +536,8 @@ fn print_sync_result(result: &SyncOk) {
         if !result.instructions_skipped.is_empty() {
             table_rows.push(vec![
                 tui::label_native("native"),
-                "AGENTS.md".bold().to_string(),
-                tui::label_native(&result.instructions_skipped.join(", ")),
+                source.bold().to_string(),
+                tui::label_native(&join_agents(&result.instructions_skipped)),
             ]);
         }
 
@@ -243,6 +545,108 @@ fn print_sync_result(result: &SyncOk) {
         print!("{}", tui::section("Instructions", &rows));
     }
 
+    // Extra files
+    if has_files {
+        let table_rows: Vec<Vec<String>> = result
+            .files_collected
+            .iter()
+            .map(|file| {
+                vec![
+                    tui::label_collected("collected"),
+                    file.name.bold().to_string(),
+                    format!("→ source (from {})", file.agent),
+                ]
+            })
+            .chain(result.files_linked.iter().map(|file| {
+                vec![
+                    tui::label_symlinked("symlinked"),
+                    file.name.bold().to_string(),
+                    format!("→ {}", file.agent),
+                ]
+            }))
+            .collect();
+        print!("{}", tui::section("Files", &tui::table(&table_rows)));
+    }
+
+    // Subagents, commands
+    if has_subagents {
+        let rows = rendered_rows(&result.subagents_generated, &result.subagents_unsupported);
+        print!("{}", tui::section("Subagents", &tui::table(&rows)));
+    }
+    if has_commands {
+        let rows = rendered_rows(&result.commands_generated, &result.commands_unsupported);
+        print!("{}", tui::section("Commands", &tui::table(&rows)));
+    }
+
+    // MCP
+    if has_mcp {
+        let mut by_server: std::collections::BTreeMap<&str, (Vec<&str>, Vec<&str>)> =
+            std::collections::BTreeMap::new();
+        for server in &result.mcp_updated {
+            by_server
+                .entry(&server.name)
+                .or_default()
+                .0
+                .push(server.agent.as_str());
+        }
+        for server in &result.mcp_removed {
+            by_server
+                .entry(&server.name)
+                .or_default()
+                .1
+                .push(server.agent.as_str());
+        }
+        let mut table_rows: Vec<Vec<String>> = Vec::new();
+        for (server, (updated, removed)) in by_server {
+            if !updated.is_empty() {
+                table_rows.push(vec![
+                    tui::label_collected("updated"),
+                    server.bold().to_string(),
+                    format!("→ {}", updated.join(", ")),
+                ]);
+            }
+            if !removed.is_empty() {
+                table_rows.push(vec![
+                    tui::label_removed("removed"),
+                    server.bold().to_string(),
+                    format!("← {}", removed.join(", ")),
+                ]);
+            }
+        }
+        print!("{}", tui::section("MCP", &tui::table(&table_rows)));
+    }
+
+    // Mappings
+    if has_mappings {
+        let mut table_rows: Vec<Vec<String>> = result
+            .mappings_collected
+            .iter()
+            .map(|entry| {
+                vec![
+                    tui::label_collected("collected"),
+                    entry.name.bold().to_string(),
+                    format!("← {}", entry.agent),
+                ]
+            })
+            .collect();
+        let mut by_entry: std::collections::BTreeMap<&str, Vec<&str>> =
+            std::collections::BTreeMap::new();
+        for entry in &result.mappings_linked {
+            by_entry
+                .entry(&entry.name)
+                .or_default()
+                .push(entry.agent.as_str());
+        }
+        for (entry, agents) in by_entry {
+            table_rows.push(vec![
+                tui::label_symlinked("symlinked"),
+                entry.bold().to_string(),
+                format!("→ {}", agents.join(", ")),
+            ]);
+        }
+        print!("{}", tui::section("Mappings", &tui::table(&table_rows)));
+    }
+
     // Cleanup
     if has_cleanup {
         let rows: Vec<String> = result
@@ -256,10 +660,52 @@ fn print_sync_result(result: &SyncOk) {
                     "(broken symlink)".dimmed()
                 )
             })
+            .chain(result.skills_unlinked.iter().map(|unlinked| {
+                format!(
+                    "{}  {} ← {} {}",
+                    tui::label_removed("unlinked"),
+                    unlinked.skill,
+                    unlinked.agent,
+                    "(excluded)".dimmed()
+                )
+            }))
+            .chain(result.pruned.iter().map(|path| {
+                format!(
+                    "{}  {} {}",
+                    tui::label_removed("removed"),
+                    path.display(),
+                    "(foreign symlink)".dimmed()
+                )
+            }))
+            .chain(
+                result
+                    .subagents_removed
+                    .iter()
+                    .chain(&result.commands_removed)
+                    .map(|file| {
+                        format!(
+                            "{}  {} ← {} {}",
+                            tui::label_removed("removed"),
+                            file.name,
+                            file.agent,
+                            "(no longer rendered)".dimmed()
+                        )
+                    }),
+            )
             .collect();
         print!("{}", tui::section("Cleanup", &rows));
     }
 
+    // Git
+    if result.gitignore_updated {
+        let rows = vec![format!(
+            "{}  .gitignore {}",
+            tui::label_collected("updated"),
+            "(managed links block)".dimmed()
+        )];
+        print!("{}", tui::section("Git", &rows));
+    }
+
     // Warnings
     if has_warnings {
         let rows: Vec<String> = result
@@ -271,17 +717,135 @@ fn print_sync_result(result: &SyncOk) {
     }
 
     // Footer
-    if !has_skills && !has_instructions && !has_cleanup {
+    if !has_skills
+        && !has_instructions
+        && !has_files
+        && result.subagents_generated.is_empty()
+        && result.commands_generated.is_empty()
+        && !has_mcp
+        && !has_mappings
+        && !has_cleanup
+        && !result.gitignore_updated
+    {
         print!("{}", tui::footer_no_changes());
     } else {
         print!("{}", tui::footer_done());
     }
 }
 
-// ── status ──
+/// Sync rows for rendered definitions: one per file with its agents, then unsupported ones.
+fn rendered_rows(
+    generated: &[sync::AgentEntry],
+    unsupported: &[sync::UnsupportedEntry],
+) -> Vec<Vec<String>> {
+    let mut by_file: std::collections::BTreeMap<&str, Vec<&str>> =
+        std::collections::BTreeMap::new();
+    for file in generated {
+        by_file
+            .entry(&file.name)
+            .or_default()
+            .push(file.agent.as_str());
+    }
+    by_file
+        .into_iter()
+        .map(|(file, agents)| {
+            vec![
+                tui::label_collected("generated"),
+                file.bold().to_string(),
+                format!("→ {}", agents.join(", ")),
+            ]
+        })
+        .chain(unsupported.iter().map(|file| {
+            vec![
+                tui::label_native("unsupported"),
+                file.name.bold().to_string(),
+                format!("{}: {}", file.agent, file.reason)
+                    .dimmed()
+                    .to_string(),
+            ]
+        }))
+        .collect()
+}
 
-fn run_status(global: bool) -> i32 {
-    let base_dir = match resolve_base_dir(global) {
+fn join_agents(agents: &[AgentName]) -> String {
+    agents
+        .iter()
+        .map(|agent| agent.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// ── repair ──
+
+fn run_repair(opts: repair::RepairOptions) -> i32 {
+    let base_dir = match resolve_base_dir(opts.global) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            return 1;
+        }
+    };
+
+    let config = match load_config(&base_dir) {
+        Ok(c) => c,
+        Err(code) => return code,
+    };
+
+    let result = repair::run(&config, &base_dir, &opts);
+
+    print!("{}", tui::header("repair", opts.dry_run));
+    print_repair_result(&result);
+    if result.warnings.is_empty() { 0 } else { 1 }
+}
+
+fn print_repair_result(result: &repair::RepairOk) {
+    use repair::RepairKind;
+
+    if !result.changes.is_empty() {
+        let table_rows: Vec<Vec<String>> = result
+            .changes
+            .iter()
+            .map(|change| {
+                let label = match change.kind {
+                    RepairKind::Restyled => tui::label_symlinked(result.style.as_str()),
+                    RepairKind::Rerouted => tui::label_symlinked("rerouted"),
+                    RepairKind::Retargeted => tui::label_collected("retargeted"),
+                    _ => tui::label_collected("repaired"),
+                };
+                vec![
+                    label,
+                    change.path.display().to_string(),
+                    format!(
+                        "{} → {}",
+                        change.from.display().dimmed(),
+                        change.to.display()
+                    ),
+                ]
+            })
+            .collect();
+        print!("{}", tui::section("Links", &tui::table(&table_rows)));
+    }
+
+    if !result.warnings.is_empty() {
+        let rows: Vec<String> = result
+            .warnings
+            .iter()
+            .map(|w| tui::label_warning(&format!("⚠ {w}")))
+            .collect();
+        print!("{}", tui::section("Warnings", &rows));
+    }
+
+    if result.changes.is_empty() {
+        print!("{}", tui::footer_no_changes());
+    } else {
+        print!("{}", tui::footer_done());
+    }
+}
+
+// ── watch ──
+
+fn run_watch(opts: watch::WatchOptions) -> i32 {
+    let base_dir = match resolve_base_dir(opts.global) {
         Ok(d) => d,
         Err(e) => {
             eprintln!("{} {e}", "error:".red().bold());
@@ -289,27 +853,540 @@ fn run_status(global: bool) -> i32 {
         }
     };
 
-    let config_path = base_dir.join(".agents/hana.toml");
-    let config = match Config::load(&config_path) {
+    let config = match load_config(&base_dir) {
         Ok(c) => c,
+        Err(code) => return code,
+    };
+
+    print!("{}", tui::header("watch", false));
+    let result = watch::run(
+        &config,
+        &base_dir,
+        &opts,
+        |dirs| {
+            let rows: Vec<String> = dirs
+                .iter()
+                .map(|dir| format!("{}  {}", tui::label_native("watching"), dir.display()))
+                .collect();
+            print!("{}", tui::section("Watch", &rows));
+        },
+        |changed, result| {
+            for line in format_sync_actions(result) {
+                println!("  {line}");
+            }
+            for warning in &result.warnings {
+                eprintln!("  {}", tui::label_warning(&format!("⚠ {warning}")));
+            }
+            if !changed.is_empty() && !has_sync_actions(result) {
+                println!(
+                    "  {}",
+                    format!("{} change(s), nothing to sync", changed.len()).dimmed()
+                );
+            }
+        },
+    );
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            1
+        }
+    }
+}
+
+fn has_sync_actions(result: &SyncOk) -> bool {
+    !result.skills_collected.is_empty()
+        || !result.skills_linked.is_empty()
+        || !result.skills_unlinked.is_empty()
+        || !result.cleaned.is_empty()
+        || !result.warnings.is_empty()
+}
+
+/// One line per skill action, for the watch log. Warnings go to stderr separately.
+fn format_sync_actions(result: &SyncOk) -> Vec<String> {
+    let collected = result.skills_collected.iter().map(|collected| {
+        format!(
+            "{}  {} ← {}",
+            tui::label_collected("collected"),
+            collected.skill.bold(),
+            collected.agent
+        )
+    });
+    let linked = result.skills_linked.iter().map(|linked| {
+        format!(
+            "{}  {} → {}",
+            tui::label_symlinked("symlinked"),
+            linked.skill.bold(),
+            linked.agent
+        )
+    });
+    let unlinked = result.skills_unlinked.iter().map(|unlinked| {
+        format!(
+            "{}  {} ← {} {}",
+            tui::label_removed("unlinked"),
+            unlinked.skill.bold(),
+            unlinked.agent,
+            "(excluded)".dimmed()
+        )
+    });
+    let cleaned = result.cleaned.iter().map(|path| {
+        format!(
+            "{}  {} {}",
+            tui::label_removed("removed"),
+            path.display(),
+            "(broken symlink)".dimmed()
+        )
+    });
+    collected
+        .chain(linked)
+        .chain(unlinked)
+        .chain(cleaned)
+        .collect()
+}
+
+// ── hooks ──
+
+fn run_hooks(install: bool, opts: hooks::HooksOptions) -> i32 {
+    use hooks::HookChange;
+
+    let base_dir = match resolve_base_dir(false) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            return 1;
+        }
+    };
+
+    let result = if install {
+        hooks::install(&base_dir, &opts)
+    } else {
+        hooks::uninstall(&base_dir, &opts)
+    };
+    let result = match result {
+        Ok(r) => r,
         Err(e) => {
             eprintln!("{} {e}", "error:".red().bold());
-            eprintln!("  run {} to create the config first.", "hana init".bold());
             return 1;
         }
     };
 
+    print!("{}", tui::header("hooks", opts.dry_run));
+    let table_rows: Vec<Vec<String>> = result
+        .changes
+        .iter()
+        .map(|(name, change)| {
+            let label = match change {
+                HookChange::Created => tui::label_symlinked("created"),
+                HookChange::Chained => tui::label_collected("chained"),
+                HookChange::Updated => tui::label_collected("updated"),
+                HookChange::Unchanged => tui::label_native("unchanged"),
+                HookChange::Removed => tui::label_removed("removed"),
+                HookChange::Deleted => tui::label_removed("deleted"),
+                HookChange::Restored => tui::label_removed("restored"),
+                _ => tui::label_native("changed"),
+            };
+            vec![label, name.bold().to_string()]
+        })
+        .collect();
+    let title = format!("Hooks ({})", result.hooks_dir.display());
+    print!("{}", tui::section(&title, &tui::table(&table_rows)));
+
+    if result
+        .changes
+        .iter()
+        .all(|(_, c)| *c == HookChange::Unchanged)
+    {
+        print!("{}", tui::footer_no_changes());
+    } else {
+        print!("{}", tui::footer_done());
+    }
+    0
+}
+
+// ── status ──
+
+fn run_status(global: bool, skill: Option<&str>, check: bool) -> i32 {
+    let base_dir = match resolve_base_dir(global) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            return 1;
+        }
+    };
+
+    let config = match load_config(&base_dir) {
+        Ok(c) => c,
+        Err(code) => return code,
+    };
+
+    if let Some(skill) = skill {
+        return match status::run_skill(&config, &base_dir, global, skill) {
+            Ok(detail) => {
+                print!("{}", tui::header("status", false));
+                print!("{}", format_skill_detail(&detail));
+                0
+            }
+            Err(e) => {
+                eprintln!("{} {e}", "error:".red().bold());
+                1
+            }
+        };
+    }
+
     let result = status::run(&config, &base_dir, global);
+    let problems = result.problem_count();
+    if check && problems == 0 {
+        return 0;
+    }
     print!("{}", tui::header("status", false));
     print!("{}", format_status(&result));
+    if check {
+        eprintln!(
+            "{} {problems} entries out of sync. Run {} first.",
+            "error:".red().bold(),
+            "hana sync".bold()
+        );
+        return 1;
+    }
     0
 }
 
-fn format_status(result: &status::StatusOk) -> String {
-    use status::{InstructionState, SkillState};
+// ── use ──
+
+fn run_use(opts: profile::UseOptions) -> i32 {
+    let base_dir = match resolve_base_dir(opts.global) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            return 1;
+        }
+    };
+
+    // Loaded without the saved profile: it is the one being replaced
+    let config = match Config::load(&base_dir.join(".agents/hana.toml")) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            eprintln!("  run {} to create the config first.", "hana init".bold());
+            return 1;
+        }
+    };
+
+    match profile::run(&config, &base_dir, &opts) {
+        Ok(result) => {
+            print!("{}", tui::header("use", opts.dry_run));
+            let show = |name: &Option<String>| name.clone().unwrap_or_else(|| "(none)".to_string());
+            let mut rows = vec![format!(
+                "{}  →  {}",
+                show(&result.previous).dimmed(),
+                show(&result.profile).bold()
+            )];
+            if !result.unaffected.is_empty() {
+                let agents: Vec<&str> = result.unaffected.iter().map(|a| a.as_str()).collect();
+                rows.push(format!(
+                    "{}  {} read {} directly and keep its instructions",
+                    tui::label_warning("unchanged"),
+                    agents.join(", "),
+                    config.configured_instruction_path(opts.global)
+                ));
+            }
+            print!("{}", tui::section("Profile", &rows));
+            print_sync_result(&result.sync);
+            0
+        }
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            1
+        }
+    }
+}
+
+fn run_status_all(check: bool) -> i32 {
+    let levels = match all_levels() {
+        Ok(levels) => levels,
+        Err(code) => return code,
+    };
+    let mut loaded = Vec::new();
+    for (global, base_dir) in levels {
+        match load_config(&base_dir) {
+            Ok(config) => loaded.push((global, base_dir, config)),
+            Err(code) => return code,
+        }
+    }
+    let level = |want: bool| {
+        loaded
+            .iter()
+            .find(|(global, _, _)| *global == want)
+            .map(|(_, base_dir, config)| (config, base_dir.as_path()))
+    };
+
+    let result = status::run_all(level(true), level(false));
+    let problems = result.problem_count();
+    if check && problems == 0 {
+        return 0;
+    }
+    print!("{}", tui::header("status --all", false));
+    print!("{}", format_layered_status(&result));
+    if check {
+        eprintln!(
+            "{} {problems} entries out of sync. Run {} first.",
+            "error:".red().bold(),
+            "hana sync --all".bold()
+        );
+        return 1;
+    }
+    0
+}
+
+fn format_layered_status(result: &status::LayeredStatusOk) -> String {
+    let mut out = String::new();
+
+    let level_rows: Vec<Vec<String>> = [
+        (status::SkillLevel::Global, &result.global),
+        (status::SkillLevel::Project, &result.project),
+    ]
+    .into_iter()
+    .map(|(level, status)| {
+        let state = match status {
+            None => tui::badge_skip("no hana.toml"),
+            Some(status) => match status.problem_count() {
+                0 => tui::badge_ok("in sync"),
+                n => tui::badge_warn(&format!("{n} out of sync")),
+            },
+        };
+        vec![level.label().bold().to_string(), state]
+    })
+    .collect();
+    out.push_str(&tui::section("Levels", &tui::table(&level_rows)));
+
+    if result.skills.is_empty() {
+        let rows = vec![tui::label_native("(none)")];
+        out.push_str(&tui::section("Skills", &rows));
+        return out;
+    }
+    let table_rows: Vec<Vec<String>> = result
+        .skills
+        .iter()
+        .map(|skill| {
+            let levels: Vec<&str> = skill.levels.iter().map(|level| level.label()).collect();
+            let mut row = vec![
+                skill.name.bold().to_string(),
+                levels.join(" + ").dimmed().to_string(),
+            ];
+            row.extend(skill.agents.iter().map(|loaded| match loaded.level {
+                Some(level) => tui::badge_ok(&format!("{} ({})", loaded.agent, level.label())),
+                None => tui::badge_skip(&format!("{} (not loaded)", loaded.agent)),
+            }));
+            row
+        })
+        .collect();
+    out.push_str(&tui::section("Skills", &tui::table(&table_rows)));
+    out.push_str(&tui::section(
+        "Legend",
+        &[format!(
+            "{}",
+            "agent (level) = the copy the agent loads; a project skill shadows a global one"
+                .dimmed()
+        )],
+    ));
+    out
+}
+
+// ── workspace ──
+
+fn run_workspace(opts: workspace::WorkspaceOptions) -> i32 {
+    let home = match resolve_base_dir(true) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            return 1;
+        }
+    };
+    let config = match load_config(&home) {
+        Ok(c) => c,
+        Err(code) => return code,
+    };
+
+    let result = match workspace::run(&config, &home, &opts) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            return 1;
+        }
+    };
+
+    let command = if opts.sync {
+        "workspace sync"
+    } else {
+        "workspace status"
+    };
+    print!("{}", tui::header(command, opts.dry_run));
+    let count = |n: usize| {
+        if n == 0 {
+            tui::badge_ok("0")
+        } else {
+            tui::badge_warn(&n.to_string())
+        }
+    };
+    let mut table_rows = vec![
+        ["repo", "skills", "instructions", "other", ""]
+            .iter()
+            .map(|h| h.dimmed().to_string())
+            .collect::<Vec<_>>(),
+    ];
+    for repo in &result.repos {
+        let name = match repo.path.strip_prefix(&home) {
+            Ok(rel) => format!("~/{}", rel.display()),
+            Err(_) => repo.path.display().to_string(),
+        };
+        let mut row = vec![name.bold().to_string()];
+        match &repo.outcome {
+            workspace::RepoOutcome::Checked {
+                profile,
+                warnings,
+                drift,
+            } => {
+                row.extend([
+                    count(drift.skills),
+                    count(drift.instructions),
+                    count(drift.other),
+                ]);
+                let mut notes = Vec::new();
+                if let Some(profile) = profile {
+                    notes.push(format!("profile {profile}"));
+                }
+                if *warnings > 0 {
+                    notes.push(format!("{warnings} warnings"));
+                }
+                row.push(notes.join(", ").dimmed().to_string());
+            }
+            workspace::RepoOutcome::Failed(error) => {
+                row.extend([tui::badge_err("error"), String::new(), String::new()]);
+                row.push(error.dimmed().to_string());
+            }
+            _ => row.extend([String::new(), String::new(), String::new(), String::new()]),
+        }
+        table_rows.push(row);
+    }
+    print!("{}", tui::section("Repos", &tui::table(&table_rows)));
+
+    let out_of_sync = result.out_of_sync();
+    if out_of_sync > 0 {
+        eprintln!(
+            "{} {out_of_sync} of {} repos out of sync.",
+            "error:".red().bold(),
+            result.repos.len()
+        );
+        return 1;
+    }
+    print!("{}", tui::footer_done());
+    0
+}
+
+/// Badge for a skill or instruction state, shared by every status view.
+fn state_badge(state: status::SyncState, text: &str) -> String {
+    use status::SyncState;
+
+    match state {
+        SyncState::Synced => tui::badge_ok(text),
+        SyncState::Native => tui::badge_info(text),
+        SyncState::Disabled | SyncState::Excluded | SyncState::Unsupported => tui::badge_skip(text),
+        SyncState::Conflict | SyncState::WrongTarget | SyncState::Stale | SyncState::Edited => {
+            tui::badge_warn(text)
+        }
+        SyncState::Broken => tui::badge_broken(text),
+        SyncState::Missing => tui::badge_err(text),
+        state if state.is_problem() => tui::badge_warn(text),
+        _ => tui::badge_skip(text),
+    }
+}
+
+/// `agent` when synced, `agent (state)` otherwise.
+fn agent_state_badge(agent: AgentName, state: status::SyncState) -> String {
+    match state {
+        status::SyncState::Synced => state_badge(state, agent.as_str()),
+        _ => state_badge(state, &format!("{agent} ({})", state.label())),
+    }
+}
 
+fn format_skill_detail(detail: &status::SkillDetail) -> String {
     let mut out = String::new();
 
+    // Source
+    let rows = tui::table(&[
+        vec!["name".dimmed().to_string(), detail.name.bold().to_string()],
+        vec![
+            "source".dimmed().to_string(),
+            detail.source_path.display().to_string(),
+        ],
+        vec![
+            "size".dimmed().to_string(),
+            format!(
+                "{} in {} files",
+                tui::size(detail.total_size),
+                detail.files.len()
+            ),
+        ],
+    ]);
+    out.push_str(&tui::section("Skill", &rows));
+
+    // Files
+    if !detail.files.is_empty() {
+        let table_rows: Vec<Vec<String>> = detail
+            .files
+            .iter()
+            .map(|file| vec![file.path.clone(), tui::size(file.size).dimmed().to_string()])
+            .collect();
+        out.push_str(&tui::section("Files", &tui::table(&table_rows)));
+    }
+
+    // Frontmatter
+    if !detail.frontmatter.is_empty() {
+        let table_rows: Vec<Vec<String>> = detail
+            .frontmatter
+            .iter()
+            .map(|(key, value)| vec![key.dimmed().to_string(), value.clone()])
+            .collect();
+        out.push_str(&tui::section("Frontmatter", &tui::table(&table_rows)));
+    }
+
+    // Agents
+    let mut rows: Vec<String> = Vec::new();
+    for agent in &detail.agents {
+        rows.push(agent_state_badge(agent.agent, agent.state));
+        rows.push(format!(
+            "  {} {}",
+            "link    ".dimmed(),
+            agent.link_path.display()
+        ));
+        if let Some(target) = &agent.read_link {
+            rows.push(format!("  {} {}", "readlink".dimmed(), target.display()));
+        }
+        if let Some(canonical) = &agent.canonical {
+            rows.push(format!("  {} {}", "resolves".dimmed(), canonical.display()));
+        }
+        if let Some(reason) = &agent.reason {
+            rows.push(format!(
+                "  {} {}",
+                "why     ".dimmed(),
+                tui::label_warning(reason)
+            ));
+        }
+    }
+    out.push_str(&tui::section("Agents", &rows));
+
+    out
+}
+
+fn format_status(result: &status::StatusOk) -> String {
+    let mut out = String::new();
+
+    if let Some(profile) = &result.profile {
+        let rows = vec![tui::badge_info(profile)];
+        out.push_str(&tui::section("Profile", &rows));
+    }
+
     // Skills
     if result.skills.is_empty() {
         let rows = vec![tui::label_native("(none)")];
@@ -318,14 +1395,8 @@ fn format_status(result: &status::StatusOk) -> String {
         let mut table_rows: Vec<Vec<String>> = Vec::new();
         for skill in &result.skills {
             let mut row = vec![skill.name.bold().to_string()];
-            for (agent, state) in &skill.agents {
-                row.push(match state {
-                    SkillState::Synced => tui::badge_ok(agent),
-                    SkillState::RealDir => tui::badge_warn(&format!("{agent} (real dir)")),
-                    SkillState::BrokenSymlink => tui::badge_broken(&format!("{agent} (broken)")),
-                    SkillState::Missing => tui::badge_err(agent),
-                    SkillState::WrongTarget => tui::badge_warn(&format!("{agent} (wrong target)")),
-                });
+            for cell in &skill.agents {
+                row.push(agent_state_badge(cell.agent, cell.state));
             }
             table_rows.push(row);
         }
@@ -351,16 +1422,10 @@ fn format_status(result: &status::StatusOk) -> String {
         }
 
         // Agent rows
-        for (agent, state) in &result.instructions.agents {
+        for cell in &result.instructions.agents {
             table_rows.push(vec![
-                agent.to_string(),
-                match state {
-                    InstructionState::Synced => tui::badge_ok("symlinked"),
-                    InstructionState::DirectRead => tui::badge_info("native"),
-                    InstructionState::RealFile => tui::badge_warn("real file (conflict)"),
-                    InstructionState::Missing => tui::badge_err("missing"),
-                    InstructionState::Disabled => tui::badge_skip("disabled"),
-                },
+                cell.agent.to_string(),
+                state_badge(cell.state, cell.state.label()),
             ]);
         }
 
@@ -368,5 +1433,317 @@ fn format_status(result: &status::StatusOk) -> String {
         out.push_str(&tui::section("Instructions", &rows));
     }
 
+    // Extra files
+    if !result.files.is_empty() {
+        let table_rows: Vec<Vec<String>> = result
+            .files
+            .iter()
+            .map(|file| {
+                vec![
+                    file.dest.bold().to_string(),
+                    file.agent.to_string(),
+                    state_badge(file.state, file.state.label()),
+                    format!("← {}", file.source.display()).dimmed().to_string(),
+                ]
+            })
+            .collect();
+        out.push_str(&tui::section("Files", &tui::table(&table_rows)));
+    }
+
+    // Subagents, commands
+    for (title, rows) in [
+        ("Subagents", &result.subagents),
+        ("Commands", &result.commands),
+        ("MCP", &result.mcp),
+    ] {
+        if rows.is_empty() {
+            continue;
+        }
+        let table_rows: Vec<Vec<String>> = rows
+            .iter()
+            .map(|definition| {
+                std::iter::once(definition.name.bold().to_string())
+                    .chain(
+                        definition
+                            .agents
+                            .iter()
+                            .map(|cell| agent_state_badge(cell.agent, cell.state)),
+                    )
+                    .collect()
+            })
+            .collect();
+        out.push_str(&tui::section(title, &tui::table(&table_rows)));
+    }
+    if let Some(error) = &result.mcp_error {
+        let rows = vec![format!("{}  {}", tui::badge_err("invalid"), error.dimmed())];
+        out.push_str(&tui::section("MCP", &rows));
+    }
+
+    // Mappings
+    if !result.mappings.is_empty() {
+        let mut table_rows: Vec<Vec<String>> = Vec::new();
+        for mapping in &result.mappings {
+            table_rows.push(vec![
+                mapping.name.bold().to_string(),
+                if mapping.source_exists {
+                    tui::badge_ok("source")
+                } else {
+                    tui::badge_err("missing")
+                },
+                format!("{}", mapping.source.display()).dimmed().to_string(),
+            ]);
+            for entry in &mapping.entries {
+                let mut row = vec![format!("  {}", entry.name)];
+                row.extend(
+                    entry
+                        .agents
+                        .iter()
+                        .map(|cell| agent_state_badge(cell.agent, cell.state)),
+                );
+                table_rows.push(row);
+            }
+        }
+        out.push_str(&tui::section("Mappings", &tui::table(&table_rows)));
+    }
+
+    // Agent directories: everything that is not a managed link
+    {
+        use status::TargetEntryKind;
+
+        let mut table_rows: Vec<Vec<String>> = Vec::new();
+        let agents: std::collections::BTreeSet<AgentName> =
+            result.entries.iter().map(|e| e.agent).collect();
+        for agent in agents {
+            let agent_entries: Vec<_> =
+                result.entries.iter().filter(|e| e.agent == agent).collect();
+            if agent_entries.is_empty() {
+                continue;
+            }
+            let managed = agent_entries
+                .iter()
+                .filter(|e| e.kind == TargetEntryKind::Managed)
+                .count();
+            table_rows.push(vec![
+                agent.as_str().bold().to_string(),
+                tui::badge_ok(&format!("{managed} managed")),
+            ]);
+            for entry in agent_entries
+                .iter()
+                .filter(|e| e.kind != TargetEntryKind::Managed)
+            {
+                table_rows.push(vec![
+                    format!("  {}", entry.name),
+                    match entry.kind {
+                        TargetEntryKind::Managed => tui::badge_ok("managed"),
+                        TargetEntryKind::OrphanDir => tui::badge_warn("orphan real dir"),
+                        TargetEntryKind::ForeignSymlink => tui::badge_info("foreign symlink"),
+                        TargetEntryKind::Broken => tui::badge_broken("broken"),
+                        TargetEntryKind::File => tui::badge_info("file"),
+                        _ => tui::badge_info("other"),
+                    },
+                ]);
+            }
+        }
+        if !table_rows.is_empty() {
+            let rows = tui::table(&table_rows);
+            out.push_str(&tui::section("Agent directories", &rows));
+        }
+    }
+
+    // Ignored
+    if !result.ignored.is_empty() {
+        let table_rows: Vec<Vec<String>> = result
+            .ignored
+            .iter()
+            .map(|entry| {
+                vec![
+                    entry.name.bold().to_string(),
+                    tui::badge_skip(&format!("{} (.hanaignore)", entry.location)),
+                ]
+            })
+            .collect();
+        let rows = tui::table(&table_rows);
+        out.push_str(&tui::section("Ignored", &rows));
+    }
+
+    // Git
+    if !result.committed_links.is_empty() {
+        let rows: Vec<String> = result
+            .committed_links
+            .iter()
+            .map(|path| tui::label_warning(&format!("⚠ {} is committed", path.display())))
+            .chain(std::iter::once(
+                "[git] ignore_links = true — untrack with `git rm --cached <path>`"
+                    .dimmed()
+                    .to_string(),
+            ))
+            .collect();
+        out.push_str(&tui::section("Git", &rows));
+    }
+
+    // Legend
+    {
+        let legend: Vec<String> = status::SyncState::ALL
+            .iter()
+            .map(|state| state_badge(*state, state.label()))
+            .collect();
+        out.push_str(&tui::section("Legend", &[legend.join("  ")]));
+    }
+
     out
 }
+
+fn load_instructions_config(global: bool) -> Result<(PathBuf, Config), i32> {
+    let base_dir = match resolve_base_dir(global) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            return Err(1);
+        }
+    };
+
+    load_config(&base_dir).map(|config| (base_dir, config))
+}
+
+fn run_instructions_diff(global: bool) -> i32 {
+    use instructions::DiffLine;
+
+    let (base_dir, config) = match load_instructions_config(global) {
+        Ok(loaded) => loaded,
+        Err(code) => return code,
+    };
+    let result = instructions::diff(&config, &base_dir, global);
+
+    print!("{}", tui::header("instructions diff", false));
+    if result.divergences.is_empty() {
+        print!("{}", tui::footer_no_changes());
+        return 0;
+    }
+
+    let source_name = result
+        .source
+        .strip_prefix(&base_dir)
+        .unwrap_or(&result.source)
+        .display()
+        .to_string();
+    for divergence in &result.divergences {
+        let path = divergence
+            .path
+            .strip_prefix(&base_dir)
+            .unwrap_or(&divergence.path)
+            .display()
+            .to_string();
+        let mut rows: Vec<String> = divergence
+            .diff
+            .iter()
+            .map(|line| match line {
+                DiffLine::Same(text) => format!("  {}", text.dimmed()),
+                DiffLine::Removed(text) => format!("{}", format!("- {text}").red()),
+                DiffLine::Added(text) => format!("{}", format!("+ {text}").green()),
+                _ => String::new(),
+            })
+            .collect();
+        rows.push(String::new());
+        rows.push(format!(
+            "{} hana instructions resolve {} merge|keep|adopt",
+            "resolve:".dimmed(),
+            divergence.agent
+        ));
+        let title = if result.source_exists {
+            format!("{source_name} → {path}")
+        } else {
+            format!("{path} ({source_name} missing)")
+        };
+        print!("{}", tui::section(&title, &rows));
+    }
+    print!("{}", tui::footer_done());
+    0
+}
+
+fn run_instructions_resolve(
+    agent: &str,
+    choice: instructions::Resolution,
+    global: bool,
+    dry_run: bool,
+) -> i32 {
+    let (base_dir, config) = match load_instructions_config(global) {
+        Ok(loaded) => loaded,
+        Err(code) => return code,
+    };
+    let Some(agent) = config.find_agent(agent) else {
+        eprintln!("{} unknown agent '{agent}'", "error:".red().bold());
+        return 1;
+    };
+    let mut opts = instructions::ResolveOptions::new(agent, choice);
+    opts.dry_run = dry_run;
+    opts.global = global;
+
+    let result = match instructions::resolve(&config, &base_dir, &opts) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("{} {e}", "error:".red().bold());
+            return 1;
+        }
+    };
+
+    let rel = |path: &std::path::Path| {
+        path.strip_prefix(&base_dir)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    let mut rows = vec![vec![
+        tui::label_collected(result.choice.as_str()),
+        rel(&result.path),
+        format!("→ {}", rel(&result.source)),
+    ]];
+    if let Some(backup) = &result.backup {
+        let backed_up = if result.choice == instructions::Resolution::Adopt {
+            &result.source
+        } else {
+            &result.path
+        };
+        rows.push(vec![
+            tui::label_removed("backup"),
+            rel(backed_up),
+            format!("→ {}", rel(backup)),
+        ]);
+    }
+    if let Some(merged) = &result.merged {
+        rows.push(vec![
+            tui::label_warning("conflict"),
+            rel(&result.path),
+            format!("→ {}", rel(merged)),
+        ]);
+    }
+    if result.relinked {
+        rows.push(vec![
+            tui::label_symlinked("symlinked"),
+            rel(&result.path),
+            format!("({})", result.agent),
+        ]);
+    }
+
+    print!("{}", tui::header("instructions resolve", opts.dry_run));
+    print!("{}", tui::section("Instructions", &tui::table(&rows)));
+
+    let mut notes = Vec::new();
+    if let Some(merged) = &result.merged {
+        notes.push(tui::label_warning(&format!(
+            "⚠ {} has conflict markers; edit it, move it over {}, then run hana instructions resolve {} keep",
+            rel(merged),
+            rel(&result.source),
+            result.agent
+        )));
+    } else if !result.relinked {
+        notes.push(tui::label_warning(&format!(
+            "⚠ run hana sync to regenerate {}",
+            rel(&result.path)
+        )));
+    }
+    if !notes.is_empty() {
+        print!("{}", tui::section("Next", &notes));
+    }
+    print!("{}", tui::footer_done());
+    0
+}
@@ -0,0 +1,277 @@
+//! `hana use`: switch between `[profile.<name>]` skill and instruction sets.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{AgentName, Config, TargetFeature};
+use crate::sync::{self, SyncOk, SyncOptions};
+
+// --- Options ---
+
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct UseOptions {
+    /// Profile to switch to (`None` = back to the plain config)
+    pub profile: Option<String>,
+    pub global: bool,
+    pub dry_run: bool,
+}
+
+// --- Ok ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct UseOk {
+    pub previous: Option<String>,
+    pub profile: Option<String>,
+    /// Agents that read the `[source]` instruction file directly, so they keep its
+    /// instructions instead of the profile's
+    pub unaffected: Vec<AgentName>,
+    /// Skill and instruction changes made for the new profile
+    pub sync: SyncOk,
+}
+
+// --- Error ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum UseError {
+    /// No `[profile.<name>]` in hana.toml
+    UnknownProfile {
+        name: String,
+        available: Vec<String>,
+    },
+    /// The profile's `instructions` file does not exist
+    InstructionsMissing { profile: String, path: PathBuf },
+    /// `.agents/.hanaprofile` could not be written or removed
+    StateFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl std::fmt::Display for UseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownProfile { name, available } if available.is_empty() => {
+                write!(f, "no profile '{name}': hana.toml defines no [profile.*]")
+            }
+            Self::UnknownProfile { name, available } => {
+                write!(
+                    f,
+                    "no profile '{name}' (available: {})",
+                    available.join(", ")
+                )
+            }
+            Self::InstructionsMissing { profile, path } => {
+                write!(
+                    f,
+                    "profile '{profile}' uses {}, which does not exist",
+                    path.display()
+                )
+            }
+            Self::StateFile { path, source } => {
+                write!(f, "cannot update {}: {source}", path.display())
+            }
+        }
+    }
+}
+
+// --- pub fn run ---
+
+/// Record `opts.profile` as the active profile, then re-broadcast skills and relink
+/// instructions under it. `config` must not have a profile applied yet.
+pub fn run(config: &Config, base_dir: &Path, opts: &UseOptions) -> Result<UseOk, UseError> {
+    let state_path = config.resolve_profile_state_path(base_dir);
+    let previous = fs::read_to_string(&state_path)
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    let mut effective = config.clone();
+    if let Some(name) = &opts.profile {
+        effective
+            .apply_profile(name)
+            .map_err(|_| UseError::UnknownProfile {
+                name: name.clone(),
+                available: config.profiles.keys().cloned().collect(),
+            })?;
+        let instructions = effective.resolve_source_instruction_path(base_dir, opts.global);
+        if !instructions.exists() {
+            return Err(UseError::InstructionsMissing {
+                profile: name.clone(),
+                path: instructions,
+            });
+        }
+    }
+
+    if !opts.dry_run {
+        let written = match &opts.profile {
+            Some(name) => fs::create_dir_all(state_path.parent().unwrap_or(base_dir))
+                .and_then(|_| fs::write(&state_path, format!("{name}\n"))),
+            None if state_path.exists() => fs::remove_file(&state_path),
+            None => Ok(()),
+        };
+        written.map_err(|source| UseError::StateFile {
+            path: state_path.clone(),
+            source,
+        })?;
+    }
+
+    let source = effective.resolve_configured_instruction_path(base_dir, opts.global);
+    let unaffected = if effective.resolve_source_instruction_path(base_dir, opts.global) == source {
+        vec![]
+    } else {
+        effective
+            .enabled_targets(TargetFeature::Instructions)
+            .filter(|agent| {
+                effective
+                    .resolve_target_instruction_path(*agent, base_dir, opts.global)
+                    .is_some_and(|path| path == source)
+            })
+            .collect()
+    };
+
+    let sync = sync::run_profile(
+        &effective,
+        base_dir,
+        &SyncOptions {
+            dry_run: opts.dry_run,
+            global: opts.global,
+            ..Default::default()
+        },
+    );
+
+    Ok(UseOk {
+        previous,
+        profile: opts.profile.clone(),
+        unaffected,
+        sync,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AgentName;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"
+[target.codex]
+skills = false
+
+[target.pi]
+skills = false
+
+[target.opencode]
+skills = false
+
+[profile.review]
+skills = ["review-*"]
+instructions = "AGENTS.review.md"
+"#;
+
+    fn setup() -> (TempDir, Config) {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for skill in ["review-pr", "deploy"] {
+            fs::create_dir_all(root.join(".agents/skills").join(skill)).unwrap();
+            fs::write(root.join(".agents/skills").join(skill).join("SKILL.md"), "").unwrap();
+        }
+        fs::write(root.join("AGENTS.md"), "# Default\n").unwrap();
+        fs::write(root.join("AGENTS.review.md"), "# Review\n").unwrap();
+        let config = Config::parse(CONFIG).unwrap();
+        sync::run(&config, root, &SyncOptions::default());
+        (tmp, config)
+    }
+
+    fn opts(profile: Option<&str>) -> UseOptions {
+        UseOptions {
+            profile: profile.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_use_narrows_skills_and_swaps_instructions() {
+        let (tmp, config) = setup();
+        let root = tmp.path();
+
+        let ok = run(&config, root, &opts(Some("review"))).unwrap();
+
+        assert_eq!(ok.previous, None);
+        let unlinked: Vec<_> = ok
+            .sync
+            .skills_unlinked
+            .iter()
+            .map(|s| (s.skill.as_str(), s.agent))
+            .collect();
+        assert_eq!(unlinked, vec![("deploy", AgentName::Claude)]);
+        assert!(root.join(".claude/skills/review-pr").is_symlink());
+        assert!(!root.join(".claude/skills/deploy").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("CLAUDE.md")).unwrap(),
+            "# Review\n"
+        );
+        // Codex reads the real AGENTS.md, which the profile leaves alone
+        assert_eq!(
+            fs::read_to_string(root.join("AGENTS.md")).unwrap(),
+            "# Default\n"
+        );
+        assert!(ok.unaffected.contains(&AgentName::Codex));
+        assert!(!ok.unaffected.contains(&AgentName::Claude));
+        assert_eq!(
+            fs::read_to_string(root.join(".agents/.hanaprofile")).unwrap(),
+            "review\n"
+        );
+        assert!(
+            fs::read_to_string(root.join(".gitignore"))
+                .unwrap()
+                .contains("/.agents/.hanaprofile\n")
+        );
+
+        // A fresh load picks the profile up again
+        let mut reloaded = config.clone();
+        reloaded.apply_saved_profile(root).unwrap();
+        assert_eq!(reloaded.active_profile.as_deref(), Some("review"));
+    }
+
+    #[test]
+    fn test_clear_restores_everything() {
+        let (tmp, config) = setup();
+        let root = tmp.path();
+        run(&config, root, &opts(Some("review"))).unwrap();
+
+        let ok = run(&config, root, &opts(None)).unwrap();
+
+        assert_eq!(ok.previous.as_deref(), Some("review"));
+        assert!(ok.unaffected.is_empty());
+        assert!(root.join(".claude/skills/deploy").is_symlink());
+        assert_eq!(
+            fs::read_to_string(root.join("CLAUDE.md")).unwrap(),
+            "# Default\n"
+        );
+        assert!(!root.join(".agents/.hanaprofile").exists());
+        assert!(!root.join(".gitignore").exists());
+    }
+
+    #[test]
+    fn test_rejects_unknown_profile_and_missing_instructions() {
+        let (tmp, mut config) = setup();
+        let root = tmp.path();
+
+        let err = run(&config, root, &opts(Some("oncall"))).unwrap_err();
+        assert!(
+            matches!(err, UseError::UnknownProfile { ref available, .. } if available == &["review"])
+        );
+
+        fs::remove_file(root.join("AGENTS.review.md")).unwrap();
+        let err = run(&config, root, &opts(Some("review"))).unwrap_err();
+        assert!(matches!(err, UseError::InstructionsMissing { .. }));
+        assert!(!root.join(".agents/.hanaprofile").exists());
+
+        // A state file naming a removed profile fails the load instead of being ignored
+        fs::write(root.join(".agents/.hanaprofile"), "oncall\n").unwrap();
+        assert!(config.apply_saved_profile(root).is_err());
+    }
+}
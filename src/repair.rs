@@ -0,0 +1,325 @@
+//! `hana repair`: rewrite managed symlinks to the configured link style.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, LinkStyle, MappingKind, TargetFeature};
+use crate::helper::broadcast_target_symlink::points_to;
+use crate::helper::collect_source_skills::collect_source_skills;
+use crate::helper::generate_instruction_file::wants_generated;
+use crate::helper::link_target::link_target;
+use crate::helper::list_dir_entries::list_dir_entries;
+use crate::helper::load_ignore_rules::load_ignore_rules;
+use crate::helper::resolve_managed_files::resolve_managed_files;
+use crate::helper::resolve_skill_rule::resolve_skill_rule;
+use crate::helper::resolve_target_destinations::resolve_target_destinations;
+use crate::sync::SyncWarning;
+
+// --- Options ---
+
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct RepairOptions {
+    pub dry_run: bool,
+    pub global: bool,
+}
+
+// --- Ok ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RepairOk {
+    pub style: LinkStyle,
+    pub changes: Vec<RepairChange>,
+    pub warnings: Vec<SyncWarning>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RepairKind {
+    /// Resolved to the source, but relative where absolute was wanted (or vice versa)
+    Restyled,
+    /// Resolved to the source through another path (`..` detour, symlinked parent, …)
+    Rerouted,
+    /// Pointed somewhere else, or nowhere
+    Retargeted,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct RepairChange {
+    pub path: PathBuf,
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub kind: RepairKind,
+}
+
+// --- pub fn run ---
+
+/// Rewrite every existing managed symlink so its text matches the configured link style.
+/// Missing links and real files are left to `hana sync`.
+pub fn run(config: &Config, base_dir: &Path, opts: &RepairOptions) -> RepairOk {
+    let style = config.source.link_style;
+    let mut changes = Vec::new();
+    let mut warnings = Vec::new();
+
+    // Skills
+    let source_dir = config.resolve_source_skills_path(base_dir, opts.global);
+    let skills = match collect_source_skills(
+        &source_dir,
+        &config.source.filter,
+        &load_ignore_rules(&config.resolve_ignore_path(base_dir)),
+        config.source.namespaces,
+    ) {
+        Ok(skills) => skills,
+        Err(warning) => {
+            warnings.push(warning);
+            vec![]
+        }
+    };
+    let skill_targets =
+        resolve_target_destinations(config, base_dir, opts.global, TargetFeature::Skills);
+
+    let mut links: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for skill in &skills {
+        let source = source_dir.join(skill);
+        let (rule, _) = resolve_skill_rule(config, &source, skill);
+        let link_name = config.skill_link_name(skill);
+        for (agent, dir) in &skill_targets {
+            if rule.allows(*agent) {
+                links.push((dir.join(&link_name), source.clone()));
+            }
+        }
+    }
+
+    // Instructions
+    let instruction_source = config.resolve_source_instruction_path(base_dir, opts.global);
+    if instruction_source.exists() {
+        let instruction_targets =
+            resolve_target_destinations(config, base_dir, opts.global, TargetFeature::Instructions);
+        links.extend(
+            instruction_targets
+                .into_iter()
+                .filter(|(agent, _)| !wants_generated(config, base_dir, opts.global, *agent))
+                .map(|(_, dest)| (dest, instruction_source.clone())),
+        );
+    }
+
+    // Extra files
+    links.extend(
+        resolve_managed_files(config, base_dir, opts.global)
+            .into_iter()
+            .filter(|file| file.source.exists())
+            .map(|file| (file.dest, file.source)),
+    );
+
+    // Mappings
+    for mapping in &config.mappings {
+        let source = mapping.resolve_source(base_dir);
+        for dest in config
+            .resolve_mapping_dests(mapping, base_dir)
+            .into_values()
+        {
+            match mapping.kind {
+                MappingKind::Dir => links.extend(
+                    list_dir_entries(&source, false)
+                        .into_iter()
+                        .map(|(name, target)| (dest.join(name), target)),
+                ),
+                MappingKind::File if source.exists() => links.push((dest, source.clone())),
+                MappingKind::File => {}
+            }
+        }
+    }
+
+    links.sort();
+    links.dedup();
+
+    for (link, source) in &links {
+        let Some(change) = plan_repair(link, source, style) else {
+            continue;
+        };
+        if !opts.dry_run
+            && let Err(e) =
+                fs::remove_file(link).and_then(|_| std::os::unix::fs::symlink(&change.to, link))
+        {
+            warnings.push(SyncWarning::IoFailed {
+                operation: format!("repair symlink ({})", link.display()),
+                detail: e.to_string(),
+            });
+            continue;
+        }
+        changes.push(change);
+    }
+
+    RepairOk {
+        style,
+        changes,
+        warnings,
+    }
+}
+
+/// Decide what an existing symlink should become. Non-symlinks are never touched.
+fn plan_repair(link: &Path, source: &Path, style: LinkStyle) -> Option<RepairChange> {
+    if !link.is_symlink() {
+        return None;
+    }
+    let from = fs::read_link(link).ok()?;
+    let to = link_target(link, source, style);
+    if from == to {
+        return None;
+    }
+
+    let kind = if !points_to(link, source) {
+        RepairKind::Retargeted
+    } else if from.is_absolute() != to.is_absolute() {
+        RepairKind::Restyled
+    } else {
+        RepairKind::Rerouted
+    };
+
+    Some(RepairChange {
+        path: link.to_path_buf(),
+        from,
+        to,
+        kind,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    fn setup_source(tmp: &Path) {
+        let skills = tmp.join(".agents/skills");
+        fs::create_dir_all(skills.join("my-skill")).unwrap();
+        fs::write(skills.join("my-skill/SKILL.md"), "# Skill").unwrap();
+        fs::write(tmp.join("AGENTS.md"), "# Instructions").unwrap();
+    }
+
+    #[test]
+    fn test_repair_restyles_absolute_link() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let claude = tmp.path().join(".claude/skills");
+        fs::create_dir_all(&claude).unwrap();
+        symlink(
+            tmp.path().join(".agents/skills/my-skill"),
+            claude.join("my-skill"),
+        )
+        .unwrap();
+
+        let result = run(&Config::default(), tmp.path(), &RepairOptions::default());
+
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].kind, RepairKind::Restyled);
+        assert_eq!(
+            fs::read_link(claude.join("my-skill")).unwrap(),
+            PathBuf::from("../../.agents/skills/my-skill")
+        );
+    }
+
+    #[test]
+    fn test_repair_absolute_style() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let config = Config::parse("[source]\nlink_style = \"absolute\"\n").unwrap();
+        crate::sync::run(&Config::default(), tmp.path(), &Default::default());
+
+        let result = run(&config, tmp.path(), &RepairOptions::default());
+
+        let paths: Vec<_> = result.changes.iter().map(|c| c.path.clone()).collect();
+        assert!(paths.contains(&tmp.path().join(".claude/skills/my-skill")));
+        assert!(paths.contains(&tmp.path().join("CLAUDE.md")));
+        assert_eq!(
+            fs::read_link(tmp.path().join("CLAUDE.md")).unwrap(),
+            tmp.path().join("AGENTS.md")
+        );
+
+        // Second run has nothing left to do
+        let again = run(&config, tmp.path(), &RepairOptions::default());
+        assert!(again.changes.is_empty());
+    }
+
+    #[test]
+    fn test_repair_reroutes_and_retargets() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let claude = tmp.path().join(".claude/skills");
+        let opencode = tmp.path().join(".opencode/skills");
+        fs::create_dir_all(&claude).unwrap();
+        fs::create_dir_all(&opencode).unwrap();
+        fs::create_dir_all(tmp.path().join("elsewhere")).unwrap();
+        symlink(
+            "../../.claude/../.agents/skills/my-skill",
+            claude.join("my-skill"),
+        )
+        .unwrap();
+        symlink(tmp.path().join("elsewhere"), opencode.join("my-skill")).unwrap();
+
+        let result = run(&Config::default(), tmp.path(), &RepairOptions::default());
+
+        let kind_of = |path: &Path| {
+            result
+                .changes
+                .iter()
+                .find(|c| c.path == path)
+                .map(|c| c.kind)
+        };
+        assert_eq!(
+            kind_of(&claude.join("my-skill")),
+            Some(RepairKind::Rerouted)
+        );
+        assert_eq!(
+            kind_of(&opencode.join("my-skill")),
+            Some(RepairKind::Retargeted)
+        );
+        assert_eq!(
+            fs::read_link(opencode.join("my-skill")).unwrap(),
+            PathBuf::from("../../.agents/skills/my-skill")
+        );
+    }
+
+    #[test]
+    fn test_repair_dry_run_and_real_files_untouched() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        fs::write(tmp.path().join("CLAUDE.md"), "real file").unwrap();
+        let claude = tmp.path().join(".claude/skills");
+        fs::create_dir_all(&claude).unwrap();
+        let absolute = tmp.path().join(".agents/skills/my-skill");
+        symlink(&absolute, claude.join("my-skill")).unwrap();
+
+        let opts = RepairOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let result = run(&Config::default(), tmp.path(), &opts);
+
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(fs::read_link(claude.join("my-skill")).unwrap(), absolute);
+        assert!(!tmp.path().join("CLAUDE.md").is_symlink());
+    }
+
+    #[test]
+    fn test_repair_skips_frontmatter_excluded_agent() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let skill = tmp.path().join(".agents/skills/my-skill");
+        fs::write(
+            skill.join("SKILL.md"),
+            "---\nhana:\n  exclude: [claude]\n---\n# Skill\n",
+        )
+        .unwrap();
+        let claude = tmp.path().join(".claude/skills");
+        fs::create_dir_all(&claude).unwrap();
+        symlink(&skill, claude.join("my-skill")).unwrap();
+
+        let result = run(&Config::default(), tmp.path(), &RepairOptions::default());
+
+        assert!(result.changes.is_empty());
+        assert_eq!(fs::read_link(claude.join("my-skill")).unwrap(), skill);
+    }
+}
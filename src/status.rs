@@ -1,45 +1,321 @@
+//! `hana status`: the state of every managed entry, computed without changing anything.
+
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::config::Config;
+use crate::config::{AgentName, Config, Mapping, MappingKind, McpServer, RenderTarget};
+use crate::helper::classify_target_entries::classify_target_entries;
+use crate::helper::collect_managed_links::collect_managed_links;
+use crate::helper::collect_source_skills::collect_source_skills;
+use crate::helper::generate_instruction_file::{expected_generated_file, wants_generated};
+use crate::helper::git_tracked_paths::git_tracked_paths;
+use crate::helper::list_dir_entries::list_dir_entries;
+use crate::helper::load_env_file::load_env_file;
+use crate::helper::load_ignore_rules::{IgnoreRules, load_ignore_rules};
+use crate::helper::mcp_config_file::{McpConfigFile, expected_mcp_entry};
+use crate::helper::mcp_ownership::McpOwnership;
+use crate::helper::parse_frontmatter::parse_frontmatter;
+use crate::helper::plan_rendered_files::{RenderPlan, plan_rendered_files};
+use crate::helper::render_instructions::parse_generated;
+use crate::helper::resolve_managed_files::resolve_managed_files;
+use crate::helper::resolve_skill_rule::resolve_skill_rule;
 
 // --- Ok ---
 
+pub use crate::helper::classify_target_entries::{TargetEntry, TargetEntryKind};
+
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct StatusOk {
+    /// Profile chosen with `hana use`
+    pub profile: Option<String>,
     pub skills: Vec<SkillStatusEntry>,
     pub instructions: InstructionStatusEntry,
+    /// `[target.x] files` entries
+    pub files: Vec<FileStatusEntry>,
+    /// A row per canonical subagent definition, with each agent's rendered file
+    pub subagents: Vec<SkillStatusEntry>,
+    /// Same for slash commands (`[source] commands = true`)
+    pub commands: Vec<SkillStatusEntry>,
+    /// A row per MCP server, with its entry in each agent config file
+    pub mcp: Vec<SkillStatusEntry>,
+    /// Why `.agents/mcp.toml` could not be read; no MCP rows then
+    pub mcp_error: Option<String>,
+    pub mappings: Vec<MappingStatusEntry>,
+    pub ignored: Vec<IgnoredEntry>,
+    /// Every entry in each enabled agent skills directory, classified
+    pub entries: Vec<TargetEntry>,
+    /// Managed links tracked by git although `[git] ignore_links = true`
+    pub committed_links: Vec<PathBuf>,
+}
+
+impl StatusOk {
+    /// Number of skill and instruction cells that `hana sync` would still change.
+    pub fn problem_count(&self) -> usize {
+        self.skills
+            .iter()
+            .flat_map(|skill| &skill.agents)
+            .chain(&self.instructions.agents)
+            .map(|cell| cell.state)
+            .chain(self.files.iter().map(|file| file.state))
+            .chain(
+                self.subagents
+                    .iter()
+                    .chain(&self.commands)
+                    .chain(&self.mcp)
+                    .flat_map(|definition| &definition.agents)
+                    .map(|cell| cell.state),
+            )
+            .chain(
+                self.mappings
+                    .iter()
+                    .flat_map(|mapping| &mapping.entries)
+                    .flat_map(|entry| &entry.agents)
+                    .map(|cell| cell.state),
+            )
+            .filter(|state| state.is_problem())
+            .count()
+            + usize::from(self.mcp_error.is_some())
+    }
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct SkillStatusEntry {
     pub name: String,
-    pub agents: Vec<(String, SkillState)>,
+    pub agents: Vec<AgentState>,
+}
+
+/// One agent's cell in a status row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AgentState {
+    pub agent: AgentName,
+    pub state: SyncState,
+}
+
+/// A directory skipped because it matches `.hanaignore`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct IgnoredEntry {
+    /// `source` or the agent whose skills directory holds the entry
+    pub location: String,
+    pub name: String,
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct InstructionStatusEntry {
     pub source: String,
     pub source_exists: bool,
-    pub agents: Vec<(String, InstructionState)>,
+    pub agents: Vec<AgentState>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum SkillState {
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct FileStatusEntry {
+    pub agent: AgentName,
+    /// Destination as written in hana.toml
+    pub dest: String,
+    pub source: PathBuf,
+    pub state: SyncState,
+}
+
+/// One `[[mapping]]`: a row per source entry (or a single row for a file mapping).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct MappingStatusEntry {
+    pub name: String,
+    pub source: PathBuf,
+    pub source_exists: bool,
+    pub entries: Vec<SkillStatusEntry>,
+}
+
+/// State of one agent's entry for a skill or an instruction file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SyncState {
+    /// Symlink resolves to the source
     Synced,
-    RealDir,
-    BrokenSymlink,
-    Missing,
+    /// Feature turned off for this agent in hana.toml
+    Disabled,
+    /// Agent reads the source path directly, no link needed
+    Native,
+    /// Real file or directory where the link should be
+    Conflict,
+    /// Symlink whose target does not exist
+    Broken,
+    /// Symlink that resolves somewhere other than the source
     WrongTarget,
+    /// Nothing at the link path
+    Missing,
+    /// Skipped on purpose by a per-skill rule
+    Excluded,
+    /// Generated file older than its source or fragment
+    Stale,
+    /// Generated file edited by hand after hana wrote it
+    Edited,
+    /// The agent cannot express this definition (e.g. a command field it lacks)
+    Unsupported,
+}
+
+impl SyncState {
+    pub const ALL: [SyncState; 11] = [
+        SyncState::Synced,
+        SyncState::Native,
+        SyncState::Disabled,
+        SyncState::Excluded,
+        SyncState::Unsupported,
+        SyncState::Conflict,
+        SyncState::WrongTarget,
+        SyncState::Broken,
+        SyncState::Stale,
+        SyncState::Edited,
+        SyncState::Missing,
+    ];
+
+    pub fn is_problem(self) -> bool {
+        matches!(
+            self,
+            SyncState::Conflict
+                | SyncState::Broken
+                | SyncState::WrongTarget
+                | SyncState::Missing
+                | SyncState::Stale
+                | SyncState::Edited
+        )
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SyncState::Synced => "synced",
+            SyncState::Disabled => "disabled",
+            SyncState::Native => "native",
+            SyncState::Conflict => "conflict",
+            SyncState::Broken => "broken",
+            SyncState::WrongTarget => "wrong target",
+            SyncState::Missing => "missing",
+            SyncState::Excluded => "excluded",
+            SyncState::Stale => "stale",
+            SyncState::Edited => "edited",
+            SyncState::Unsupported => "unsupported",
+        }
+    }
+}
+
+/// Drill-down for a single skill (`hana status <skill>`).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SkillDetail {
+    pub name: String,
+    pub source_path: PathBuf,
+    pub files: Vec<SkillFile>,
+    pub total_size: u64,
+    pub frontmatter: Vec<(String, String)>,
+    pub agents: Vec<SkillAgentDetail>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum InstructionState {
-    Synced,
-    DirectRead,
-    RealFile,
-    Missing,
-    Disabled,
+#[non_exhaustive]
+pub struct SkillFile {
+    /// Path relative to the skill directory
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct SkillAgentDetail {
+    pub agent: AgentName,
+    pub link_path: PathBuf,
+    pub state: SyncState,
+    /// Raw `read_link` target, if the entry is a symlink
+    pub read_link: Option<PathBuf>,
+    /// Where the entry resolves to, if it resolves at all
+    pub canonical: Option<PathBuf>,
+    /// Why the entry is not `Synced`
+    pub reason: Option<String>,
+}
+
+/// `hana status --all`: each level's status, plus which copy of a skill every agent loads.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct LayeredStatusOk {
+    pub global: Option<StatusOk>,
+    pub project: Option<StatusOk>,
+    /// A row per skill name found at either level
+    pub skills: Vec<LayeredSkillEntry>,
+}
+
+impl LayeredStatusOk {
+    pub fn problem_count(&self) -> usize {
+        [&self.global, &self.project]
+            .into_iter()
+            .flatten()
+            .map(StatusOk::problem_count)
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SkillLevel {
+    Global,
+    Project,
+}
+
+impl SkillLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            SkillLevel::Global => "global",
+            SkillLevel::Project => "project",
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct LayeredSkillEntry {
+    pub name: String,
+    /// Levels whose source has the skill
+    pub levels: Vec<SkillLevel>,
+    /// The copy each agent loads. Project shadows global.
+    pub agents: Vec<LoadedSkill>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LoadedSkill {
+    pub agent: AgentName,
+    /// `None` = neither level links the skill for this agent
+    pub level: Option<SkillLevel>,
+}
+
+// --- Error ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StatusError {
+    /// No skill with this name in the source directory
+    SkillNotFound { name: String, source_dir: PathBuf },
+    /// Skill name is not a plain directory name (or `<category>/<skill>` with namespaces)
+    InvalidSkillName { name: String },
+}
+
+impl std::fmt::Display for StatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SkillNotFound { name, source_dir } => {
+                write!(
+                    f,
+                    "skill not found: {name} (looked in {})",
+                    source_dir.display()
+                )
+            }
+            Self::InvalidSkillName { name } => write!(f, "invalid skill name: {name}"),
+        }
+    }
 }
 
 // --- pub fn run ---
@@ -47,44 +323,41 @@ pub enum InstructionState {
 pub fn run(config: &Config, base_dir: &Path, global: bool) -> StatusOk {
     let source_dir = config.resolve_source_skills_path(base_dir, global);
 
-    let mut skill_names: Vec<String> = if source_dir.exists() {
-        fs::read_dir(&source_dir)
-            .into_iter()
-            .flatten()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_dir())
-            .map(|e| e.file_name().to_string_lossy().to_string())
-            .collect()
-    } else {
-        vec![]
-    };
+    let ignore = load_ignore_rules(&config.resolve_ignore_path(base_dir));
+    let mut skill_names = collect_source_skills(
+        &source_dir,
+        &config.source.filter,
+        &ignore,
+        config.source.namespaces,
+    )
+    .unwrap_or_default();
     skill_names.sort();
 
-    let skill_targets: Vec<(String, PathBuf)> = Config::agent_names()
-        .filter_map(|agent| {
-            let name = agent.as_str();
-            let target_dir = config.resolve_target_skills_path(name, base_dir, global)?;
-            if target_dir == source_dir {
-                return None;
-            }
-            Some((name.to_string(), target_dir))
-        })
-        .collect();
+    let skill_targets = skill_target_dirs(config, base_dir, global);
 
     let skills = skill_names
         .iter()
         .map(|name| {
             let expected_target = source_dir.join(name);
-            let agent_states: Vec<(String, SkillState)> = skill_targets
+            let (rule, _) = resolve_skill_rule(config, &expected_target, name);
+            let agent_states: Vec<AgentState> = skill_targets
                 .iter()
                 .filter_map(|(agent, agent_dir)| {
                     let target_config = config.targets.get(agent)?;
-                    if !target_config.skills {
-                        return Some((agent.clone(), SkillState::Missing));
-                    }
-                    let link_path = agent_dir.join(name);
-                    let state = check_skill_state(&link_path, &expected_target);
-                    Some((agent.clone(), state))
+                    let state = if !target_config.skills {
+                        SyncState::Disabled
+                    } else if *agent_dir == source_dir {
+                        SyncState::Native
+                    } else if !rule.allows(*agent) {
+                        SyncState::Excluded
+                    } else {
+                        let link_path = agent_dir.join(config.skill_link_name(name));
+                        check_link_state(&link_path, &expected_target)
+                    };
+                    Some(AgentState {
+                        agent: *agent,
+                        state,
+                    })
                 })
                 .collect();
             SkillStatusEntry {
@@ -94,73 +367,529 @@ pub fn run(config: &Config, base_dir: &Path, global: bool) -> StatusOk {
         })
         .collect();
 
+    let enabled_skill_targets: Vec<(AgentName, PathBuf)> = skill_targets
+        .iter()
+        .filter(|(agent, dir)| {
+            *dir != source_dir && config.targets.get(agent).map(|t| t.skills).unwrap_or(false)
+        })
+        .cloned()
+        .collect();
+    let entries = classify_target_entries(&source_dir, &enabled_skill_targets);
+
+    let ignored = std::iter::once(("source".to_string(), source_dir.clone()))
+        .chain(
+            enabled_skill_targets
+                .iter()
+                .map(|(agent, dir)| (agent.as_str().to_string(), dir.clone())),
+        )
+        .flat_map(|(location, dir)| {
+            ignored_entries(&dir, &ignore)
+                .into_iter()
+                .map(move |name| IgnoredEntry {
+                    location: location.clone(),
+                    name,
+                })
+        })
+        .collect();
+
     // Instruction status
     let source_path = config.resolve_source_instruction_path(base_dir, global);
     let source_exists = source_path.exists();
 
-    let instruction_agents = Config::agent_names()
+    let instruction_agents = config
+        .agent_names()
         .map(|agent| {
-            let name = agent.as_str();
+            let cell = |state| AgentState { agent, state };
             let disabled = config
                 .targets
-                .get(name)
+                .get(&agent)
                 .map(|t| !t.instructions)
                 .unwrap_or(true);
 
             if disabled {
-                return (name.to_string(), InstructionState::Disabled);
+                return cell(SyncState::Disabled);
             }
 
-            let Some(link_path) = config.resolve_target_instruction_path(name, base_dir, global)
+            let Some(link_path) = config.resolve_target_instruction_path(agent, base_dir, global)
             else {
-                return (name.to_string(), InstructionState::Missing);
+                return cell(SyncState::Missing);
             };
 
-            if link_path == source_path {
-                return (name.to_string(), InstructionState::DirectRead);
+            if link_path == source_path
+                || link_path == config.resolve_configured_instruction_path(base_dir, global)
+            {
+                return cell(SyncState::Native);
             }
 
-            if link_path.is_symlink() {
-                match (fs::canonicalize(&link_path), fs::canonicalize(&source_path)) {
-                    (Ok(a), Ok(b)) if a == b => (name.to_string(), InstructionState::Synced),
-                    _ => (name.to_string(), InstructionState::Missing),
-                }
-            } else if link_path.exists() {
-                (name.to_string(), InstructionState::RealFile)
+            cell(if wants_generated(config, base_dir, global, agent) {
+                let expected = expected_generated_file(config, base_dir, global, agent);
+                check_generated_state(&link_path, expected.map(|e| e.content).as_deref())
             } else {
-                (name.to_string(), InstructionState::Missing)
-            }
+                check_link_state(&link_path, &source_path)
+            })
+        })
+        .collect();
+
+    let files = resolve_managed_files(config, base_dir, global)
+        .into_iter()
+        .map(|file| FileStatusEntry {
+            agent: file.agent,
+            state: if file.dest == file.source {
+                SyncState::Native
+            } else {
+                check_link_state(&file.dest, &file.source)
+            },
+            dest: file.display,
+            source: file.source,
         })
         .collect();
 
+    let subagents = rendered_status(
+        &config.resolve_source_subagents_path(base_dir, global),
+        &config.resolve_subagent_targets(base_dir, global),
+    );
+    let commands = rendered_status(
+        &config.resolve_source_commands_path(base_dir, global),
+        &config.resolve_command_targets(base_dir, global),
+    );
+
+    let (mcp, mcp_error) = match config.load_mcp_servers(base_dir, global) {
+        Ok(servers) => (mcp_status(config, &servers, base_dir, global), None),
+        Err(e) => (vec![], Some(e.to_string())),
+    };
+
+    let mappings = config
+        .mappings
+        .iter()
+        .map(|mapping| mapping_status(config, mapping, base_dir))
+        .collect();
+
+    let committed_links = if config.git.ignore_links && !global {
+        git_tracked_paths(base_dir, &collect_managed_links(config, base_dir, global))
+    } else {
+        vec![]
+    };
+
     StatusOk {
+        profile: config.active_profile.clone(),
         skills,
         instructions: InstructionStatusEntry {
             source: config.source_instruction_path(global).to_string(),
             source_exists,
             agents: instruction_agents,
         },
+        files,
+        subagents,
+        commands,
+        mcp,
+        mcp_error,
+        mappings,
+        ignored,
+        entries,
+        committed_links,
+    }
+}
+
+/// A row per definition, with each target's rendered file checked like a generated instruction.
+fn rendered_status(
+    source_dir: &Path,
+    targets: &[(AgentName, Option<PathBuf>, &RenderTarget)],
+) -> Vec<SkillStatusEntry> {
+    let mut rows: Vec<SkillStatusEntry> = Vec::new();
+    for planned in plan_rendered_files(source_dir, targets) {
+        let state = match &planned.plan {
+            RenderPlan::Write { path, content } => check_generated_state(path, Some(content)),
+            RenderPlan::Unsupported(_) => SyncState::Unsupported,
+        };
+        let cell = AgentState {
+            agent: planned.agent,
+            state,
+        };
+        match rows.last_mut() {
+            Some(row) if row.name == planned.name => row.agents.push(cell),
+            _ => rows.push(SkillStatusEntry {
+                name: planned.name,
+                agents: vec![cell],
+            }),
+        }
+    }
+    rows
+}
+
+/// `synced` when the agent entry matches the source, `stale` when sync would rewrite or remove
+/// it, `broken` when a `${env:NAME}` it needs has no value, `conflict` when the agent config
+/// file cannot be parsed or holds a hand-written entry under the same name. Only states are
+/// reported, never resolved values.
+fn mcp_status(
+    config: &Config,
+    servers: &[McpServer],
+    base_dir: &Path,
+    global: bool,
+) -> Vec<SkillStatusEntry> {
+    let env = load_env_file(&config.resolve_env_path(base_dir, global)).unwrap_or_default();
+    let owned = McpOwnership::load(&config.resolve_mcp_state_path(base_dir)).unwrap_or_default();
+    let files: Vec<_> = config
+        .resolve_mcp_targets(base_dir, global)
+        .into_iter()
+        .map(|(agent, path, format)| (agent, format, McpConfigFile::load(&path, format).ok()))
+        .collect();
+
+    servers
+        .iter()
+        .map(|server| SkillStatusEntry {
+            name: server.name.clone(),
+            agents: files
+                .iter()
+                .map(|(agent, format, file)| {
+                    let current = file.as_ref().map(|f| f.get(&server.name));
+                    let expected = expected_mcp_entry(server, *format, &env);
+                    let owns = owned.owns(*agent, &server.name);
+                    let state = match (current, server.applies_to(*agent)) {
+                        (None, _) => SyncState::Conflict,
+                        (Some(_), true) if expected.is_err() => SyncState::Broken,
+                        (Some(None), true) => SyncState::Missing,
+                        (Some(Some(entry)), true) if expected.is_ok_and(|e| e == entry) => {
+                            SyncState::Synced
+                        }
+                        (Some(Some(_)), true) if !owns => SyncState::Conflict,
+                        (Some(Some(_)), false) if !owns => SyncState::Disabled,
+                        (Some(Some(_)), _) => SyncState::Stale,
+                        (Some(None), false) => SyncState::Disabled,
+                    };
+                    AgentState {
+                        agent: *agent,
+                        state,
+                    }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn mapping_status(config: &Config, mapping: &Mapping, base_dir: &Path) -> MappingStatusEntry {
+    let source = mapping.resolve_source(base_dir);
+    let mut dests: Vec<(AgentName, PathBuf)> = config
+        .resolve_mapping_dests(mapping, base_dir)
+        .into_iter()
+        .collect();
+    dests.sort_by_key(|(agent, _)| agent.as_str());
+
+    let row = |name: String, link_name: Option<&str>, target: &Path| SkillStatusEntry {
+        name,
+        agents: dests
+            .iter()
+            .map(|(agent, dest)| {
+                let link = link_name.map_or_else(|| dest.clone(), |n| dest.join(n));
+                AgentState {
+                    agent: *agent,
+                    state: check_link_state(&link, target),
+                }
+            })
+            .collect(),
+    };
+
+    let entries = match mapping.kind {
+        MappingKind::Dir => list_dir_entries(&source, false)
+            .into_iter()
+            .map(|(name, path)| row(name.clone(), Some(&name), &path))
+            .collect(),
+        MappingKind::File if source.exists() => vec![row(mapping.name.clone(), None, &source)],
+        MappingKind::File => vec![],
+    };
+
+    MappingStatusEntry {
+        name: mapping.name.clone(),
+        source_exists: source.exists(),
+        source,
+        entries,
+    }
+}
+
+/// Status of both levels. Agents load global and project skills together, and a project
+/// skill shadows a global one with the same name.
+pub fn run_all(
+    global: Option<(&Config, &Path)>,
+    project: Option<(&Config, &Path)>,
+) -> LayeredStatusOk {
+    let global = global.map(|(config, base_dir)| run(config, base_dir, true));
+    let project = project.map(|(config, base_dir)| run(config, base_dir, false));
+    let skills = layer_skills(
+        global.as_ref().map_or(&[], |s| &s.skills),
+        project.as_ref().map_or(&[], |s| &s.skills),
+    );
+    LayeredStatusOk {
+        global,
+        project,
+        skills,
+    }
+}
+
+fn layer_skills(
+    global: &[SkillStatusEntry],
+    project: &[SkillStatusEntry],
+) -> Vec<LayeredSkillEntry> {
+    let names: BTreeSet<&str> = global
+        .iter()
+        .chain(project)
+        .map(|entry| entry.name.as_str())
+        .collect();
+    // Built-in agents, plus custom ones defined at either level
+    let agents: BTreeSet<AgentName> = AgentName::iter()
+        .chain(
+            global
+                .iter()
+                .chain(project)
+                .flat_map(|entry| &entry.agents)
+                .map(|cell| cell.agent),
+        )
+        .collect();
+    // Only a hana-managed copy counts as loaded from that level
+    let loads = |entry: Option<&SkillStatusEntry>, agent: AgentName| {
+        entry.is_some_and(|entry| {
+            entry.agents.iter().any(|cell| {
+                cell.agent == agent && matches!(cell.state, SyncState::Synced | SyncState::Native)
+            })
+        })
+    };
+
+    names
+        .into_iter()
+        .map(|name| {
+            let in_global = global.iter().find(|entry| entry.name == name);
+            let in_project = project.iter().find(|entry| entry.name == name);
+            let levels = [
+                (SkillLevel::Global, in_global.is_some()),
+                (SkillLevel::Project, in_project.is_some()),
+            ]
+            .into_iter()
+            .filter_map(|(level, present)| present.then_some(level))
+            .collect();
+            let agents = agents
+                .iter()
+                .map(|&agent| {
+                    let level = if loads(in_project, agent) {
+                        Some(SkillLevel::Project)
+                    } else if loads(in_global, agent) {
+                        Some(SkillLevel::Global)
+                    } else {
+                        None
+                    };
+                    LoadedSkill { agent, level }
+                })
+                .collect();
+            LayeredSkillEntry {
+                name: name.to_string(),
+                levels,
+                agents,
+            }
+        })
+        .collect()
+}
+
+/// Inspect one skill: its source files and frontmatter, and every agent's link in detail.
+pub fn run_skill(
+    config: &Config,
+    base_dir: &Path,
+    global: bool,
+    name: &str,
+) -> Result<SkillDetail, StatusError> {
+    let max_parts = if config.source.namespaces { 2 } else { 1 };
+    let parts: Vec<&str> = name.split('/').collect();
+    let valid = parts.len() <= max_parts
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && *p != "." && *p != ".." && !p.contains('\\'));
+    if !valid {
+        return Err(StatusError::InvalidSkillName {
+            name: name.to_string(),
+        });
+    }
+
+    let source_dir = config.resolve_source_skills_path(base_dir, global);
+    let source_path = source_dir.join(name);
+    if !source_path.is_dir() {
+        return Err(StatusError::SkillNotFound {
+            name: name.to_string(),
+            source_dir,
+        });
     }
+
+    let mut files = Vec::new();
+    list_files(&source_path, &source_path, &mut files);
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let total_size = files.iter().map(|f| f.size).sum();
+
+    let frontmatter = fs::read_to_string(source_path.join("SKILL.md"))
+        .ok()
+        .and_then(|content| {
+            parse_frontmatter(&content).map(|(fm, _)| {
+                fm.fields
+                    .into_iter()
+                    .map(|(key, value)| (key, value.to_string()))
+                    .collect()
+            })
+        })
+        .unwrap_or_default();
+
+    let (rule, _) = resolve_skill_rule(config, &source_path, name);
+    let expected = fs::canonicalize(&source_path).ok();
+    let agents = skill_target_dirs(config, base_dir, global)
+        .into_iter()
+        .filter_map(|(agent, agent_dir)| {
+            let target_config = config.targets.get(&agent)?;
+            let link_path = agent_dir.join(config.skill_link_name(name));
+            let read_link = fs::read_link(&link_path).ok();
+            let canonical = fs::canonicalize(&link_path).ok();
+
+            let (state, reason) = if !target_config.skills {
+                (
+                    SyncState::Disabled,
+                    Some("skills are disabled for this agent".to_string()),
+                )
+            } else if agent_dir == source_dir {
+                (SyncState::Native, None)
+            } else if !config.profile_allows(name) {
+                (
+                    SyncState::Excluded,
+                    Some(format!(
+                        "not in the active profile ({})",
+                        config.active_profile.as_deref().unwrap_or_default()
+                    )),
+                )
+            } else if !rule.allows(agent) {
+                (
+                    SyncState::Excluded,
+                    Some("excluded by a per-skill rule".to_string()),
+                )
+            } else {
+                let state = check_link_state(&link_path, &source_path);
+                let reason = match state {
+                    SyncState::Synced | SyncState::Native => None,
+                    SyncState::WrongTarget => Some(format!(
+                        "resolves to {} instead of {}",
+                        canonical.as_deref().unwrap_or(&link_path).display(),
+                        expected.as_deref().unwrap_or(&source_path).display()
+                    )),
+                    SyncState::Broken => Some(format!(
+                        "link target {} does not exist",
+                        read_link.as_deref().unwrap_or(Path::new("?")).display()
+                    )),
+                    SyncState::Conflict => {
+                        Some("real directory, not a symlink (sync will collect it)".to_string())
+                    }
+                    SyncState::Missing => Some("no entry at the link path".to_string()),
+                    // Stale/Edited/Unsupported only apply to generated files
+                    SyncState::Disabled
+                    | SyncState::Excluded
+                    | SyncState::Stale
+                    | SyncState::Edited
+                    | SyncState::Unsupported => None,
+                };
+                (state, reason)
+            };
+
+            Some(SkillAgentDetail {
+                agent,
+                link_path,
+                state,
+                read_link,
+                canonical,
+                reason,
+            })
+        })
+        .collect();
+
+    Ok(SkillDetail {
+        name: name.to_string(),
+        source_path,
+        files,
+        total_size,
+        frontmatter,
+        agents,
+    })
 }
 
-fn check_skill_state(link_path: &Path, expected_target: &Path) -> SkillState {
+fn skill_target_dirs(config: &Config, base_dir: &Path, global: bool) -> Vec<(AgentName, PathBuf)> {
+    config
+        .agent_names()
+        .filter_map(|agent| {
+            let target_dir = config.resolve_target_skills_path(agent, base_dir, global)?;
+            Some((agent, target_dir))
+        })
+        .collect()
+}
+
+fn list_files(root: &Path, dir: &Path, out: &mut Vec<SkillFile>) {
+    for entry in fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if path.is_dir() && !path.is_symlink() {
+            list_files(root, &path, out);
+        } else {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let rel = path.strip_prefix(root).unwrap_or(&path);
+            out.push(SkillFile {
+                path: rel.to_string_lossy().to_string(),
+                size,
+            });
+        }
+    }
+}
+
+fn ignored_entries(dir: &Path, ignore: &IgnoreRules) -> Vec<String> {
+    if ignore.is_empty() {
+        return vec![];
+    }
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir() && !e.path().is_symlink())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| ignore.is_ignored(name, true))
+        .collect();
+    names.sort();
+    names
+}
+
+/// State of a generated instruction file against what sync would write now.
+fn check_generated_state(path: &Path, expected: Option<&str>) -> SyncState {
+    if path.is_symlink() {
+        // Left over from link mode; sync replaces it
+        return SyncState::Stale;
+    }
+    let Ok(content) = fs::read_to_string(path) else {
+        return SyncState::Missing;
+    };
+    match parse_generated(&content) {
+        None => SyncState::Conflict,
+        Some(generated) if generated.is_edited() => SyncState::Edited,
+        Some(_) if Some(content.as_str()) == expected => SyncState::Synced,
+        Some(_) => SyncState::Stale,
+    }
+}
+
+fn check_link_state(link_path: &Path, expected_target: &Path) -> SyncState {
     if link_path.is_symlink() {
         if !link_path.exists() {
-            SkillState::BrokenSymlink
+            SyncState::Broken
         } else {
             match (
                 fs::canonicalize(link_path),
                 fs::canonicalize(expected_target),
             ) {
-                (Ok(a), Ok(b)) if a == b => SkillState::Synced,
-                (Ok(_), Ok(_)) => SkillState::WrongTarget,
-                _ => SkillState::BrokenSymlink,
+                (Ok(a), Ok(b)) if a == b => SyncState::Synced,
+                (Ok(_), Ok(_)) => SyncState::WrongTarget,
+                _ => SyncState::Broken,
             }
         }
-    } else if link_path.is_dir() {
-        SkillState::RealDir
+    } else if link_path.exists() {
+        SyncState::Conflict
     } else {
-        SkillState::Missing
+        SyncState::Missing
     }
 }
 
@@ -174,6 +903,10 @@ mod tests {
         Config::default()
     }
 
+    fn cell(agent: AgentName, state: SyncState) -> AgentState {
+        AgentState { agent, state }
+    }
+
     fn setup_source(tmp: &Path) {
         let skills = tmp.join(".agents/skills");
         fs::create_dir_all(skills.join("my-skill")).unwrap();
@@ -193,8 +926,13 @@ mod tests {
 
         assert_eq!(result.skills.len(), 1);
         assert_eq!(result.skills[0].name, "my-skill");
-        for (_, state) in &result.skills[0].agents {
-            assert_eq!(*state, SkillState::Synced);
+        for &AgentState { agent, state } in &result.skills[0].agents {
+            let expected = if agent == AgentName::Codex {
+                SyncState::Native
+            } else {
+                SyncState::Synced
+            };
+            assert_eq!(state, expected, "agent: {agent}");
         }
     }
 
@@ -207,8 +945,13 @@ mod tests {
         let result = run(&config, tmp.path(), false);
 
         assert_eq!(result.skills.len(), 1);
-        for (agent, state) in &result.skills[0].agents {
-            assert_eq!(*state, SkillState::Missing, "agent: {agent}");
+        for &AgentState { agent, state } in &result.skills[0].agents {
+            let expected = if agent == AgentName::Codex {
+                SyncState::Native
+            } else {
+                SyncState::Missing
+            };
+            assert_eq!(state, expected, "agent: {agent}");
         }
     }
 
@@ -225,10 +968,10 @@ mod tests {
         let claude_state = result.skills[0]
             .agents
             .iter()
-            .find(|(a, _)| a == "claude")
-            .map(|(_, s)| s)
+            .find(|cell| cell.agent == AgentName::Claude)
+            .map(|cell| cell.state)
             .unwrap();
-        assert_eq!(*claude_state, SkillState::RealDir);
+        assert_eq!(claude_state, SyncState::Conflict);
     }
 
     #[test]
@@ -246,10 +989,158 @@ mod tests {
         let claude_state = result.skills[0]
             .agents
             .iter()
-            .find(|(a, _)| a == "claude")
-            .map(|(_, s)| s)
+            .find(|cell| cell.agent == AgentName::Claude)
+            .map(|cell| cell.state)
             .unwrap();
-        assert_eq!(*claude_state, SkillState::BrokenSymlink);
+        assert_eq!(claude_state, SyncState::Broken);
+    }
+
+    #[test]
+    fn test_status_excluded_skill() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+
+        let config = Config::parse("[skill.my-skill]\ntargets = [\"claude\"]\n").unwrap();
+        crate::sync::run(&config, tmp.path(), &crate::sync::SyncOptions::default());
+        let result = run(&config, tmp.path(), false);
+
+        let states: Vec<_> = result.skills[0].agents.clone();
+        assert!(states.contains(&cell(AgentName::Claude, SyncState::Synced)));
+        assert!(states.contains(&cell(AgentName::Opencode, SyncState::Excluded)));
+    }
+
+    #[test]
+    fn test_status_lists_ignored_entries() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        fs::write(
+            tmp.path().join(".agents/.hanaignore"),
+            "node_modules/\nprivate-*\n",
+        )
+        .unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/skills/node_modules")).unwrap();
+        fs::create_dir_all(tmp.path().join(".claude/skills/private-memory")).unwrap();
+
+        let result = run(&default_config(), tmp.path(), false);
+
+        assert_eq!(result.skills.len(), 1);
+        assert_eq!(
+            result.ignored,
+            vec![
+                IgnoredEntry {
+                    location: "source".to_string(),
+                    name: "node_modules".to_string(),
+                },
+                IgnoredEntry {
+                    location: "claude".to_string(),
+                    name: "private-memory".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_status_skill_detail() {
+        let tmp = TempDir::new().unwrap();
+        let skill = tmp.path().join(".agents/skills/my-skill");
+        fs::create_dir_all(skill.join("scripts")).unwrap();
+        fs::write(
+            skill.join("SKILL.md"),
+            "---\nname: my-skill\ndescription: Helps\n---\n# Skill\n",
+        )
+        .unwrap();
+        fs::write(skill.join("scripts/run.sh"), "echo hi").unwrap();
+
+        let config = default_config();
+        crate::sync::run(&config, tmp.path(), &crate::sync::SyncOptions::default());
+        // Point opencode somewhere else
+        let opencode_link = tmp.path().join(".opencode/skills/my-skill");
+        fs::remove_file(&opencode_link).unwrap();
+        let other = tmp.path().join("elsewhere");
+        fs::create_dir_all(&other).unwrap();
+        symlink(&other, &opencode_link).unwrap();
+
+        let detail = run_skill(&config, tmp.path(), false, "my-skill").unwrap();
+
+        let paths: Vec<&str> = detail.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["SKILL.md", "scripts/run.sh"]);
+        assert_eq!(
+            detail.total_size,
+            detail.files.iter().map(|f| f.size).sum::<u64>()
+        );
+        assert!(
+            detail
+                .frontmatter
+                .contains(&("description".to_string(), "Helps".to_string()))
+        );
+
+        let claude = detail
+            .agents
+            .iter()
+            .find(|a| a.agent == AgentName::Claude)
+            .unwrap();
+        assert_eq!(claude.state, SyncState::Synced);
+        assert!(claude.read_link.as_ref().unwrap().is_relative());
+        assert!(claude.reason.is_none());
+
+        let opencode = detail
+            .agents
+            .iter()
+            .find(|a| a.agent == AgentName::Opencode)
+            .unwrap();
+        assert_eq!(opencode.state, SyncState::WrongTarget);
+        assert_eq!(opencode.read_link.as_deref(), Some(other.as_path()));
+        assert!(opencode.reason.as_ref().unwrap().contains("elsewhere"));
+    }
+
+    #[test]
+    fn test_status_skill_detail_not_found() {
+        let tmp = TempDir::new().unwrap();
+        let result = run_skill(&default_config(), tmp.path(), false, "nope");
+        assert!(matches!(result, Err(StatusError::SkillNotFound { .. })));
+    }
+
+    #[test]
+    fn test_status_skill_detail_rejects_paths() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/skills")).unwrap();
+        for name in ["..", "../outside", "a/b", "/etc", "", "a\\b"] {
+            let result = run_skill(&default_config(), tmp.path(), false, name);
+            assert!(
+                matches!(result, Err(StatusError::InvalidSkillName { .. })),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_status_classifies_agent_dir_entries() {
+        use crate::helper::classify_target_entries::TargetEntryKind;
+
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let config = default_config();
+        crate::sync::run(&config, tmp.path(), &crate::sync::SyncOptions::default());
+
+        let plugin = tmp.path().join("plugins/lint");
+        fs::create_dir_all(&plugin).unwrap();
+        symlink(&plugin, tmp.path().join(".claude/skills/lint")).unwrap();
+
+        let result = run(&config, tmp.path(), false);
+
+        let claude: Vec<(&str, TargetEntryKind)> = result
+            .entries
+            .iter()
+            .filter(|e| e.agent == AgentName::Claude)
+            .map(|e| (e.name.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            claude,
+            vec![
+                ("lint", TargetEntryKind::ForeignSymlink),
+                ("my-skill", TargetEntryKind::Managed),
+            ]
+        );
     }
 
     #[test]
@@ -266,17 +1157,17 @@ mod tests {
             .instructions
             .agents
             .iter()
-            .find(|(a, _)| a == "claude")
+            .find(|cell| cell.agent == AgentName::Claude)
             .unwrap();
-        assert_eq!(claude.1, InstructionState::Synced);
+        assert_eq!(claude.state, SyncState::Synced);
 
         let codex = result
             .instructions
             .agents
             .iter()
-            .find(|(a, _)| a == "codex")
+            .find(|cell| cell.agent == AgentName::Codex)
             .unwrap();
-        assert_eq!(codex.1, InstructionState::DirectRead);
+        assert_eq!(codex.state, SyncState::Native);
     }
 
     #[test]
@@ -291,9 +1182,9 @@ mod tests {
             .instructions
             .agents
             .iter()
-            .find(|(a, _)| a == "claude")
+            .find(|cell| cell.agent == AgentName::Claude)
             .unwrap();
-        assert_eq!(claude.1, InstructionState::Missing);
+        assert_eq!(claude.state, SyncState::Missing);
     }
 
     #[test]
@@ -309,9 +1200,9 @@ mod tests {
             .instructions
             .agents
             .iter()
-            .find(|(a, _)| a == "claude")
+            .find(|cell| cell.agent == AgentName::Claude)
             .unwrap();
-        assert_eq!(claude.1, InstructionState::RealFile);
+        assert_eq!(claude.state, SyncState::Conflict);
     }
 
     #[test]
@@ -320,7 +1211,11 @@ mod tests {
         setup_source(tmp.path());
 
         let mut config = default_config();
-        config.targets.get_mut("claude").unwrap().instructions = false;
+        config
+            .targets
+            .get_mut(&AgentName::Claude)
+            .unwrap()
+            .instructions = false;
 
         let result = run(&config, tmp.path(), false);
 
@@ -328,9 +1223,327 @@ mod tests {
             .instructions
             .agents
             .iter()
-            .find(|(a, _)| a == "claude")
+            .find(|cell| cell.agent == AgentName::Claude)
             .unwrap();
-        assert_eq!(claude.1, InstructionState::Disabled);
+        assert_eq!(claude.state, SyncState::Disabled);
+    }
+
+    #[test]
+    fn test_status_instruction_wrong_target_and_broken() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        fs::write(tmp.path().join("OTHER.md"), "other").unwrap();
+        let link = tmp.path().join("CLAUDE.md");
+        symlink("OTHER.md", &link).unwrap();
+
+        let config = default_config();
+        let claude_state = |result: &StatusOk| {
+            result
+                .instructions
+                .agents
+                .iter()
+                .find(|cell| cell.agent == AgentName::Claude)
+                .map(|cell| cell.state)
+                .unwrap()
+        };
+        let result = run(&config, tmp.path(), false);
+        assert_eq!(claude_state(&result), SyncState::WrongTarget);
+
+        fs::remove_file(&link).unwrap();
+        symlink("missing.md", &link).unwrap();
+        let result = run(&config, tmp.path(), false);
+        assert_eq!(claude_state(&result), SyncState::Broken);
+    }
+
+    #[test]
+    fn test_status_skill_disabled_is_not_missing() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+
+        let mut config = default_config();
+        config.targets.get_mut(&AgentName::Claude).unwrap().skills = false;
+        let result = run(&config, tmp.path(), false);
+
+        let claude = result.skills[0]
+            .agents
+            .iter()
+            .find(|cell| cell.agent == AgentName::Claude)
+            .unwrap();
+        assert_eq!(claude.state, SyncState::Disabled);
+    }
+
+    #[test]
+    fn test_status_problem_count() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let config = default_config();
+
+        assert!(run(&config, tmp.path(), false).problem_count() > 0);
+        crate::sync::run(&config, tmp.path(), &crate::sync::SyncOptions::default());
+        assert_eq!(run(&config, tmp.path(), false).problem_count(), 0);
+    }
+
+    #[test]
+    fn test_status_reports_committed_links() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .status()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        crate::sync::run(
+            &default_config(),
+            tmp.path(),
+            &crate::sync::SyncOptions::default(),
+        );
+        git(&["add", "CLAUDE.md"]);
+
+        let config = Config::parse("[git]\nignore_links = true\n").unwrap();
+        let result = run(&config, tmp.path(), false);
+        assert_eq!(result.committed_links, vec![tmp.path().join("CLAUDE.md")]);
+
+        let result = run(&default_config(), tmp.path(), false);
+        assert!(result.committed_links.is_empty());
+    }
+
+    #[test]
+    fn test_status_generated_instructions_drift() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let config = Config::parse("[source]\ninstruction_mode = \"generate\"\n").unwrap();
+        let claude_state = |config: &Config| {
+            run(config, tmp.path(), false)
+                .instructions
+                .agents
+                .into_iter()
+                .find(|cell| cell.agent == AgentName::Claude)
+                .map(|cell| cell.state)
+                .unwrap()
+        };
+
+        assert_eq!(claude_state(&config), SyncState::Missing);
+        crate::sync::run(&config, tmp.path(), &crate::sync::SyncOptions::default());
+        assert_eq!(claude_state(&config), SyncState::Synced);
+
+        fs::write(tmp.path().join("AGENTS.md"), "# Instructions v2").unwrap();
+        assert_eq!(claude_state(&config), SyncState::Stale);
+
+        crate::sync::run(&config, tmp.path(), &crate::sync::SyncOptions::default());
+        let claude_md = tmp.path().join("CLAUDE.md");
+        let content = fs::read_to_string(&claude_md).unwrap();
+        fs::write(&claude_md, format!("{content}extra\n")).unwrap();
+        assert_eq!(claude_state(&config), SyncState::Edited);
+    }
+
+    #[test]
+    fn test_status_target_files() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(tmp.path().join(".agents/SYSTEM.md"), "# System").unwrap();
+        let config = Config::parse(
+            "[target.pi]\nfiles = [{ source = \".agents/SYSTEM.md\", dest = \"SYSTEM.md\" }]\n",
+        )
+        .unwrap();
+
+        let before = run(&config, tmp.path(), false);
+        assert_eq!(before.files.len(), 1);
+        assert_eq!(before.files[0].state, SyncState::Missing);
+        assert_eq!(before.files[0].dest, "SYSTEM.md");
+
+        crate::sync::run(&config, tmp.path(), &Default::default());
+        let after = run(&config, tmp.path(), false);
+        assert_eq!(after.files[0].state, SyncState::Synced);
+    }
+
+    #[test]
+    fn test_status_subagents_report_drift() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/agents")).unwrap();
+        let source = tmp.path().join(".agents/agents/reviewer.md");
+        fs::write(&source, "---\nname: reviewer\n---\nReview.\n").unwrap();
+        let config = Config::parse("[source]\nsubagents = true\n").unwrap();
+
+        crate::sync::run(&config, tmp.path(), &Default::default());
+        let synced = run(&config, tmp.path(), false);
+        assert_eq!(synced.subagents[0].name, "reviewer.md");
+        assert_eq!(
+            synced.subagents[0].agents,
+            vec![
+                cell(AgentName::Claude, SyncState::Synced),
+                cell(AgentName::Opencode, SyncState::Synced),
+            ]
+        );
+
+        fs::write(&source, "---\nname: reviewer\n---\nReview harder.\n").unwrap();
+        let claude = tmp.path().join(".claude/agents/reviewer.md");
+        let edited = fs::read_to_string(&claude)
+            .unwrap()
+            .replace("Review.", "Mine.");
+        fs::write(&claude, edited).unwrap();
+        let drifted = run(&config, tmp.path(), false);
+        assert_eq!(
+            drifted.subagents[0].agents,
+            vec![
+                cell(AgentName::Claude, SyncState::Edited),
+                cell(AgentName::Opencode, SyncState::Stale),
+            ]
+        );
+        assert_eq!(drifted.problem_count(), synced.problem_count() + 2);
+    }
+
+    #[test]
+    fn test_status_commands_mark_unsupported() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/commands")).unwrap();
+        fs::write(
+            tmp.path().join(".agents/commands/review.md"),
+            "---\ndescription: Review\n---\nGo.\n",
+        )
+        .unwrap();
+        let config = Config::parse("[source]\ncommands = true\n").unwrap();
+
+        crate::sync::run(&config, tmp.path(), &Default::default());
+        let result = run(&config, tmp.path(), false);
+        assert_eq!(
+            result.commands[0].agents,
+            vec![
+                cell(AgentName::Claude, SyncState::Synced),
+                cell(AgentName::Codex, SyncState::Unsupported),
+                cell(AgentName::Pi, SyncState::Synced),
+                cell(AgentName::Opencode, SyncState::Synced),
+            ]
+        );
+        assert!(!SyncState::Unsupported.is_problem());
+    }
+
+    #[test]
+    fn test_status_mcp_rows_per_server() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(
+            tmp.path().join(".agents/mcp.toml"),
+            "[servers.github]\ncommand = \"npx\"\n",
+        )
+        .unwrap();
+        let config = default_config();
+
+        let before = run(&config, tmp.path(), false);
+        assert_eq!(before.mcp[0].name, "github");
+        assert!(
+            before.mcp[0]
+                .agents
+                .iter()
+                .all(|cell| cell.state == SyncState::Missing)
+        );
+
+        crate::sync::run(&config, tmp.path(), &Default::default());
+        fs::write(
+            tmp.path().join("opencode.json"),
+            "{\"mcp\": {\"github\": {\"type\": \"local\", \"command\": [\"old\"]}}}",
+        )
+        .unwrap();
+        let after = run(&config, tmp.path(), false);
+        assert_eq!(
+            after.mcp[0].agents,
+            vec![
+                cell(AgentName::Claude, SyncState::Synced),
+                cell(AgentName::Codex, SyncState::Synced),
+                cell(AgentName::Opencode, SyncState::Stale),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_status_mcp_never_exposes_env_values() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(
+            tmp.path().join(".agents/mcp.toml"),
+            "[servers.api]\nurl = \"https://api\"\nheaders = { Authorization = \"Bearer ${env:HANA_STATUS_KEY}\" }\n",
+        )
+        .unwrap();
+        let config = default_config();
+
+        let unresolved = run(&config, tmp.path(), false);
+        assert!(
+            unresolved.mcp[0]
+                .agents
+                .contains(&cell(AgentName::Codex, SyncState::Broken))
+        );
+
+        fs::write(tmp.path().join(".agents/.env"), "HANA_STATUS_KEY=s3cret\n").unwrap();
+        crate::sync::run(&config, tmp.path(), &Default::default());
+        let result = run(&config, tmp.path(), false);
+        assert!(
+            result.mcp[0]
+                .agents
+                .iter()
+                .all(|cell| cell.state == SyncState::Synced)
+        );
+        assert!(!format!("{result:?}").contains("s3cret"));
+    }
+
+    #[test]
+    fn test_status_mcp_reports_invalid_source_and_foreign_entries() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(tmp.path().join(".agents/mcp.toml"), "[servers.github\n").unwrap();
+        let config = default_config();
+
+        let invalid = run(&config, tmp.path(), false);
+        assert!(invalid.mcp.is_empty());
+        assert!(invalid.mcp_error.is_some());
+        let empty = TempDir::new().unwrap();
+        let baseline = run(&config, empty.path(), false).problem_count();
+        assert_eq!(invalid.problem_count(), baseline + 1);
+
+        fs::write(
+            tmp.path().join(".agents/mcp.toml"),
+            "[servers.github]\ncommand = \"npx\"\n",
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join(".mcp.json"),
+            "{\"mcpServers\": {\"github\": {\"command\": \"mine\"}}}",
+        )
+        .unwrap();
+        let result = run(&config, tmp.path(), false);
+        assert!(result.mcp_error.is_none());
+        assert!(
+            result.mcp[0]
+                .agents
+                .contains(&cell(AgentName::Claude, SyncState::Conflict))
+        );
+    }
+
+    #[test]
+    fn test_status_mappings() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/agents")).unwrap();
+        fs::write(tmp.path().join(".agents/agents/reviewer.md"), "# Reviewer").unwrap();
+        let config = Config::parse(
+            "[[mapping]]\nname = \"personas\"\nsource = \".agents/agents\"\ndest = { claude = \".claude/agents\" }\n",
+        )
+        .unwrap();
+
+        let before = run(&config, tmp.path(), false);
+        assert_eq!(before.mappings[0].entries[0].name, "reviewer.md");
+        assert_eq!(
+            before.mappings[0].entries[0].agents,
+            vec![cell(AgentName::Claude, SyncState::Missing)]
+        );
+
+        crate::sync::run(&config, tmp.path(), &Default::default());
+        let after = run(&config, tmp.path(), false);
+        assert_eq!(
+            after.mappings[0].entries[0].agents,
+            vec![cell(AgentName::Claude, SyncState::Synced)]
+        );
+        assert_eq!(before.problem_count(), after.problem_count() + 1);
     }
 
     #[test]
@@ -344,33 +1557,93 @@ mod tests {
         assert!(!result.instructions.source_exists);
     }
 
+    #[test]
+    fn test_layer_skills_project_shadows_global() {
+        let entry = |name: &str, agents: Vec<AgentState>| SkillStatusEntry {
+            name: name.to_string(),
+            agents,
+        };
+        let global = vec![
+            entry(
+                "review",
+                vec![
+                    cell(AgentName::Claude, SyncState::Synced),
+                    cell(AgentName::Pi, SyncState::Synced),
+                ],
+            ),
+            entry("notes", vec![cell(AgentName::Claude, SyncState::Missing)]),
+        ];
+        let project = vec![entry(
+            "review",
+            vec![
+                cell(AgentName::Claude, SyncState::Synced),
+                cell(AgentName::Pi, SyncState::Excluded),
+            ],
+        )];
+
+        let layered = layer_skills(&global, &project);
+
+        assert_eq!(
+            layered.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["notes", "review"]
+        );
+        assert_eq!(layered[0].levels, vec![SkillLevel::Global]);
+        assert!(
+            layered[0]
+                .agents
+                .iter()
+                .all(|loaded| loaded.level.is_none())
+        );
+        let review = &layered[1];
+        assert_eq!(review.levels, vec![SkillLevel::Global, SkillLevel::Project]);
+        let loaded = |agent| {
+            review
+                .agents
+                .iter()
+                .find(|loaded| loaded.agent == agent)
+                .unwrap()
+                .level
+        };
+        assert_eq!(loaded(AgentName::Claude), Some(SkillLevel::Project));
+        // Excluded from the project copy, so pi still loads the global one
+        assert_eq!(loaded(AgentName::Pi), Some(SkillLevel::Global));
+        assert_eq!(loaded(AgentName::Codex), None);
+    }
+
     #[test]
     fn test_status_data_structure() {
         let result = StatusOk {
+            profile: None,
             skills: vec![SkillStatusEntry {
                 name: "my-skill".to_string(),
                 agents: vec![
-                    ("claude".to_string(), SkillState::Synced),
-                    ("pi".to_string(), SkillState::Missing),
+                    cell(AgentName::Claude, SyncState::Synced),
+                    cell(AgentName::Pi, SyncState::Missing),
                 ],
             }],
             instructions: InstructionStatusEntry {
                 source: "AGENTS.md".to_string(),
                 source_exists: true,
                 agents: vec![
-                    ("claude".to_string(), InstructionState::Synced),
-                    ("codex".to_string(), InstructionState::DirectRead),
+                    cell(AgentName::Claude, SyncState::Synced),
+                    cell(AgentName::Codex, SyncState::Native),
                 ],
             },
+            files: vec![],
+            subagents: vec![],
+            commands: vec![],
+            mcp: vec![],
+            mcp_error: None,
+            mappings: vec![],
+            ignored: vec![],
+            entries: vec![],
+            committed_links: vec![],
         };
 
-        assert_eq!(result.skills[0].agents[0].1, SkillState::Synced);
-        assert_eq!(result.skills[0].agents[1].1, SkillState::Missing);
+        assert_eq!(result.skills[0].agents[0].state, SyncState::Synced);
+        assert_eq!(result.skills[0].agents[1].state, SyncState::Missing);
         assert!(result.instructions.source_exists);
-        assert_eq!(result.instructions.agents[0].1, InstructionState::Synced);
-        assert_eq!(
-            result.instructions.agents[1].1,
-            InstructionState::DirectRead
-        );
+        assert_eq!(result.instructions.agents[0].state, SyncState::Synced);
+        assert_eq!(result.instructions.agents[1].state, SyncState::Native);
     }
 }
@@ -1,49 +1,228 @@
+//! `hana sync`: collect from agents, then link and render everything from the source.
+
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::config::{AgentName, Config, TargetFeature};
+use crate::config::{
+    AgentName, CollectPolicy, Config, Mapping, MappingKind, McpServer, RenderTarget, TargetFeature,
+};
 use crate::helper::broadcast_target_symlink::{LinkOutcome, broadcast_target_symlink, link_one};
+use crate::helper::classify_target_entries::{TargetEntryKind, classify_target_entries};
+use crate::helper::collect_managed_links::collect_managed_links;
 use crate::helper::collect_source_skills::collect_source_skills;
 use crate::helper::collect_target_skills::collect_target_skills;
+use crate::helper::expand_env_refs::EnvSyntax;
+use crate::helper::find_divergent_instructions::find_divergent_instructions;
+use crate::helper::generate_instruction_file::{
+    GenerateOutcome, expected_generated_file, generate_instruction_file, wants_generated,
+};
+use crate::helper::git_tracked_paths::git_tracked_paths;
+use crate::helper::link_points_into::link_points_into;
+use crate::helper::link_target::link_target;
+use crate::helper::list_dir_entries::list_dir_entries;
+use crate::helper::load_env_file::load_env_file;
+use crate::helper::load_ignore_rules::load_ignore_rules;
+use crate::helper::mcp_config_file::{McpConfigFile, expected_mcp_entry};
+use crate::helper::mcp_ownership::McpOwnership;
 use crate::helper::move_target_skills::move_target_skills;
-use crate::helper::relative_path::relative_path;
+use crate::helper::plan_rendered_files::{RenderPlan, plan_rendered_files};
+use crate::helper::render_instructions::parse_generated;
+use crate::helper::resolve_managed_files::resolve_managed_files;
+use crate::helper::resolve_skill_rule::resolve_skill_rule;
 use crate::helper::resolve_target_destinations::resolve_target_destinations;
+use crate::helper::update_gitignore_block::update_gitignore_block;
 
 // --- Options ---
 
-#[derive(Debug, Default)]
+/// Start from `SyncOptions::default()` and set the fields you need.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
 pub struct SyncOptions {
     pub dry_run: bool,
     pub force: bool,
     pub global: bool,
+    /// Remove symlinks in agent skill directories that point outside the source
+    pub prune_foreign: bool,
 }
 
 // --- Ok ---
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct SyncOk {
-    pub skills_linked: Vec<(String, String)>,
-    pub skills_collected: Vec<(String, String)>,
-    pub instructions_collected: Option<(String, String)>,
-    pub instructions_linked: Vec<String>,
-    pub instructions_skipped: Vec<String>,
+    pub skills_linked: Vec<LinkedSkill>,
+    pub skills_collected: Vec<CollectedSkill>,
+    /// Links hana removed because the skill no longer applies to the agent
+    pub skills_unlinked: Vec<LinkedSkill>,
+    pub instructions_collected: Option<CollectedInstructions>,
+    pub instructions_linked: Vec<AgentName>,
+    pub instructions_generated: Vec<AgentName>,
+    /// Agents that read the source instruction file directly
+    pub instructions_skipped: Vec<AgentName>,
+    /// File name of the source instruction file (`AGENTS.md` unless configured otherwise)
+    pub instructions_source: String,
+    /// `[target.x] files` linked, named by dest
+    pub files_linked: Vec<AgentEntry>,
+    /// `[target.x] files` moved into their missing source, named by dest
+    pub files_collected: Vec<AgentEntry>,
+    /// Subagent definitions rendered, named by file
+    pub subagents_generated: Vec<AgentEntry>,
+    /// Generated subagent files removed because their source is gone
+    pub subagents_removed: Vec<AgentEntry>,
+    /// Subagents an agent cannot take
+    pub subagents_unsupported: Vec<UnsupportedEntry>,
+    /// Slash commands rendered, named by file
+    pub commands_generated: Vec<AgentEntry>,
+    /// Generated command files removed
+    pub commands_removed: Vec<AgentEntry>,
+    /// Commands an agent cannot take
+    pub commands_unsupported: Vec<UnsupportedEntry>,
+    /// MCP servers written into an agent config file, named by server
+    pub mcp_updated: Vec<AgentEntry>,
+    /// MCP servers removed from an agent config file (disabled or limited to other agents)
+    pub mcp_removed: Vec<AgentEntry>,
+    /// `[[mapping]]` entries linked, named `<mapping>/<entry>` or `<mapping>`
+    pub mappings_linked: Vec<AgentEntry>,
+    /// `[[mapping]]` entries moved into the mapping source, same naming
+    pub mappings_collected: Vec<AgentEntry>,
     pub cleaned: Vec<PathBuf>,
+    pub pruned: Vec<PathBuf>,
+    /// hana's `.gitignore` block was rewritten (`[git] ignore_links`)
+    pub gitignore_updated: bool,
     pub warnings: Vec<SyncWarning>,
 }
 
+/// A skill symlink hana created in (or removed from) an agent's skills directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LinkedSkill {
+    pub skill: String,
+    pub agent: AgentName,
+    /// The link inside the agent's skills directory
+    pub path: PathBuf,
+}
+
+/// A real skill directory moved from an agent into the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CollectedSkill {
+    pub skill: String,
+    pub agent: AgentName,
+    /// Where the skill lives in the source now
+    pub path: PathBuf,
+}
+
+/// An agent instruction file moved into the missing source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CollectedInstructions {
+    /// The agent's instruction path as configured, e.g. `CLAUDE.md`
+    pub file: String,
+    pub agent: AgentName,
+}
+
+/// A file, rendered definition, MCP server or mapping entry hana changed for one agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AgentEntry {
+    pub name: String,
+    pub agent: AgentName,
+}
+
+/// A subagent or command definition an agent cannot take.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnsupportedEntry {
+    pub name: String,
+    pub agent: AgentName,
+    pub reason: String,
+}
+
+fn entry(name: impl Into<String>, agent: AgentName) -> AgentEntry {
+    AgentEntry {
+        name: name.into(),
+        agent,
+    }
+}
+
+fn join_agents(agents: &[AgentName]) -> String {
+    agents
+        .iter()
+        .map(|agent| agent.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 // --- Warning ---
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum SyncWarning {
     /// Skill name conflict: same name found in multiple agents
-    SkillConflict { name: String, agents: Vec<String> },
+    SkillConflict {
+        name: String,
+        agents: Vec<AgentName>,
+    },
     /// Source already has a skill with the same name (use --force to overwrite)
-    SourceSkillConflict { skill: String, agent: String },
+    SourceSkillConflict { skill: String, agent: AgentName },
     /// Existing file/directory conflict (--force required)
-    FileConflict { skill: String, agent: String },
+    FileConflict { skill: String, agent: AgentName },
     /// Instruction file conflict (--force required)
     InstructionConflict { file: String },
+    /// `[target.x] files` destination is a real file (--force required)
+    TargetFileConflict { file: String },
+    /// A hand-written file sits where a rendered subagent or command should go (--force required)
+    RenderedConflict {
+        kind: &'static str,
+        name: String,
+        agent: AgentName,
+    },
+    /// `.agents/mcp.toml` could not be read or parsed
+    McpSourceInvalid { detail: String },
+    /// `${env:NAME}` must be written out for this agent but has no value; the server is skipped
+    EnvUnresolved {
+        server: String,
+        agent: AgentName,
+        name: String,
+    },
+    /// An agent config file could not be parsed, so its MCP servers were left alone
+    McpConfigInvalid { file: String, detail: String },
+    /// An agent config file has a hand-written server named like a source server (--force
+    /// required)
+    McpServerConflict {
+        server: String,
+        agent: AgentName,
+        file: String,
+    },
+    /// The same mapping entry was found as a real file in several agents, or differs between them
+    MappingConflict {
+        mapping: String,
+        entry: String,
+        agents: Vec<AgentName>,
+    },
+    /// A real file/directory sits where a mapping link should go (--force required)
+    MappingEntryConflict {
+        mapping: String,
+        entry: String,
+        agent: AgentName,
+    },
+    /// Several real instruction files differ and there is no source to collect them into
+    InstructionsDiverged { files: Vec<String> },
+    /// Generated instruction file was edited by hand (--force required)
+    GeneratedEdited { file: String },
+    /// `<!-- hana:include -->` target could not be read
+    IncludeMissing { file: String, include: String },
+    /// The server needs `${env:NAME}` values written out, but the agent config file is tracked
+    /// by git; the server is skipped
+    SecretInTrackedFile {
+        server: String,
+        agent: AgentName,
+        file: String,
+    },
+    /// The `hana:` block in a skill's SKILL.md names an agent hana.toml does not know; the
+    /// name is ignored
+    UnknownSkillAgent { skill: String, agent: String },
     /// Filesystem operation failed
     IoFailed { operation: String, detail: String },
 }
@@ -55,7 +234,7 @@ impl std::fmt::Display for SyncWarning {
                 write!(
                     f,
                     "skill name conflict: '{name}' found in {}",
-                    agents.join(", ")
+                    join_agents(agents)
                 )
             }
             Self::SourceSkillConflict { skill, agent } => {
@@ -71,11 +250,105 @@ impl std::fmt::Display for SyncWarning {
                 )
             }
             Self::InstructionConflict { file } => {
+                write!(
+                    f,
+                    "{file} already exists (not a symlink). Run `hana instructions diff` to compare, or use --force to overwrite."
+                )
+            }
+            Self::TargetFileConflict { file } => {
                 write!(
                     f,
                     "{file} already exists (not a symlink). Use --force to overwrite."
                 )
             }
+            Self::RenderedConflict { kind, name, agent } => {
+                write!(
+                    f,
+                    "{kind} {name} ({agent}) is a hand-written file. Use --force to overwrite."
+                )
+            }
+            Self::McpSourceInvalid { detail } => {
+                write!(f, "MCP servers not synced: {detail}")
+            }
+            Self::EnvUnresolved {
+                server,
+                agent,
+                name,
+            } => {
+                write!(
+                    f,
+                    "MCP server {server} not written for {agent}: {name} is not set in .agents/.env or the environment"
+                )
+            }
+            Self::McpConfigInvalid { file, detail } => {
+                write!(
+                    f,
+                    "{file} is not valid ({detail}); MCP servers left unchanged"
+                )
+            }
+            Self::McpServerConflict {
+                server,
+                agent,
+                file,
+            } => {
+                write!(
+                    f,
+                    "{file} already has a hand-written MCP server {server} ({agent}). Use --force to overwrite."
+                )
+            }
+            Self::MappingConflict {
+                mapping,
+                entry,
+                agents,
+            } => {
+                write!(
+                    f,
+                    "{mapping}: '{entry}' differs between {}; nothing collected",
+                    join_agents(agents)
+                )
+            }
+            Self::MappingEntryConflict {
+                mapping,
+                entry,
+                agent,
+            } => {
+                write!(
+                    f,
+                    "{mapping}: {entry} ({agent}) is a real file/directory. Use --force to overwrite."
+                )
+            }
+            Self::InstructionsDiverged { files } => {
+                write!(
+                    f,
+                    "instruction files have diverged ({}); nothing collected. Run `hana instructions diff`, then `hana instructions resolve`.",
+                    files.join(", ")
+                )
+            }
+            Self::GeneratedEdited { file } => {
+                write!(
+                    f,
+                    "{file} was edited by hand since hana generated it. Move the edits into the source or its fragment, or use --force to overwrite."
+                )
+            }
+            Self::IncludeMissing { file, include } => {
+                write!(f, "{file}: include not found ({include}), left as-is")
+            }
+            Self::SecretInTrackedFile {
+                server,
+                agent,
+                file,
+            } => {
+                write!(
+                    f,
+                    "MCP server {server} not written for {agent}: it would put .env values into {file}, which git tracks. Untrack the file (`git rm --cached {file}`) first."
+                )
+            }
+            Self::UnknownSkillAgent { skill, agent } => {
+                write!(
+                    f,
+                    "{skill}: SKILL.md names unknown agent \"{agent}\" under `hana:`, ignored"
+                )
+            }
             Self::IoFailed { operation, detail } => {
                 write!(f, "{operation}: {detail}")
             }
@@ -86,45 +359,257 @@ impl std::fmt::Display for SyncWarning {
 // --- pub fn run ---
 
 pub fn run(config: &Config, base_dir: &Path, opts: &SyncOptions) -> SyncOk {
-    let skills = sync_skills(config, base_dir, opts);
+    let skills = sync_skills(config, base_dir, opts, None);
     let instructions = sync_instructions(config, base_dir, opts);
+    let files = sync_files(config, base_dir, opts);
+    let subagents = sync_rendered(
+        "subagent",
+        &config.resolve_source_subagents_path(base_dir, opts.global),
+        &config.resolve_subagent_targets(base_dir, opts.global),
+        &config.resolve_subagent_prune_dirs(base_dir, opts.global),
+        base_dir,
+        opts,
+    );
+    let commands = sync_rendered(
+        "command",
+        &config.resolve_source_commands_path(base_dir, opts.global),
+        &config.resolve_command_targets(base_dir, opts.global),
+        &config.resolve_command_prune_dirs(base_dir, opts.global),
+        base_dir,
+        opts,
+    );
+    let mcp = sync_mcp(config, base_dir, opts);
+    let mappings = sync_mappings(config, base_dir, opts);
+    let (gitignore_updated, gitignore_warning) = sync_gitignore(config, base_dir, opts);
 
     SyncOk {
         skills_linked: skills.linked,
         skills_collected: skills.collected,
+        skills_unlinked: skills.unlinked,
         instructions_collected: instructions.collected,
         instructions_linked: instructions.linked,
+        instructions_generated: instructions.generated,
         instructions_skipped: instructions.skipped,
-        cleaned: skills.cleaned,
+        instructions_source: instruction_source_name(config, base_dir, opts.global),
+        files_linked: files.linked,
+        files_collected: files.collected,
+        subagents_generated: subagents.generated,
+        subagents_removed: subagents.removed,
+        subagents_unsupported: subagents.unsupported,
+        commands_generated: commands.generated,
+        commands_removed: commands.removed,
+        commands_unsupported: commands.unsupported,
+        mcp_updated: mcp.updated,
+        mcp_removed: mcp.removed,
+        mappings_linked: mappings.linked,
+        mappings_collected: mappings.collected,
+        cleaned: skills.cleaned.into_iter().chain(mappings.cleaned).collect(),
+        pruned: skills.pruned,
+        gitignore_updated,
         warnings: skills
             .warnings
             .into_iter()
             .chain(instructions.warnings)
+            .chain(files.warnings)
+            .chain(subagents.warnings)
+            .chain(commands.warnings)
+            .chain(mcp.warnings)
+            .chain(mappings.warnings)
+            .chain(gitignore_warning)
             .collect(),
     }
 }
 
+/// Skill phases only (collection, broadcast, cleanup). Used by `hana watch`.
+pub fn run_skills(config: &Config, base_dir: &Path, opts: &SyncOptions) -> SyncOk {
+    skills_ok(config, base_dir, opts, None)
+}
+
+/// Like [`run_skills`], but only collects and broadcasts the given skill ids.
+/// Cleanup still covers every agent directory.
+pub fn run_changed_skills(
+    config: &Config,
+    base_dir: &Path,
+    opts: &SyncOptions,
+    changed: &BTreeSet<String>,
+) -> SyncOk {
+    skills_ok(config, base_dir, opts, Some(changed))
+}
+
+fn skills_ok(
+    config: &Config,
+    base_dir: &Path,
+    opts: &SyncOptions,
+    only: Option<&BTreeSet<String>>,
+) -> SyncOk {
+    let skills = sync_skills(config, base_dir, opts, only);
+    let (gitignore_updated, gitignore_warning) = sync_gitignore(config, base_dir, opts);
+
+    SyncOk {
+        skills_linked: skills.linked,
+        skills_collected: skills.collected,
+        skills_unlinked: skills.unlinked,
+        instructions_collected: None,
+        instructions_linked: vec![],
+        instructions_generated: vec![],
+        instructions_skipped: vec![],
+        instructions_source: instruction_source_name(config, base_dir, opts.global),
+        files_linked: vec![],
+        files_collected: vec![],
+        subagents_generated: vec![],
+        subagents_removed: vec![],
+        subagents_unsupported: vec![],
+        commands_generated: vec![],
+        commands_removed: vec![],
+        commands_unsupported: vec![],
+        mcp_updated: vec![],
+        mcp_removed: vec![],
+        mappings_linked: vec![],
+        mappings_collected: vec![],
+        cleaned: skills.cleaned,
+        pruned: skills.pruned,
+        gitignore_updated,
+        warnings: skills
+            .warnings
+            .into_iter()
+            .chain(gitignore_warning)
+            .collect(),
+    }
+}
+
+/// Skill and instruction phases only. Used by `hana use` after switching profiles.
+pub fn run_profile(config: &Config, base_dir: &Path, opts: &SyncOptions) -> SyncOk {
+    let instructions = sync_instructions(config, base_dir, opts);
+    let mut result = run_skills(config, base_dir, opts);
+    result.instructions_collected = instructions.collected;
+    result.instructions_linked = instructions.linked;
+    result.instructions_generated = instructions.generated;
+    result.instructions_skipped = instructions.skipped;
+    result.warnings.extend(instructions.warnings);
+    result
+}
+
+fn instruction_source_name(config: &Config, base_dir: &Path, global: bool) -> String {
+    config
+        .resolve_source_instruction_path(base_dir, global)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Keep `.gitignore` in step with the managed links (`[git] ignore_links`) and the files local
+/// to this checkout. The block is dropped once neither has entries.
+fn sync_gitignore(
+    config: &Config,
+    base_dir: &Path,
+    opts: &SyncOptions,
+) -> (bool, Option<SyncWarning>) {
+    if opts.global {
+        return (false, None);
+    }
+    let mut paths = if config.git.ignore_links {
+        collect_managed_links(config, base_dir, opts.global)
+    } else {
+        vec![]
+    };
+    paths.extend(local_files(config, base_dir));
+    match update_gitignore_block(base_dir, &paths, opts.dry_run) {
+        Ok(updated) => (updated, None),
+        Err(warning) => (false, Some(warning)),
+    }
+}
+
+/// Files that belong to this checkout only: the active profile, `.agents/.env` and the agent
+/// config files sync writes its values into (Codex, for servers with `${env:NAME}`
+/// placeholders).
+fn local_files(config: &Config, base_dir: &Path) -> Vec<PathBuf> {
+    let servers = config.load_mcp_servers(base_dir, false).unwrap_or_default();
+    let mut files: Vec<PathBuf> = [
+        config.resolve_profile_state_path(base_dir),
+        config.resolve_env_path(base_dir, false),
+    ]
+    .into_iter()
+    .filter(|path| path.is_file())
+    .collect();
+    for (agent, path, format) in config.resolve_mcp_targets(base_dir, false) {
+        if format.env_syntax() == EnvSyntax::Resolve
+            && servers
+                .iter()
+                .any(|s| s.applies_to(agent) && s.has_env_refs())
+        {
+            files.push(path);
+        }
+    }
+    files
+}
+
 // --- Internal result types ---
 
 #[derive(Default)]
 struct SkillsSyncResult {
-    linked: Vec<(String, String)>,
-    collected: Vec<(String, String)>,
+    linked: Vec<LinkedSkill>,
+    collected: Vec<CollectedSkill>,
+    unlinked: Vec<LinkedSkill>,
     cleaned: Vec<PathBuf>,
+    pruned: Vec<PathBuf>,
+    warnings: Vec<SyncWarning>,
+}
+
+#[derive(Default)]
+struct BroadcastSkillsResult {
+    linked: Vec<LinkedSkill>,
+    unlinked: Vec<LinkedSkill>,
     warnings: Vec<SyncWarning>,
 }
 
 #[derive(Default)]
 struct InstructionsSyncResult {
-    collected: Option<(String, String)>,
-    linked: Vec<String>,
-    skipped: Vec<String>,
+    collected: Option<CollectedInstructions>,
+    linked: Vec<AgentName>,
+    generated: Vec<AgentName>,
+    skipped: Vec<AgentName>,
+    warnings: Vec<SyncWarning>,
+}
+
+#[derive(Default)]
+struct FilesSyncResult {
+    linked: Vec<AgentEntry>,
+    collected: Vec<AgentEntry>,
+    warnings: Vec<SyncWarning>,
+}
+
+#[derive(Default)]
+struct RenderedSyncResult {
+    generated: Vec<AgentEntry>,
+    removed: Vec<AgentEntry>,
+    unsupported: Vec<UnsupportedEntry>,
+    warnings: Vec<SyncWarning>,
+}
+
+#[derive(Default)]
+struct McpSyncResult {
+    updated: Vec<AgentEntry>,
+    removed: Vec<AgentEntry>,
+    warnings: Vec<SyncWarning>,
+}
+
+#[derive(Default)]
+struct MappingsSyncResult {
+    linked: Vec<AgentEntry>,
+    collected: Vec<AgentEntry>,
+    cleaned: Vec<PathBuf>,
     warnings: Vec<SyncWarning>,
 }
 
 // --- Skills sync ---
 
-fn sync_skills(config: &Config, base_dir: &Path, opts: &SyncOptions) -> SkillsSyncResult {
+/// `only` limits collection and broadcast to these skill ids.
+fn sync_skills(
+    config: &Config,
+    base_dir: &Path,
+    opts: &SyncOptions,
+    only: Option<&BTreeSet<String>>,
+) -> SkillsSyncResult {
     let source_dir = config.resolve_source_skills_path(base_dir, opts.global);
 
     if !source_dir.exists() && !opts.dry_run {
@@ -140,7 +625,12 @@ fn sync_skills(config: &Config, base_dir: &Path, opts: &SyncOptions) -> SkillsSy
     }
 
     // Phase 1: Collect skills from agent paths into source
-    let collected_by_agent = collect_target_skills(config, base_dir, opts.global);
+    let mut collected_by_agent = collect_target_skills(config, base_dir, opts.global);
+    if let Some(only) = only {
+        for found in collected_by_agent.values_mut() {
+            found.retain(|(name, _)| only.contains(name));
+        }
+    }
     let move_result =
         move_target_skills(&collected_by_agent, &source_dir, opts.force, opts.dry_run);
     let (tasks, move_warnings) = match move_result {
@@ -148,12 +638,21 @@ fn sync_skills(config: &Config, base_dir: &Path, opts: &SyncOptions) -> SkillsSy
         Err(err) => (err.tasks, err.warnings),
     };
     let collected: Vec<_> = tasks
-        .iter()
-        .map(|t| (t.skill.clone(), t.agent.as_str().to_string()))
+        .into_iter()
+        .map(|t| CollectedSkill {
+            skill: t.skill,
+            agent: t.agent,
+            path: t.target_path,
+        })
         .collect();
 
     // Phase 2: Broadcast source skills to agent paths (create symlinks)
-    let source_skills = match collect_source_skills(&source_dir) {
+    let source_skills = match collect_source_skills(
+        &source_dir,
+        &config.source.filter,
+        &load_ignore_rules(&config.resolve_ignore_path(base_dir)),
+        config.source.namespaces,
+    ) {
         Ok(skills) => skills,
         Err(warning) => {
             return SkillsSyncResult {
@@ -168,7 +667,7 @@ fn sync_skills(config: &Config, base_dir: &Path, opts: &SyncOptions) -> SkillsSy
     let skills: Vec<String> = source_skills
         .into_iter()
         .chain(if opts.dry_run {
-            collected.iter().map(|(name, _)| name.clone()).collect()
+            collected.iter().map(|c| c.skill.clone()).collect()
         } else {
             vec![]
         })
@@ -178,57 +677,132 @@ fn sync_skills(config: &Config, base_dir: &Path, opts: &SyncOptions) -> SkillsSy
 
     let enabled_targets =
         resolve_target_destinations(config, base_dir, opts.global, TargetFeature::Skills);
-    let collected_set: HashSet<(&str, &str)> = collected
+    let collected_set: HashSet<(&str, AgentName)> = collected
         .iter()
-        .map(|(name, agent)| (name.as_str(), agent.as_str()))
+        .map(|c| (c.skill.as_str(), c.agent))
         .collect();
-    let (linked, broadcast_warnings) =
-        broadcast_skills(&source_dir, &skills, &enabled_targets, &collected_set, opts);
+    let to_broadcast: Vec<String> = match only {
+        Some(only) => skills
+            .iter()
+            .filter(|s| only.contains(*s))
+            .cloned()
+            .collect(),
+        None => skills.clone(),
+    };
+    let broadcast = broadcast_skills(
+        config,
+        &source_dir,
+        &to_broadcast,
+        &enabled_targets,
+        &collected_set,
+        opts,
+    );
 
     // Phase 3: Clean up broken symlinks
-    let cleaned = clean_broken_symlinks(&enabled_targets, opts.dry_run);
+    let cleaned = clean_broken_symlinks(&enabled_targets, None, opts.dry_run);
+
+    // Phase 4: Remove foreign symlinks (opt-in). Same-named ones were already taken over above.
+    let (pruned, prune_warnings) = if opts.prune_foreign {
+        let link_names: HashSet<String> =
+            skills.iter().map(|s| config.skill_link_name(s)).collect();
+        prune_foreign_symlinks(&source_dir, &enabled_targets, &link_names, opts.dry_run)
+    } else {
+        (vec![], vec![])
+    };
 
     SkillsSyncResult {
-        linked,
+        linked: broadcast.linked,
         collected,
+        unlinked: broadcast.unlinked,
         cleaned,
+        pruned,
         warnings: move_warnings
             .into_iter()
-            .chain(broadcast_warnings)
+            .chain(broadcast.warnings)
+            .chain(prune_warnings)
             .collect(),
     }
 }
 
 fn broadcast_skills(
+    config: &Config,
     source_dir: &Path,
     skills: &[String],
     targets: &HashMap<AgentName, PathBuf>,
-    collected: &HashSet<(&str, &str)>,
+    collected: &HashSet<(&str, AgentName)>,
     opts: &SyncOptions,
-) -> (Vec<(String, String)>, Vec<SyncWarning>) {
+) -> BroadcastSkillsResult {
     let mut linked = Vec::new();
+    let mut unlinked = Vec::new();
     let mut warnings = Vec::new();
 
     for skill in skills {
         let source = source_dir.join(skill);
-        let (ok_linked, conflicts, failed) =
-            match broadcast_target_symlink(&source, targets, opts.dry_run, opts.force) {
-                Ok(ok) => (ok.linked, vec![], vec![]),
-                Err(err) => (err.linked, err.conflicts, err.failed),
-            };
-
-        linked.extend(
-            ok_linked
-                .iter()
-                .map(|a| (skill.clone(), a.as_str().to_string())),
+        let link_name = config.skill_link_name(skill);
+        let (rule, unknown) = resolve_skill_rule(config, &source, skill);
+        warnings.extend(
+            unknown
+                .into_iter()
+                .map(|agent| SyncWarning::UnknownSkillAgent {
+                    skill: skill.clone(),
+                    agent,
+                }),
         );
+        let (allowed, excluded): (HashMap<AgentName, PathBuf>, HashMap<AgentName, PathBuf>) =
+            targets
+                .iter()
+                .map(|(agent, dir)| (*agent, dir.clone()))
+                .partition(|(agent, _)| rule.allows(*agent));
+
+        // Excluded agents: drop links hana created earlier, leave anything else alone
+        let mut excluded: Vec<_> = excluded.into_iter().collect();
+        excluded.sort_by_key(|(agent, _)| agent.as_str());
+        for (agent, dir) in excluded {
+            let link_path = dir.join(&link_name);
+            if !is_link_to(&link_path, &source) {
+                continue;
+            }
+            if !opts.dry_run
+                && let Err(e) = fs::remove_file(&link_path)
+            {
+                warnings.push(SyncWarning::IoFailed {
+                    operation: format!("remove excluded symlink ({skill}, {agent})"),
+                    detail: e.to_string(),
+                });
+                continue;
+            }
+            unlinked.push(LinkedSkill {
+                skill: skill.clone(),
+                agent,
+                path: link_path,
+            });
+        }
+
+        let (ok_linked, conflicts, failed) = match broadcast_target_symlink(
+            &source,
+            &link_name,
+            &allowed,
+            config.source.link_style,
+            opts.dry_run,
+            opts.force,
+        ) {
+            Ok(ok) => (ok.linked, vec![], vec![]),
+            Err(err) => (err.linked, err.conflicts, err.failed),
+        };
+
+        let link = |agent: AgentName| LinkedSkill {
+            skill: skill.clone(),
+            agent,
+            path: allowed[&agent].join(&link_name),
+        };
+        linked.extend(ok_linked.iter().map(|a| link(*a)));
         for a in &conflicts {
-            if collected.contains(&(skill.as_str(), a.as_str())) {
-                linked.push((skill.clone(), a.as_str().to_string()));
+            if collected.contains(&(skill.as_str(), *a)) {
+                linked.push(link(*a));
             } else {
                 warnings.push(SyncWarning::FileConflict {
                     skill: skill.clone(),
-                    agent: a.as_str().to_string(),
+                    agent: *a,
                 });
             }
         }
@@ -238,10 +812,27 @@ fn broadcast_skills(
         }));
     }
 
-    (linked, warnings)
+    BroadcastSkillsResult {
+        linked,
+        unlinked,
+        warnings,
+    }
+}
+
+fn is_link_to(link_path: &Path, source: &Path) -> bool {
+    link_path.is_symlink()
+        && matches!(
+            (fs::canonicalize(link_path), fs::canonicalize(source)),
+            (Ok(a), Ok(b)) if a == b
+        )
 }
 
-fn clean_broken_symlinks(targets: &HashMap<AgentName, PathBuf>, dry_run: bool) -> Vec<PathBuf> {
+/// Remove broken symlinks in `targets`; with `into`, only those that pointed into that directory.
+fn clean_broken_symlinks(
+    targets: &HashMap<AgentName, PathBuf>,
+    into: Option<&Path>,
+    dry_run: bool,
+) -> Vec<PathBuf> {
     let broken: Vec<PathBuf> = targets
         .values()
         .filter(|dir| dir.exists())
@@ -253,6 +844,7 @@ fn clean_broken_symlinks(targets: &HashMap<AgentName, PathBuf>, dry_run: bool) -
         })
         .map(|e| e.path())
         .filter(|p| p.is_symlink() && !p.exists())
+        .filter(|p| into.is_none_or(|dir| link_points_into(p, dir)))
         .collect();
 
     if !dry_run {
@@ -264,6 +856,37 @@ fn clean_broken_symlinks(targets: &HashMap<AgentName, PathBuf>, dry_run: bool) -
     broken
 }
 
+fn prune_foreign_symlinks(
+    source_dir: &Path,
+    targets: &HashMap<AgentName, PathBuf>,
+    link_names: &HashSet<String>,
+    dry_run: bool,
+) -> (Vec<PathBuf>, Vec<SyncWarning>) {
+    let mut target_dirs: Vec<(AgentName, PathBuf)> = targets
+        .iter()
+        .map(|(agent, dir)| (*agent, dir.clone()))
+        .collect();
+    target_dirs.sort_by_key(|(agent, _)| agent.as_str());
+
+    let mut pruned = Vec::new();
+    let mut warnings = Vec::new();
+    for entry in classify_target_entries(source_dir, &target_dirs) {
+        if entry.kind != TargetEntryKind::ForeignSymlink || link_names.contains(&entry.name) {
+            continue;
+        }
+        if !dry_run && let Err(e) = fs::remove_file(&entry.path) {
+            warnings.push(SyncWarning::IoFailed {
+                operation: format!("remove foreign symlink ({})", entry.path.display()),
+                detail: e.to_string(),
+            });
+            continue;
+        }
+        pruned.push(entry.path);
+    }
+
+    (pruned, warnings)
+}
+
 // --- Instructions sync ---
 
 fn sync_instructions(
@@ -276,8 +899,14 @@ fn sync_instructions(
     // If source doesn't exist, try collecting from agent-specific instruction files
     let collected = if !source_path.exists() {
         match collect_instruction(config, base_dir, &source_path, opts) {
-            Some(collected) => Some(collected),
-            None => return InstructionsSyncResult::default(),
+            Ok(Some(collected)) => Some(collected),
+            Ok(None) => return InstructionsSyncResult::default(),
+            Err(warning) => {
+                return InstructionsSyncResult {
+                    warnings: vec![warning],
+                    ..Default::default()
+                };
+            }
         }
     } else {
         None
@@ -287,29 +916,79 @@ fn sync_instructions(
         resolve_target_destinations(config, base_dir, opts.global, TargetFeature::Instructions);
 
     // Agents not in dest_map but enabled → they read the source path directly (skipped)
-    let skipped: Vec<String> = config
+    let skipped: Vec<AgentName> = config
         .enabled_targets(TargetFeature::Instructions)
         .filter(|agent| !dest_map.contains_key(agent))
-        .map(|agent| agent.as_str().to_string())
         .collect();
 
     // Skip the collected agent — already symlinked (in dry-run, file hasn't moved so skip to avoid false conflict)
-    let collected_agent: Option<&str> = collected.as_ref().map(|(_, agent)| agent.as_str());
+    let collected_agent: Option<AgentName> = collected.as_ref().map(|c| c.agent);
+
+    // Source text, for spotting real files that are byte-identical to it (safe to replace).
+    // In dry-run the collected file has not moved yet, so read it from the agent path.
+    let source_text = fs::read_to_string(&source_path).ok().or_else(|| {
+        dest_map
+            .iter()
+            .find(|(agent, _)| collected_agent == Some(**agent))
+            .and_then(|(_, path)| fs::read_to_string(path).ok())
+    });
 
     let mut linked = Vec::new();
+    let mut generated = Vec::new();
     let mut warnings = Vec::new();
 
     for (agent, dest_path) in &dest_map {
-        if collected_agent == Some(agent.as_str()) {
+        let display_name = config
+            .target_instruction_path(*agent, opts.global)
+            .unwrap_or(agent.as_str());
+
+        if wants_generated(config, base_dir, opts.global, *agent) {
+            let Some(expected) = expected_generated_file(config, base_dir, opts.global, *agent)
+            else {
+                // Source only exists after a dry-run collection; nothing to render yet
+                continue;
+            };
+            warnings.extend(expected.missing.into_iter().map(|include| {
+                SyncWarning::IncludeMissing {
+                    file: display_name.to_string(),
+                    include,
+                }
+            }));
+            match generate_instruction_file(dest_path, &expected.content, opts.dry_run, opts.force)
+            {
+                GenerateOutcome::Written => generated.push(*agent),
+                GenerateOutcome::AlreadyValid => {}
+                GenerateOutcome::Conflict => warnings.push(SyncWarning::InstructionConflict {
+                    file: display_name.to_string(),
+                }),
+                GenerateOutcome::Edited => warnings.push(SyncWarning::GeneratedEdited {
+                    file: display_name.to_string(),
+                }),
+                GenerateOutcome::Failed(detail) => warnings.push(SyncWarning::IoFailed {
+                    operation: format!("generate instructions ({display_name})"),
+                    detail,
+                }),
+            }
             continue;
         }
 
-        let display_name = config
-            .target_instruction_path(agent.as_str(), opts.global)
-            .unwrap_or(agent.as_str());
+        if collected_agent == Some(*agent) {
+            continue;
+        }
 
-        match link_one(&source_path, dest_path, opts.dry_run, opts.force) {
-            LinkOutcome::Created => linked.push(agent.as_str().to_string()),
+        let identical = !dest_path.is_symlink()
+            && dest_path.is_file()
+            && source_text.is_some()
+            && fs::read_to_string(dest_path).ok() == source_text;
+
+        match link_one(
+            &source_path,
+            dest_path,
+            config.source.link_style,
+            opts.dry_run,
+            opts.force || identical,
+        ) {
+            LinkOutcome::Created => linked.push(*agent),
             LinkOutcome::AlreadyValid => {}
             LinkOutcome::Conflict => {
                 warnings.push(SyncWarning::InstructionConflict {
@@ -328,111 +1007,669 @@ fn sync_instructions(
     InstructionsSyncResult {
         collected,
         linked,
+        generated,
         skipped,
         warnings,
     }
 }
 
-/// When the source instruction file is missing, find an agent-specific instruction file
-/// (e.g. CLAUDE.md) that is a real file (not a symlink), move it to the source path,
-/// and create a symlink in its place.
+/// When the source instruction file is missing, move the agent-specific instruction file
+/// (e.g. CLAUDE.md) to the source path and symlink it back. When several real files exist
+/// they must agree; diverged files are reported instead of picking one.
 fn collect_instruction(
     config: &Config,
     base_dir: &Path,
     source_path: &Path,
     opts: &SyncOptions,
-) -> Option<(String, String)> {
-    let dest_map =
-        resolve_target_destinations(config, base_dir, opts.global, TargetFeature::Instructions);
+) -> Result<Option<CollectedInstructions>, SyncWarning> {
+    let candidates = find_divergent_instructions(config, base_dir, opts.global);
+    let display_name = |agent: AgentName| {
+        config
+            .target_instruction_path(agent, opts.global)
+            .unwrap_or(agent.as_str())
+            .to_string()
+    };
 
-    // Find the first agent with a real instruction file (not a symlink)
-    let candidate = AgentName::iter()
-        .filter(|agent| dest_map.contains_key(agent))
-        .find_map(|agent| {
-            let path = dest_map.get(&agent)?;
-            if path.exists() && !path.is_symlink() && path.is_file() {
-                Some((agent, path.clone()))
-            } else {
-                None
-            }
+    let Some(first) = candidates.first() else {
+        return Ok(None);
+    };
+    if candidates.iter().any(|c| c.content != first.content) {
+        return Err(SyncWarning::InstructionsDiverged {
+            files: candidates.iter().map(|c| display_name(c.agent)).collect(),
         });
-
-    let (agent, agent_path) = candidate?;
+    }
 
     if !opts.dry_run {
-        if let Err(e) = fs::rename(&agent_path, source_path) {
-            eprintln!(
-                "  ⚠ failed to collect instruction ({} → {}): {e}",
-                agent_path.display(),
+        fs::rename(&first.path, source_path).map_err(|e| SyncWarning::IoFailed {
+            operation: format!(
+                "collect instruction ({} → {})",
+                first.path.display(),
                 source_path.display()
-            );
-            return None;
-        }
-        let rel_source = agent_path
-            .parent()
-            .map(|parent| relative_path(parent, source_path))
-            .unwrap_or_else(|| source_path.to_path_buf());
-        if let Err(e) = std::os::unix::fs::symlink(&rel_source, &agent_path) {
-            eprintln!(
-                "  ⚠ failed to create symlink ({}): {e}",
-                agent_path.display()
-            );
-        }
+            ),
+            detail: e.to_string(),
+        })?;
+        let rel_source = link_target(&first.path, source_path, config.source.link_style);
+        std::os::unix::fs::symlink(&rel_source, &first.path).map_err(|e| {
+            SyncWarning::IoFailed {
+                operation: format!("create symlink ({})", first.path.display()),
+                detail: e.to_string(),
+            }
+        })?;
     }
 
-    let display_name = config
-        .target_instruction_path(agent.as_str(), opts.global)
-        .unwrap_or(agent.as_str());
-
-    Some((display_name.to_string(), agent.as_str().to_string()))
+    Ok(Some(CollectedInstructions {
+        file: display_name(first.agent),
+        agent: first.agent,
+    }))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::os::unix::fs::symlink;
-    use tempfile::TempDir;
+// --- Extra files sync ---
 
-    fn setup_source(tmp: &Path) {
-        let skills = tmp.join(".agents/skills");
-        fs::create_dir_all(skills.join("my-skill")).unwrap();
-        fs::write(skills.join("my-skill/SKILL.md"), "# My Skill").unwrap();
-        fs::write(tmp.join("AGENTS.md"), "# Instructions").unwrap();
-    }
+/// Link each `[target.x] files` entry to its source. A real file at the destination whose
+/// source does not exist yet is collected into it first, like an instruction file.
+fn sync_files(config: &Config, base_dir: &Path, opts: &SyncOptions) -> FilesSyncResult {
+    let mut result = FilesSyncResult::default();
 
-    #[test]
-    fn test_sync_skills_and_instructions() {
-        let tmp = TempDir::new().unwrap();
-        setup_source(tmp.path());
+    for file in resolve_managed_files(config, base_dir, opts.global) {
+        let entry = entry(file.display.clone(), file.agent);
+        if file.source == file.dest {
+            continue;
+        }
 
-        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+        if !file.source.exists() {
+            if file.dest.is_symlink() || !file.dest.is_file() {
+                continue;
+            }
+            if !opts.dry_run {
+                let moved = file
+                    .source
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::rename(&file.dest, &file.source))
+                    .and_then(|_| {
+                        std::os::unix::fs::symlink(
+                            link_target(&file.dest, &file.source, config.source.link_style),
+                            &file.dest,
+                        )
+                    });
+                if let Err(e) = moved {
+                    result.warnings.push(SyncWarning::IoFailed {
+                        operation: format!("collect file ({})", file.display),
+                        detail: e.to_string(),
+                    });
+                    continue;
+                }
+            }
+            result.collected.push(entry);
+            continue;
+        }
 
-        assert!(tmp.path().join(".claude/skills/my-skill").is_symlink());
-        assert!(tmp.path().join(".opencode/skills/my-skill").is_symlink());
-        // Pi and Codex use .agents/skills (same as source), no symlinks needed
-        assert!(!tmp.path().join(".agents/skills/my-skill").is_symlink());
-        assert!(result.skills_linked.len() >= 2);
-        assert!(tmp.path().join("CLAUDE.md").is_symlink());
-        assert!(result.instructions_linked.contains(&"claude".to_string()));
-        assert!(result.warnings.is_empty());
+        let identical = !file.dest.is_symlink()
+            && file.dest.is_file()
+            && fs::read(&file.dest).ok() == fs::read(&file.source).ok();
+
+        match link_one(
+            &file.source,
+            &file.dest,
+            config.source.link_style,
+            opts.dry_run,
+            opts.force || identical,
+        ) {
+            LinkOutcome::Created => result.linked.push(entry),
+            LinkOutcome::AlreadyValid => {}
+            LinkOutcome::Conflict => result
+                .warnings
+                .push(SyncWarning::TargetFileConflict { file: file.display }),
+            LinkOutcome::Failed(detail) => result.warnings.push(SyncWarning::IoFailed {
+                operation: format!("file symlink ({})", file.display),
+                detail,
+            }),
+        }
     }
 
-    #[test]
-    fn test_sync_idempotent() {
-        let tmp = TempDir::new().unwrap();
-        setup_source(tmp.path());
-        let config = Config::default();
+    result
+}
 
-        run(&config, tmp.path(), &SyncOptions::default());
-        let result = run(&config, tmp.path(), &SyncOptions::default());
+// --- Rendered definitions sync ---
 
-        assert!(result.skills_linked.is_empty());
-        assert!(result.instructions_linked.is_empty());
+/// Render every `*.md` definition in `source_dir` (subagents, commands) for each target, and
+/// drop unedited generated files in `prune_dirs` that are no longer produced, including those
+/// of agents or features turned off since.
+fn sync_rendered(
+    kind: &'static str,
+    source_dir: &Path,
+    targets: &[(AgentName, Option<PathBuf>, &RenderTarget)],
+    prune_dirs: &[(AgentName, PathBuf)],
+    base_dir: &Path,
+    opts: &SyncOptions,
+) -> RenderedSyncResult {
+    let mut result = RenderedSyncResult::default();
+    let plans = plan_rendered_files(source_dir, targets);
+
+    for planned in &plans {
+        let agent = planned.agent;
+        let (path, content) = match &planned.plan {
+            RenderPlan::Write { path, content } => (path, content),
+            RenderPlan::Unsupported(reason) => {
+                result.unsupported.push(UnsupportedEntry {
+                    name: planned.name.clone(),
+                    agent,
+                    reason: reason.clone(),
+                });
+                continue;
+            }
+        };
+        match generate_instruction_file(path, content, opts.dry_run, opts.force) {
+            GenerateOutcome::Written => result.generated.push(entry(&planned.name, agent)),
+            GenerateOutcome::AlreadyValid => {}
+            GenerateOutcome::Conflict => result.warnings.push(SyncWarning::RenderedConflict {
+                kind,
+                name: planned.name.clone(),
+                agent,
+            }),
+            GenerateOutcome::Edited => result.warnings.push(SyncWarning::GeneratedEdited {
+                file: path
+                    .strip_prefix(base_dir)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string(),
+            }),
+            GenerateOutcome::Failed(detail) => result.warnings.push(SyncWarning::IoFailed {
+                operation: format!("generate {kind} ({}, {agent})", planned.name),
+                detail,
+            }),
+        }
     }
 
-    #[test]
-    fn test_sync_collects_and_broadcasts() {
-        let tmp = TempDir::new().unwrap();
+    for (agent, dest_dir) in prune_dirs {
+        for (name, path) in list_dir_entries(dest_dir, true) {
+            let wanted = plans
+                .iter()
+                .any(|p| matches!(&p.plan, RenderPlan::Write { path: w, .. } if *w == path));
+            if wanted {
+                continue;
+            }
+            let orphaned = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| parse_generated(&content).map(|g| !g.is_edited()))
+                .unwrap_or(false);
+            if !orphaned {
+                continue;
+            }
+            if !opts.dry_run
+                && let Err(e) = fs::remove_file(&path)
+            {
+                result.warnings.push(SyncWarning::IoFailed {
+                    operation: format!("remove {kind} ({name}, {agent})"),
+                    detail: e.to_string(),
+                });
+                continue;
+            }
+            result.removed.push(entry(name, *agent));
+        }
+    }
+
+    result
+}
+
+// --- MCP sync ---
+
+/// Merge the MCP source into each agent config file. Only entries hana wrote before (tracked in
+/// `.agents/.hanamcp`) are updated or removed; a hand-written entry with the same name as a
+/// source server is reported unless `--force`. A file is rewritten only when it changed.
+fn sync_mcp(config: &Config, base_dir: &Path, opts: &SyncOptions) -> McpSyncResult {
+    let mut result = McpSyncResult::default();
+    let servers = match config.load_mcp_servers(base_dir, opts.global) {
+        Ok(servers) => servers,
+        Err(e) => {
+            result.warnings.push(SyncWarning::McpSourceInvalid {
+                detail: e.to_string(),
+            });
+            return result;
+        }
+    };
+    let state_path = config.resolve_mcp_state_path(base_dir);
+    let mut owned = match McpOwnership::load(&state_path) {
+        Ok(owned) => owned,
+        Err(e) => {
+            result.warnings.push(SyncWarning::IoFailed {
+                operation: format!("read MCP ownership ({})", state_path.display()),
+                detail: e.to_string(),
+            });
+            return result;
+        }
+    };
+    let initially_owned = owned.clone();
+    let env_path = config.resolve_env_path(base_dir, opts.global);
+    let env = load_env_file(&env_path).unwrap_or_else(|e| {
+        result.warnings.push(SyncWarning::IoFailed {
+            operation: format!("read {}", env_path.display()),
+            detail: e.to_string(),
+        });
+        vec![]
+    });
+    let enabled = config.resolve_mcp_targets(base_dir, opts.global);
+
+    for (agent, path, format) in config.resolve_mcp_prune_targets(base_dir, opts.global) {
+        let wanted: Vec<&McpServer> = if enabled.iter().any(|(a, _, _)| *a == agent) {
+            servers.iter().filter(|s| s.applies_to(agent)).collect()
+        } else {
+            vec![]
+        };
+        let stale: Vec<String> = owned
+            .servers(agent)
+            .into_iter()
+            .filter(|name| !wanted.iter().any(|s| s.name == *name))
+            .collect();
+        if wanted.is_empty() && stale.is_empty() {
+            continue;
+        }
+
+        let display = path
+            .strip_prefix(base_dir)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        let mut file = match McpConfigFile::load(&path, format) {
+            Ok(file) => file,
+            Err(detail) => {
+                result.warnings.push(SyncWarning::McpConfigInvalid {
+                    file: display,
+                    detail,
+                });
+                continue;
+            }
+        };
+
+        // Resolved secrets never go into a file git tracks
+        let tracked = !opts.global
+            && format.env_syntax() == EnvSyntax::Resolve
+            && !git_tracked_paths(base_dir, std::slice::from_ref(&path)).is_empty();
+
+        let mut next = owned.clone();
+        let mut changed = Vec::new();
+        for server in wanted {
+            if tracked && server.has_env_refs() {
+                result.warnings.push(SyncWarning::SecretInTrackedFile {
+                    server: server.name.clone(),
+                    agent,
+                    file: display.clone(),
+                });
+                continue;
+            }
+            let entry = match expected_mcp_entry(server, format, &env) {
+                Ok(entry) => entry,
+                Err(name) => {
+                    result.warnings.push(SyncWarning::EnvUnresolved {
+                        server: server.name.clone(),
+                        agent,
+                        name,
+                    });
+                    continue;
+                }
+            };
+            match file.get(&server.name) {
+                Some(current) if current == entry => {}
+                Some(_) if !owned.owns(agent, &server.name) && !opts.force => {
+                    result.warnings.push(SyncWarning::McpServerConflict {
+                        server: server.name.clone(),
+                        agent,
+                        file: display.clone(),
+                    });
+                    continue;
+                }
+                _ => {
+                    file.set(&server.name, &entry);
+                    changed.push((server.name.clone(), true));
+                }
+            }
+            next.insert(agent, &server.name);
+        }
+        for name in stale {
+            if file.remove(&name) {
+                changed.push((name.clone(), false));
+            }
+            next.remove(agent, &name);
+        }
+
+        if !changed.is_empty() && !opts.dry_run {
+            if let Err(e) = file.save(&path) {
+                result.warnings.push(SyncWarning::IoFailed {
+                    operation: format!("write MCP servers ({display})"),
+                    detail: e.to_string(),
+                });
+                continue;
+            }
+        }
+        owned = next;
+        for (name, updated) in changed {
+            let row = entry(name, agent);
+            if updated {
+                result.updated.push(row);
+            } else {
+                result.removed.push(row);
+            }
+        }
+    }
+
+    if owned != initially_owned && !opts.dry_run {
+        if let Err(e) = owned.save(&state_path) {
+            result.warnings.push(SyncWarning::IoFailed {
+                operation: format!("write MCP ownership ({})", state_path.display()),
+                detail: e.to_string(),
+            });
+        }
+    }
+    result
+}
+
+// --- Mappings sync ---
+
+/// Collect, broadcast and clean up every `[[mapping]]`, the same way skills are handled.
+fn sync_mappings(config: &Config, base_dir: &Path, opts: &SyncOptions) -> MappingsSyncResult {
+    let mut result = MappingsSyncResult::default();
+    for mapping in &config.mappings {
+        match mapping.kind {
+            MappingKind::Dir => sync_mapping_dir(config, mapping, base_dir, opts, &mut result),
+            MappingKind::File => sync_mapping_file(config, mapping, base_dir, opts, &mut result),
+        }
+    }
+    result
+}
+
+fn sync_mapping_dir(
+    config: &Config,
+    mapping: &Mapping,
+    base_dir: &Path,
+    opts: &SyncOptions,
+    result: &mut MappingsSyncResult,
+) {
+    let source_dir = mapping.resolve_source(base_dir);
+    let dests = config.resolve_mapping_dests(mapping, base_dir);
+    let label = |entry: &str| format!("{}/{entry}", mapping.name);
+
+    if !source_dir.exists()
+        && !opts.dry_run
+        && let Err(e) = fs::create_dir_all(&source_dir)
+    {
+        result.warnings.push(SyncWarning::IoFailed {
+            operation: format!("create mapping source ({})", source_dir.display()),
+            detail: e.to_string(),
+        });
+        return;
+    }
+
+    // Collect real entries from the destinations
+    let mut collected: HashSet<(String, AgentName)> = HashSet::new();
+    if mapping.collect == CollectPolicy::Auto {
+        let found: HashMap<AgentName, Vec<(String, PathBuf)>> = dests
+            .iter()
+            .map(|(agent, dir)| (*agent, list_dir_entries(dir, true)))
+            .collect();
+        let (tasks, warnings) =
+            match move_target_skills(&found, &source_dir, opts.force, opts.dry_run) {
+                Ok(ok) => (ok.tasks, vec![]),
+                Err(err) => (err.tasks, err.warnings),
+            };
+        for task in tasks {
+            result.collected.push(entry(label(&task.skill), task.agent));
+            collected.insert((task.skill, task.agent));
+        }
+        result
+            .warnings
+            .extend(warnings.into_iter().map(|warning| match warning {
+                SyncWarning::SkillConflict { name, agents } => SyncWarning::MappingConflict {
+                    mapping: mapping.name.clone(),
+                    entry: name,
+                    agents,
+                },
+                SyncWarning::SourceSkillConflict { skill, agent } => {
+                    SyncWarning::MappingEntryConflict {
+                        mapping: mapping.name.clone(),
+                        entry: skill,
+                        agent,
+                    }
+                }
+                other => other,
+            }));
+    }
+
+    // Broadcast source entries (plus pending collections in dry-run)
+    let entries: BTreeSet<String> = list_dir_entries(&source_dir, false)
+        .into_iter()
+        .map(|(name, _)| name)
+        .chain(collected.iter().map(|(name, _)| name.clone()))
+        .collect();
+    for entry in &entries {
+        let (linked, conflicts, failed) = match broadcast_target_symlink(
+            &source_dir.join(entry),
+            entry,
+            &dests,
+            config.source.link_style,
+            opts.dry_run,
+            opts.force,
+        ) {
+            Ok(ok) => (ok.linked, vec![], vec![]),
+            Err(err) => (err.linked, err.conflicts, err.failed),
+        };
+        for agent in linked {
+            if !collected.contains(&(entry.clone(), agent)) {
+                result.linked.push(self::entry(label(entry), agent));
+            }
+        }
+        for agent in conflicts {
+            if !collected.contains(&(entry.clone(), agent)) {
+                result.warnings.push(SyncWarning::MappingEntryConflict {
+                    mapping: mapping.name.clone(),
+                    entry: entry.clone(),
+                    agent,
+                });
+            }
+        }
+        result.warnings.extend(
+            failed
+                .into_iter()
+                .map(|(agent, detail)| SyncWarning::IoFailed {
+                    operation: format!("create symlink ({}, {agent})", label(entry)),
+                    detail,
+                }),
+        );
+    }
+
+    // Clean up broken symlinks into the mapping source; the directories may hold other links
+    result.cleaned.extend(clean_broken_symlinks(
+        &dests,
+        Some(&source_dir),
+        opts.dry_run,
+    ));
+}
+
+fn sync_mapping_file(
+    config: &Config,
+    mapping: &Mapping,
+    base_dir: &Path,
+    opts: &SyncOptions,
+    result: &mut MappingsSyncResult,
+) {
+    let source = mapping.resolve_source(base_dir);
+    let mut dests: Vec<(AgentName, PathBuf)> = config
+        .resolve_mapping_dests(mapping, base_dir)
+        .into_iter()
+        .collect();
+    dests.sort_by_key(|(agent, _)| agent.as_str());
+
+    // Collect: only when every real destination file agrees
+    let mut collected_agent = None;
+    if !source.exists() {
+        let real: Vec<(AgentName, &PathBuf, Vec<u8>)> = dests
+            .iter()
+            .filter(|(_, dest)| !dest.is_symlink() && dest.is_file())
+            .filter_map(|(agent, dest)| Some((*agent, dest, fs::read(dest).ok()?)))
+            .collect();
+        let Some((agent, dest, content)) = real.first() else {
+            return;
+        };
+        if mapping.collect == CollectPolicy::Never {
+            return;
+        }
+        if real.iter().any(|(_, _, other)| other != content) {
+            result.warnings.push(SyncWarning::MappingConflict {
+                mapping: mapping.name.clone(),
+                entry: mapping.name.clone(),
+                agents: real.iter().map(|(a, _, _)| *a).collect(),
+            });
+            return;
+        }
+        if !opts.dry_run {
+            let moved = source
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::rename(dest, &source));
+            if let Err(e) = moved {
+                result.warnings.push(SyncWarning::IoFailed {
+                    operation: format!("collect mapping ({})", mapping.name),
+                    detail: e.to_string(),
+                });
+                return;
+            }
+        }
+        result.collected.push(entry(&mapping.name, *agent));
+        collected_agent = Some((*agent, content.clone()));
+    }
+
+    let source_content = fs::read(&source)
+        .ok()
+        .or_else(|| collected_agent.as_ref().map(|(_, c)| c.clone()));
+    for (agent, dest) in &dests {
+        let is_collected = collected_agent.as_ref().is_some_and(|(a, _)| a == agent);
+        if is_collected && opts.dry_run {
+            continue;
+        }
+        let identical = !dest.is_symlink()
+            && dest.is_file()
+            && source_content.is_some()
+            && fs::read(dest).ok() == source_content;
+
+        match link_one(
+            &source,
+            dest,
+            config.source.link_style,
+            opts.dry_run,
+            opts.force || identical,
+        ) {
+            LinkOutcome::Created if !is_collected => {
+                result.linked.push(entry(&mapping.name, *agent))
+            }
+            LinkOutcome::Created | LinkOutcome::AlreadyValid => {}
+            LinkOutcome::Conflict => result.warnings.push(SyncWarning::MappingEntryConflict {
+                mapping: mapping.name.clone(),
+                entry: dest
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                agent: *agent,
+            }),
+            LinkOutcome::Failed(detail) => result.warnings.push(SyncWarning::IoFailed {
+                operation: format!("create symlink ({}, {agent})", mapping.name),
+                detail,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    fn setup_source(tmp: &Path) {
+        let skills = tmp.join(".agents/skills");
+        fs::create_dir_all(skills.join("my-skill")).unwrap();
+        fs::write(skills.join("my-skill/SKILL.md"), "# My Skill").unwrap();
+        fs::write(tmp.join("AGENTS.md"), "# Instructions").unwrap();
+    }
+
+    #[test]
+    fn test_sync_skills_and_instructions() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+
+        assert!(tmp.path().join(".claude/skills/my-skill").is_symlink());
+        assert!(tmp.path().join(".opencode/skills/my-skill").is_symlink());
+        // Pi and Codex use .agents/skills (same as source), no symlinks needed
+        assert!(!tmp.path().join(".agents/skills/my-skill").is_symlink());
+        assert!(result.skills_linked.len() >= 2);
+        assert!(tmp.path().join("CLAUDE.md").is_symlink());
+        assert!(result.instructions_linked.contains(&AgentName::Claude));
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_run_changed_skills_only_touches_given_skills() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let other = tmp.path().join(".agents/skills/other-skill");
+        fs::create_dir_all(&other).unwrap();
+        fs::write(other.join("SKILL.md"), "# Other").unwrap();
+
+        let changed = BTreeSet::from(["other-skill".to_string()]);
+        let result = run_changed_skills(
+            &Config::default(),
+            tmp.path(),
+            &SyncOptions::default(),
+            &changed,
+        );
+
+        assert!(tmp.path().join(".claude/skills/other-skill").is_symlink());
+        assert!(!tmp.path().join(".claude/skills/my-skill").exists());
+        assert!(
+            result
+                .skills_linked
+                .iter()
+                .all(|l| l.skill == "other-skill")
+        );
+    }
+
+    #[test]
+    fn test_sync_custom_agent() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let config = Config::parse("[target.cursor]\ncustom = true\n").unwrap();
+        let cursor = AgentName::new("cursor");
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        let link = tmp.path().join(".cursor/skills/my-skill");
+        assert!(link.is_symlink());
+        assert!(result.skills_linked.contains(&LinkedSkill {
+            skill: "my-skill".to_string(),
+            agent: cursor,
+            path: link,
+        }));
+        // Reads AGENTS.md, the source, like codex
+        assert!(result.instructions_skipped.contains(&cursor));
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_sync_idempotent() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let config = Config::default();
+
+        run(&config, tmp.path(), &SyncOptions::default());
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert!(result.skills_linked.is_empty());
+        assert!(result.instructions_linked.is_empty());
+    }
+
+    #[test]
+    fn test_sync_collects_and_broadcasts() {
+        let tmp = TempDir::new().unwrap();
         setup_source(tmp.path());
 
         // Use claude path (claude still needs symlinks)
@@ -519,20 +1756,17 @@ mod tests {
             result
                 .skills_collected
                 .iter()
-                .any(|(n, a)| n == "new-skill" && a == "claude")
+                .any(|s| s.skill == "new-skill" && s.agent == AgentName::Claude)
         );
         assert!(
             result
                 .skills_linked
                 .iter()
-                .any(|(n, a)| n == "new-skill" && a == "claude")
-        );
-        assert!(
-            !result
-                .warnings
-                .iter()
-                .any(|w| matches!(w, SyncWarning::FileConflict { agent, .. } if agent == "claude"))
+                .any(|s| s.skill == "new-skill" && s.agent == AgentName::Claude)
         );
+        assert!(!result.warnings.iter().any(
+            |w| matches!(w, SyncWarning::FileConflict { agent, .. } if *agent == AgentName::Claude)
+        ));
     }
 
     #[test]
@@ -560,9 +1794,9 @@ mod tests {
                 .is_relative()
         );
         assert!(result.instructions_collected.is_some());
-        let (file, agent) = result.instructions_collected.unwrap();
-        assert_eq!(file, "CLAUDE.md");
-        assert_eq!(agent, "claude");
+        let collected = result.instructions_collected.unwrap();
+        assert_eq!(collected.file, "CLAUDE.md");
+        assert_eq!(collected.agent, AgentName::Claude);
     }
 
     #[test]
@@ -587,31 +1821,864 @@ mod tests {
     }
 
     #[test]
-    fn test_sync_no_collect_when_agents_md_exists() {
+    fn test_sync_does_not_pick_between_diverged_instructions() {
         let tmp = TempDir::new().unwrap();
-        fs::write(tmp.path().join("AGENTS.md"), "# Source").unwrap();
         fs::write(tmp.path().join("CLAUDE.md"), "# Claude").unwrap();
+        fs::write(tmp.path().join("PI.md"), "# Pi").unwrap();
+        let config = Config::parse("[target.pi]\ninstruction_path = \"PI.md\"\n").unwrap();
 
-        let opts = SyncOptions {
-            force: true,
-            ..Default::default()
-        };
-        let result = run(&Config::default(), tmp.path(), &opts);
+        let result = run(&config, tmp.path(), &SyncOptions::default());
 
+        assert!(!tmp.path().join("AGENTS.md").exists());
         assert!(result.instructions_collected.is_none());
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::InstructionsDiverged { files } if files == &["CLAUDE.md", "PI.md"]
+        )));
+    }
+
+    #[test]
+    fn test_sync_collects_identical_instructions_without_conflict() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("CLAUDE.md"), "# Same").unwrap();
+        fs::write(tmp.path().join("PI.md"), "# Same").unwrap();
+        let config = Config::parse("[target.pi]\ninstruction_path = \"PI.md\"\n").unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
         assert_eq!(
             fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap(),
-            "# Source"
+            "# Same"
         );
+        assert!(tmp.path().join("CLAUDE.md").is_symlink());
+        assert!(tmp.path().join("PI.md").is_symlink());
+        assert!(result.warnings.is_empty());
     }
 
     #[test]
-    fn test_sync_no_instruction_when_nothing_exists() {
+    fn test_sync_links_and_collects_target_files() {
         let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".pi/agent")).unwrap();
+        fs::write(tmp.path().join(".pi/agent/APPEND_SYSTEM.md"), "# Append").unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(tmp.path().join(".agents/SYSTEM.md"), "# System").unwrap();
+        let config = Config::parse(
+            "[target.pi]\nfiles = [\n  { source = \".agents/SYSTEM.md\", dest = \".pi/agent/SYSTEM.md\" },\n  { source = \".agents/APPEND_SYSTEM.md\", dest = \".pi/agent/APPEND_SYSTEM.md\" },\n]\n",
+        )
+        .unwrap();
 
-        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+        let result = run(&config, tmp.path(), &SyncOptions::default());
 
-        assert!(result.instructions_collected.is_none());
-        assert!(result.instructions_linked.is_empty());
+        assert_eq!(
+            result.files_linked,
+            vec![entry(".pi/agent/SYSTEM.md", AgentName::Pi)]
+        );
+        assert_eq!(
+            result.files_collected,
+            vec![entry(".pi/agent/APPEND_SYSTEM.md", AgentName::Pi)]
+        );
+        assert_eq!(
+            fs::read_link(tmp.path().join(".pi/agent/SYSTEM.md")).unwrap(),
+            PathBuf::from("../../.agents/SYSTEM.md")
+        );
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(".agents/APPEND_SYSTEM.md")).unwrap(),
+            "# Append"
+        );
+        assert!(tmp.path().join(".pi/agent/APPEND_SYSTEM.md").is_symlink());
+
+        let again = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(again.files_linked.is_empty() && again.files_collected.is_empty());
+    }
+
+    const REVIEWER: &str = "---\nname: reviewer\ndescription: Reviews code\ntools: [Read, Grep]\n---\nYou review code.\n";
+
+    #[test]
+    fn test_sync_renders_subagents_per_agent() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/agents")).unwrap();
+        fs::write(tmp.path().join(".agents/agents/reviewer.md"), REVIEWER).unwrap();
+        let config = Config::parse("[source]\nsubagents = true\n").unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert_eq!(
+            result.subagents_generated,
+            vec![
+                entry("reviewer.md", AgentName::Claude),
+                entry("reviewer.md", AgentName::Opencode),
+            ]
+        );
+        let claude = fs::read_to_string(tmp.path().join(".claude/agents/reviewer.md")).unwrap();
+        assert!(claude.starts_with("---\n# hana:generated from reviewer.md"));
+        assert!(claude.contains("tools: Read, Grep\n"));
+        let opencode = fs::read_to_string(tmp.path().join(".opencode/agent/reviewer.md")).unwrap();
+        assert!(opencode.contains("mode: subagent\n"));
+        assert!(!opencode.contains("name: reviewer"));
+
+        let again = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(again.subagents_generated.is_empty());
+    }
+
+    #[test]
+    fn test_sync_subagents_keeps_hand_edits_and_removes_orphans() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/agents")).unwrap();
+        fs::write(tmp.path().join(".agents/agents/reviewer.md"), REVIEWER).unwrap();
+        fs::create_dir_all(tmp.path().join(".claude/agents")).unwrap();
+        fs::write(tmp.path().join(".claude/agents/reviewer.md"), "# Mine\n").unwrap();
+        let config = Config::parse("[source]\nsubagents = true\n").unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::RenderedConflict { agent, .. } if *agent == AgentName::Claude
+        )));
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(".claude/agents/reviewer.md")).unwrap(),
+            "# Mine\n"
+        );
+
+        fs::remove_file(tmp.path().join(".agents/agents/reviewer.md")).unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert_eq!(
+            result.subagents_removed,
+            vec![entry("reviewer.md", AgentName::Opencode)]
+        );
+        assert!(!tmp.path().join(".opencode/agent/reviewer.md").exists());
+        assert!(tmp.path().join(".claude/agents/reviewer.md").exists());
+    }
+
+    #[test]
+    fn test_sync_commands_reports_unsupported_agents() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/commands")).unwrap();
+        let review = tmp.path().join(".agents/commands/review.md");
+        fs::write(
+            &review,
+            "---\ndescription: Review $ARGUMENTS\n---\nReview it.\n",
+        )
+        .unwrap();
+        let config = Config::parse("[source]\ncommands = true\n").unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        let agents: Vec<&str> = result
+            .commands_generated
+            .iter()
+            .map(|e| e.agent.as_str())
+            .collect();
+        assert_eq!(agents, vec!["claude", "pi", "opencode"]);
+        assert_eq!(
+            result.commands_unsupported,
+            vec![UnsupportedEntry {
+                name: "review.md".to_string(),
+                agent: AgentName::Codex,
+                reason: "no directory at this level".to_string(),
+            }]
+        );
+        assert!(tmp.path().join(".claude/commands/review.md").is_file());
+        assert!(tmp.path().join(".opencode/command/review.md").is_file());
+
+        fs::write(
+            &review,
+            "---\ndescription: Review\nmodel: opus\n---\nReview it.\n",
+        )
+        .unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(result.commands_unsupported.contains(&UnsupportedEntry {
+            name: "review.md".to_string(),
+            agent: AgentName::Pi,
+            reason: "`model` has no equivalent".to_string(),
+        }));
+        assert_eq!(
+            result.commands_removed,
+            vec![entry("review.md", AgentName::Pi)]
+        );
+        assert!(!tmp.path().join(".pi/prompts/review.md").exists());
+    }
+
+    #[test]
+    fn test_sync_prunes_commands_after_opt_out_or_delete() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/commands")).unwrap();
+        let review = tmp.path().join(".agents/commands/review.md");
+        let lint = tmp.path().join(".agents/commands/lint.md");
+        fs::write(&review, "Review.\n").unwrap();
+        fs::write(&lint, "Lint.\n").unwrap();
+        fs::create_dir_all(tmp.path().join(".claude/commands")).unwrap();
+        fs::write(tmp.path().join(".claude/commands/mine.md"), "# Mine\n").unwrap();
+        let config = Config::parse("[source]\ncommands = true\n").unwrap();
+        run(&config, tmp.path(), &SyncOptions::default());
+        assert!(tmp.path().join(".claude/commands/lint.md").is_file());
+
+        fs::remove_file(&lint).unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(
+            result
+                .commands_removed
+                .contains(&entry("lint.md", AgentName::Claude))
+        );
+        assert!(!tmp.path().join(".claude/commands/lint.md").exists());
+        assert!(tmp.path().join(".claude/commands/review.md").is_file());
+
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+        assert!(
+            result
+                .commands_removed
+                .contains(&entry("review.md", AgentName::Claude))
+        );
+        assert!(!tmp.path().join(".claude/commands/review.md").exists());
+        assert!(!tmp.path().join(".opencode/command/review.md").exists());
+        // Hand-written files are never pruned
+        assert!(tmp.path().join(".claude/commands/mine.md").is_file());
+    }
+
+    #[test]
+    fn test_sync_commands_are_opt_in() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/commands")).unwrap();
+        fs::write(tmp.path().join(".agents/commands/review.md"), "Review.\n").unwrap();
+
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+        assert!(result.commands_generated.is_empty());
+        assert!(!tmp.path().join(".claude/commands").exists());
+    }
+
+    const MCP: &str = "[servers.github]\ncommand = \"npx\"\nargs = [\"gh\"]\n";
+
+    #[test]
+    fn test_sync_mcp_merges_into_agent_configs() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(tmp.path().join(".agents/mcp.toml"), MCP).unwrap();
+        fs::write(
+            tmp.path().join(".mcp.json"),
+            "{\n  \"mcpServers\": {\n    \"mine\": {\n      \"command\": \"x\"\n    }\n  }\n}\n",
+        )
+        .unwrap();
+        let config = Config::default();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        let agents: Vec<&str> = result
+            .mcp_updated
+            .iter()
+            .map(|e| e.agent.as_str())
+            .collect();
+        assert_eq!(agents, vec!["claude", "codex", "opencode"]);
+        let claude = fs::read_to_string(tmp.path().join(".mcp.json")).unwrap();
+        assert!(claude.contains("\"mine\"") && claude.contains("\"github\""));
+        assert!(
+            fs::read_to_string(tmp.path().join(".codex/config.toml"))
+                .unwrap()
+                .contains("[mcp_servers.github]")
+        );
+        assert!(
+            fs::read_to_string(tmp.path().join("opencode.json"))
+                .unwrap()
+                .contains("\"type\": \"local\"")
+        );
+
+        let again = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(again.mcp_updated.is_empty());
+
+        fs::write(
+            tmp.path().join(".agents/mcp.toml"),
+            format!("{MCP}agents = [\"codex\"]\n"),
+        )
+        .unwrap();
+        let limited = run(&config, tmp.path(), &SyncOptions::default());
+        assert_eq!(
+            limited.mcp_removed,
+            vec![
+                entry("github", AgentName::Claude),
+                entry("github", AgentName::Opencode),
+            ]
+        );
+        let claude = fs::read_to_string(tmp.path().join(".mcp.json")).unwrap();
+        assert!(claude.contains("\"mine\"") && !claude.contains("\"github\""));
+    }
+
+    #[test]
+    fn test_sync_mcp_only_touches_servers_it_wrote() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(tmp.path().join(".agents/mcp.toml"), MCP).unwrap();
+        let hand_written = "{\n  \"mcpServers\": {\n    \"github\": {\n      \"command\": \"mine\"\n    }\n  }\n}\n";
+        fs::write(tmp.path().join(".mcp.json"), hand_written).unwrap();
+        let config = Config::default();
+
+        // A hand-written server of the same name is reported, not overwritten
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::McpServerConflict { agent, .. } if *agent == AgentName::Claude
+        )));
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(".mcp.json")).unwrap(),
+            hand_written
+        );
+
+        // Limiting the server to codex removes only the entries hana wrote
+        fs::write(
+            tmp.path().join(".agents/mcp.toml"),
+            format!("{MCP}agents = [\"codex\"]\n"),
+        )
+        .unwrap();
+        let limited = run(&config, tmp.path(), &SyncOptions::default());
+        assert_eq!(
+            limited.mcp_removed,
+            vec![entry("github", AgentName::Opencode)]
+        );
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(".mcp.json")).unwrap(),
+            hand_written
+        );
+
+        // Deleting the server from mcp.toml removes it from the files hana wrote it to
+        fs::write(tmp.path().join(".agents/mcp.toml"), "").unwrap();
+        let deleted = run(&config, tmp.path(), &SyncOptions::default());
+        assert_eq!(deleted.mcp_removed, vec![entry("github", AgentName::Codex)]);
+        assert!(
+            !fs::read_to_string(tmp.path().join(".codex/config.toml"))
+                .unwrap()
+                .contains("github")
+        );
+        assert!(!tmp.path().join(".agents/.hanamcp").exists());
+    }
+
+    #[test]
+    fn test_sync_mcp_skips_unused_targets() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(tmp.path().join(".agents/mcp.toml"), MCP).unwrap();
+        run(&Config::default(), tmp.path(), &SyncOptions::default());
+        assert!(tmp.path().join(".codex/config.toml").is_file());
+
+        let config =
+            Config::parse("[target.codex]\nskills = false\ninstructions = false\n").unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert_eq!(result.mcp_removed, vec![entry("github", AgentName::Codex)]);
+
+        fs::remove_file(tmp.path().join(".codex/config.toml")).unwrap();
+        run(&config, tmp.path(), &SyncOptions::default());
+        assert!(!tmp.path().join(".codex/config.toml").exists());
+    }
+
+    #[test]
+    fn test_sync_mcp_env_refs_resolved_only_where_needed() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(
+            tmp.path().join(".agents/mcp.toml"),
+            "[servers.github]\ncommand = \"npx\"\nenv = { TOKEN = \"${env:HANA_SYNC_TOKEN}\" }\n",
+        )
+        .unwrap();
+        let config = Config::default();
+
+        let missing = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(missing.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::EnvUnresolved { agent, name, .. }
+                if *agent == AgentName::Codex && name == "HANA_SYNC_TOKEN"
+        )));
+        assert!(!tmp.path().join(".codex/config.toml").exists());
+
+        fs::write(tmp.path().join(".agents/.env"), "HANA_SYNC_TOKEN=s3cret\n").unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(result.warnings.is_empty());
+        assert!(
+            fs::read_to_string(tmp.path().join(".codex/config.toml"))
+                .unwrap()
+                .contains("TOKEN = \"s3cret\"")
+        );
+        let claude = fs::read_to_string(tmp.path().join(".mcp.json")).unwrap();
+        assert!(claude.contains("${HANA_SYNC_TOKEN}") && !claude.contains("s3cret"));
+        assert!(!format!("{result:?}").contains("s3cret"));
+    }
+
+    #[test]
+    fn test_sync_mcp_keeps_resolved_secrets_out_of_git() {
+        let tmp = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .status()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::create_dir_all(tmp.path().join(".codex")).unwrap();
+        fs::write(
+            tmp.path().join(".agents/mcp.toml"),
+            "[servers.github]\ncommand = \"npx\"\nenv = { TOKEN = \"${env:HANA_GIT_TOKEN}\" }\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join(".agents/.env"), "HANA_GIT_TOKEN=s3cret\n").unwrap();
+        fs::write(tmp.path().join(".codex/config.toml"), "model = \"o3\"\n").unwrap();
+        git(&["add", ".codex/config.toml"]);
+
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::SecretInTrackedFile { agent, .. } if *agent == AgentName::Codex
+        )));
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(".codex/config.toml")).unwrap(),
+            "model = \"o3\"\n"
+        );
+        let gitignore = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
+        assert!(gitignore.contains("/.agents/.env\n"));
+        assert!(gitignore.contains("/.codex/config.toml\n"));
+
+        git(&["rm", "-q", "--cached", ".codex/config.toml"]);
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+        assert!(result.warnings.is_empty());
+        assert!(
+            fs::read_to_string(tmp.path().join(".codex/config.toml"))
+                .unwrap()
+                .contains("s3cret")
+        );
+    }
+
+    #[test]
+    fn test_sync_mcp_leaves_invalid_config_alone() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents")).unwrap();
+        fs::write(tmp.path().join(".agents/mcp.toml"), MCP).unwrap();
+        fs::write(tmp.path().join(".mcp.json"), "{ broken").unwrap();
+
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::McpConfigInvalid { file, .. } if file == ".mcp.json"
+        )));
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(".mcp.json")).unwrap(),
+            "{ broken"
+        );
+    }
+
+    const PERSONAS: &str = "[[mapping]]\nname = \"personas\"\nsource = \".agents/agents\"\n\n[mapping.dest]\nclaude = \".claude/agents\"\nopencode = \".opencode/agent\"\n";
+
+    #[test]
+    fn test_sync_dir_mapping_collects_broadcasts_and_cleans() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".claude/agents")).unwrap();
+        fs::write(tmp.path().join(".claude/agents/reviewer.md"), "# Reviewer").unwrap();
+        symlink(
+            tmp.path().join(".agents/agents/gone.md"),
+            tmp.path().join(".claude/agents/gone.md"),
+        )
+        .unwrap();
+        let config = Config::parse(PERSONAS).unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert_eq!(
+            result.mappings_collected,
+            vec![entry("personas/reviewer.md", AgentName::Claude)]
+        );
+        assert_eq!(
+            result.mappings_linked,
+            vec![entry("personas/reviewer.md", AgentName::Opencode)]
+        );
+        assert!(tmp.path().join(".agents/agents/reviewer.md").is_file());
+        assert!(tmp.path().join(".claude/agents/reviewer.md").is_symlink());
+        assert!(tmp.path().join(".opencode/agent/reviewer.md").is_symlink());
+        assert!(!tmp.path().join(".claude/agents/gone.md").is_symlink());
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_sync_dir_mapping_collect_never() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".claude/agents")).unwrap();
+        fs::write(tmp.path().join(".claude/agents/local.md"), "# Local").unwrap();
+        let config =
+            Config::parse(&PERSONAS.replace("source = ", "collect = \"never\"\nsource = "))
+                .unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert!(result.mappings_collected.is_empty());
+        assert!(!tmp.path().join(".claude/agents/local.md").is_symlink());
+        assert!(!tmp.path().join(".opencode/agent/local.md").exists());
+    }
+
+    #[test]
+    fn test_sync_dir_mapping_keeps_foreign_broken_links() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".claude/agents")).unwrap();
+        let foreign = tmp.path().join(".claude/agents/mine.md");
+        symlink(tmp.path().join("elsewhere/mine.md"), &foreign).unwrap();
+        let config = Config::parse(PERSONAS).unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert!(foreign.is_symlink());
+        assert!(!result.cleaned.contains(&foreign));
+    }
+
+    #[test]
+    fn test_sync_dir_mapping_skips_unused_targets() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/agents")).unwrap();
+        fs::write(tmp.path().join(".agents/agents/reviewer.md"), "# Reviewer").unwrap();
+        let config = Config::parse(&format!(
+            "{PERSONAS}\n[target.opencode]\nskills = false\ninstructions = false\n"
+        ))
+        .unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert!(tmp.path().join(".claude/agents/reviewer.md").is_symlink());
+        assert!(!tmp.path().join(".opencode/agent").exists());
+        assert!(
+            result
+                .mappings_linked
+                .iter()
+                .all(|e| e.agent == AgentName::Claude)
+        );
+    }
+
+    #[test]
+    fn test_sync_file_mapping_refuses_to_pick_between_diverged_files() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("claude.md"), "# A").unwrap();
+        fs::write(tmp.path().join("pi.md"), "# B").unwrap();
+        let config = Config::parse(
+            "[[mapping]]\nname = \"style\"\nsource = \"style.md\"\nkind = \"file\"\ndest = { claude = \"claude.md\", pi = \"pi.md\" }\n",
+        )
+        .unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(!tmp.path().join("style.md").exists());
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::MappingConflict { agents, .. } if agents == &[AgentName::Claude, AgentName::Pi]
+        )));
+
+        fs::write(tmp.path().join("pi.md"), "# A").unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert_eq!(
+            result.mappings_collected,
+            vec![entry("style", AgentName::Claude)]
+        );
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("style.md")).unwrap(),
+            "# A"
+        );
+        assert!(tmp.path().join("claude.md").is_symlink());
+        assert!(tmp.path().join("pi.md").is_symlink());
+    }
+
+    #[test]
+    fn test_sync_no_collect_when_agents_md_exists() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "# Source").unwrap();
+        fs::write(tmp.path().join("CLAUDE.md"), "# Claude").unwrap();
+
+        let opts = SyncOptions {
+            force: true,
+            ..Default::default()
+        };
+        let result = run(&Config::default(), tmp.path(), &opts);
+
+        assert!(result.instructions_collected.is_none());
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("AGENTS.md")).unwrap(),
+            "# Source"
+        );
+    }
+
+    #[test]
+    fn test_sync_respects_skill_targets() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let hooks = tmp.path().join(".agents/skills/claude-hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(
+            hooks.join("SKILL.md"),
+            "---\nname: claude-hooks\nhana:\n  targets: [claude]\n---\n",
+        )
+        .unwrap();
+
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+
+        assert!(tmp.path().join(".claude/skills/claude-hooks").is_symlink());
+        assert!(!tmp.path().join(".opencode/skills/claude-hooks").exists());
+        assert!(tmp.path().join(".opencode/skills/my-skill").is_symlink());
+        assert!(
+            !result
+                .skills_linked
+                .iter()
+                .any(|s| s.skill == "claude-hooks" && s.agent == AgentName::Opencode)
+        );
+    }
+
+    #[test]
+    fn test_sync_warns_on_unknown_frontmatter_agent() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let hooks = tmp.path().join(".agents/skills/claude-hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(
+            hooks.join("SKILL.md"),
+            "---\nname: claude-hooks\nhana:\n  targets: [claud]\n---\n",
+        )
+        .unwrap();
+
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::UnknownSkillAgent { skill, agent } if skill == "claude-hooks" && agent == "claud"
+        )));
+        assert!(!tmp.path().join(".claude/skills/claude-hooks").exists());
+    }
+
+    #[test]
+    fn test_sync_removes_link_for_newly_excluded_agent() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        run(&Config::default(), tmp.path(), &SyncOptions::default());
+        assert!(tmp.path().join(".opencode/skills/my-skill").is_symlink());
+
+        let config = Config::parse("[skill.my-skill]\nexclude = [\"opencode\"]\n").unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert!(!tmp.path().join(".opencode/skills/my-skill").exists());
+        assert!(tmp.path().join(".claude/skills/my-skill").is_symlink());
+        assert_eq!(
+            result.skills_unlinked,
+            vec![LinkedSkill {
+                skill: "my-skill".to_string(),
+                agent: AgentName::Opencode,
+                path: tmp.path().join(".opencode/skills/my-skill"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_sync_namespaced_skills_are_flattened() {
+        let tmp = TempDir::new().unwrap();
+        let review = tmp.path().join(".agents/skills/frontend/react-review");
+        fs::create_dir_all(&review).unwrap();
+        fs::write(review.join("SKILL.md"), "# Review").unwrap();
+        let claude_new = tmp.path().join(".claude/skills/backend--db-migrate");
+        fs::create_dir_all(&claude_new).unwrap();
+        fs::write(claude_new.join("SKILL.md"), "# Migrate").unwrap();
+
+        let config = Config::parse("[source]\nnamespaces = true\n").unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        let link = tmp.path().join(".claude/skills/frontend--react-review");
+        assert!(link.is_symlink());
+        assert_eq!(
+            fs::canonicalize(&link).unwrap(),
+            fs::canonicalize(&review).unwrap()
+        );
+        assert!(
+            tmp.path()
+                .join(".agents/skills/backend/db-migrate/SKILL.md")
+                .is_file()
+        );
+        assert!(
+            tmp.path()
+                .join(".opencode/skills/backend--db-migrate")
+                .is_symlink()
+        );
+        assert!(
+            result
+                .skills_collected
+                .iter()
+                .any(|s| s.skill == "backend/db-migrate" && s.agent == AgentName::Claude)
+        );
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_sync_prune_foreign_symlinks() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let plugin = tmp.path().join("plugins/lint");
+        fs::create_dir_all(&plugin).unwrap();
+        let claude_dir = tmp.path().join(".claude/skills");
+        fs::create_dir_all(&claude_dir).unwrap();
+        symlink(&plugin, claude_dir.join("lint")).unwrap();
+        symlink(&plugin, claude_dir.join("my-skill")).unwrap();
+
+        // Without the flag, foreign links are left alone (same-named ones are taken over)
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+        assert!(claude_dir.join("lint").is_symlink());
+        assert!(result.pruned.is_empty());
+        assert_eq!(
+            fs::canonicalize(claude_dir.join("my-skill")).unwrap(),
+            fs::canonicalize(tmp.path().join(".agents/skills/my-skill")).unwrap()
+        );
+
+        let opts = SyncOptions {
+            prune_foreign: true,
+            ..Default::default()
+        };
+        let result = run(&Config::default(), tmp.path(), &opts);
+        assert!(!claude_dir.join("lint").exists());
+        assert!(plugin.is_dir());
+        assert_eq!(result.pruned, vec![claude_dir.join("lint")]);
+        assert!(claude_dir.join("my-skill").is_symlink());
+    }
+
+    #[test]
+    fn test_sync_generates_instructions_with_fragments() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        fs::write(
+            tmp.path().join("AGENTS.md"),
+            "# Shared\n<!-- hana:only claude -->\nClaude block.\n<!-- hana:end -->\n",
+        )
+        .unwrap();
+        fs::create_dir_all(tmp.path().join(".agents/instructions")).unwrap();
+        fs::write(
+            tmp.path().join(".agents/instructions/claude.md"),
+            "Claude fragment.\n",
+        )
+        .unwrap();
+        symlink("AGENTS.md", tmp.path().join("CLAUDE.md")).unwrap();
+
+        let config = Config::parse("[source]\ninstruction_mode = \"generate\"\n").unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert_eq!(result.instructions_generated, vec![AgentName::Claude]);
+        let claude_md = tmp.path().join("CLAUDE.md");
+        assert!(!claude_md.is_symlink());
+        let content = fs::read_to_string(&claude_md).unwrap();
+        assert!(content.contains("Claude block."));
+        assert!(content.ends_with("Claude fragment.\n"));
+        assert!(!content.contains("hana:only"));
+
+        let again = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(again.instructions_generated.is_empty());
+        assert!(again.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_sync_detects_reverse_edit_of_generated_file() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        let config = Config::parse("[source]\ninstruction_mode = \"generate\"\n").unwrap();
+        run(&config, tmp.path(), &SyncOptions::default());
+
+        let claude_md = tmp.path().join("CLAUDE.md");
+        let edited = format!("{}Hand edit.\n", fs::read_to_string(&claude_md).unwrap());
+        fs::write(&claude_md, &edited).unwrap();
+        fs::write(tmp.path().join("AGENTS.md"), "# Instructions v2").unwrap();
+
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(result.instructions_generated.is_empty());
+        assert!(matches!(
+            result.warnings.as_slice(),
+            [SyncWarning::GeneratedEdited { file }] if file == "CLAUDE.md"
+        ));
+        assert_eq!(fs::read_to_string(&claude_md).unwrap(), edited);
+
+        let forced = run(
+            &config,
+            tmp.path(),
+            &SyncOptions {
+                force: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(forced.instructions_generated, vec![AgentName::Claude]);
+        assert!(fs::read_to_string(&claude_md).unwrap().contains("v2"));
+    }
+
+    #[test]
+    fn test_sync_expands_includes_for_agents_without_imports() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        fs::write(
+            tmp.path().join("AGENTS.md"),
+            "# Base\n@docs/rules.md\n<!-- hana:include docs/missing.md -->\n",
+        )
+        .unwrap();
+        fs::create_dir_all(tmp.path().join("docs")).unwrap();
+        fs::write(tmp.path().join("docs/rules.md"), "Rule.\n").unwrap();
+
+        let config = Config::parse(
+            "[source]\nexpand_includes = true\n\n[target.opencode]\ninstruction_path = \"OPENCODE.md\"\n",
+        )
+        .unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        // Claude resolves `@path` itself, but not the comment form, so both are generated
+        let mut generated = result.instructions_generated.clone();
+        generated.sort();
+        assert_eq!(generated, vec![AgentName::Claude, AgentName::Opencode]);
+        let claude = fs::read_to_string(tmp.path().join("CLAUDE.md")).unwrap();
+        assert!(claude.contains("# Base\n@docs/rules.md\n"));
+        let opencode = fs::read_to_string(tmp.path().join("OPENCODE.md")).unwrap();
+        assert!(opencode.contains("# Base\nRule.\n"));
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::IncludeMissing { include, .. } if include == "docs/missing.md"
+        )));
+    }
+
+    #[test]
+    fn test_sync_keeps_symlink_for_agents_with_imports() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        fs::write(tmp.path().join("AGENTS.md"), "# Base\n@docs/rules.md\n").unwrap();
+        fs::create_dir_all(tmp.path().join("docs")).unwrap();
+        fs::write(tmp.path().join("docs/rules.md"), "Rule.\n").unwrap();
+
+        let config = Config::parse(
+            "[source]\nexpand_includes = true\n\n[target.opencode]\ninstruction_path = \"OPENCODE.md\"\n",
+        )
+        .unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+
+        assert!(tmp.path().join("CLAUDE.md").is_symlink());
+        assert!(!tmp.path().join("OPENCODE.md").is_symlink());
+        assert_eq!(result.instructions_generated, vec![AgentName::Opencode]);
+    }
+
+    #[test]
+    fn test_sync_no_instruction_when_nothing_exists() {
+        let tmp = TempDir::new().unwrap();
+
+        let result = run(&Config::default(), tmp.path(), &SyncOptions::default());
+
+        assert!(result.instructions_collected.is_none());
+        assert!(result.instructions_linked.is_empty());
+    }
+
+    #[test]
+    fn test_sync_gitignore_block_follows_policy() {
+        let tmp = TempDir::new().unwrap();
+        setup_source(tmp.path());
+        fs::write(tmp.path().join(".gitignore"), "target/\n").unwrap();
+
+        let config = Config::parse("[git]\nignore_links = true\n").unwrap();
+        let result = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(result.gitignore_updated);
+        let gitignore = fs::read_to_string(tmp.path().join(".gitignore")).unwrap();
+        assert!(gitignore.starts_with("target/\n"));
+        assert!(gitignore.contains("/.claude/skills/my-skill\n"));
+        assert!(gitignore.contains("/CLAUDE.md\n"));
+        assert!(!gitignore.contains("/.agents/skills"));
+
+        let again = run(&config, tmp.path(), &SyncOptions::default());
+        assert!(!again.gitignore_updated);
+
+        let off = run(&Config::default(), tmp.path(), &SyncOptions::default());
+        assert!(off.gitignore_updated);
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(".gitignore")).unwrap(),
+            "target/\n"
+        );
     }
 }
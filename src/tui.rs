@@ -21,7 +21,7 @@ pub fn section(title: &str, rows: &[String]) -> String {
     out.push_str(&format!(
         "{}{}{}{}\n",
         TL.dimmed(),
-        H.repeat(1).dimmed(),
+        H.dimmed(),
         title_display.bold(),
         format!("{}{}", H.repeat(remaining + 1), TR).dimmed(),
     ));
@@ -98,6 +98,23 @@ pub fn badge_broken(text: &str) -> String {
     format!("{} {}", "💔".red(), text)
 }
 
+// ── Sizes ──
+
+/// Human-readable byte size (`512 B`, `1.5 KB`, `2.0 MB`).
+pub fn size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
 // ── Table formatting ──
 
 /// Format rows as an aligned table. Each row is a vec of columns.
@@ -222,6 +239,13 @@ mod tests {
         assert!(result[1].contains("much longer"));
     }
 
+    #[test]
+    fn test_size() {
+        assert_eq!(size(512), "512 B");
+        assert_eq!(size(1536), "1.5 KB");
+        assert_eq!(size(2 * 1024 * 1024), "2.0 MB");
+    }
+
     #[test]
     fn test_section_output() {
         let out = section("Test", &["line 1".to_string(), "line 2".to_string()]);
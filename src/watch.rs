@@ -0,0 +1,380 @@
+//! `hana watch`: rerun the skill phases of sync when agent directories change.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, channel};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use crate::config::{Config, TargetFeature};
+use crate::helper::resolve_target_destinations::resolve_target_destinations;
+use crate::sync::{self, SyncOk, SyncOptions, SyncWarning};
+
+// --- Options ---
+
+/// Default quiet period, in milliseconds
+pub const DEFAULT_DEBOUNCE_MS: u64 = 500;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct WatchOptions {
+    pub global: bool,
+    /// Quiet period after the last event before a batch is synced
+    pub debounce: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            global: false,
+            debounce: Duration::from_millis(DEFAULT_DEBOUNCE_MS),
+        }
+    }
+}
+
+// --- Error ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WatchError {
+    /// The platform watcher could not be created
+    Init { detail: String },
+    /// A directory could not be watched
+    Watch { path: PathBuf, detail: String },
+}
+
+impl std::fmt::Display for WatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Init { detail } => write!(f, "cannot start file watcher: {detail}"),
+            Self::Watch { path, detail } => {
+                write!(f, "cannot watch {}: {detail}", path.display())
+            }
+        }
+    }
+}
+
+// --- pub fn run ---
+
+/// Watch the source and every agent skills directory, syncing the touched skills after each
+/// burst of changes. `on_ready` receives the watched directories, `on_sync` every batch result.
+/// Runs until the watcher shuts down.
+pub fn run(
+    config: &Config,
+    base_dir: &Path,
+    opts: &WatchOptions,
+    on_ready: impl FnOnce(&[PathBuf]),
+    mut on_sync: impl FnMut(&[PathBuf], &SyncOk),
+) -> Result<(), WatchError> {
+    let sync_opts = SyncOptions {
+        global: opts.global,
+        ..Default::default()
+    };
+    let source_dir = config.resolve_source_skills_path(base_dir, opts.global);
+    let (dirs, dir_warnings) = watch_dirs(config, base_dir, opts.global);
+
+    // Pick up anything created while hana was not running
+    let mut initial = sync::run_skills(config, base_dir, &sync_opts);
+    initial.warnings.extend(dir_warnings);
+    on_sync(&[], &initial);
+
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| WatchError::Init {
+        detail: e.to_string(),
+    })?;
+    watch_all(&mut watcher, &dirs, &source_dir)?;
+    on_ready(&dirs);
+
+    while let Some((paths, errors)) = next_batch(&rx, opts.debounce) {
+        let relevant: Vec<PathBuf> = paths
+            .iter()
+            .filter(|path| is_relevant(path, &source_dir))
+            .cloned()
+            .collect();
+        // A failed, removed or recreated watched directory loses its watch, and events may
+        // have been missed: watch everything again and resync in full
+        if !errors.is_empty() || paths.iter().any(|path| dirs.contains(path)) {
+            let (_, dir_warnings) = watch_dirs(config, base_dir, opts.global);
+            watch_all(&mut watcher, &dirs, &source_dir)?;
+            let mut result = sync::run_skills(config, base_dir, &sync_opts);
+            result
+                .warnings
+                .extend(errors.into_iter().map(|detail| SyncWarning::IoFailed {
+                    operation: "watch for changes".to_string(),
+                    detail,
+                }));
+            result.warnings.extend(dir_warnings);
+            on_sync(&relevant, &result);
+            continue;
+        }
+        if relevant.is_empty() {
+            continue;
+        }
+        let changed = changed_skills(config, &relevant, &source_dir, &dirs);
+        let result = sync::run_changed_skills(config, base_dir, &sync_opts, &changed);
+        on_sync(&relevant, &result);
+    }
+    Ok(())
+}
+
+/// (Re)watch every existing directory: edits happen inside skills in the source, agent
+/// directories only gain new entries.
+fn watch_all(
+    watcher: &mut impl Watcher,
+    dirs: &[PathBuf],
+    source_dir: &Path,
+) -> Result<(), WatchError> {
+    for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+        let mode = if dir == source_dir {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        // Drop a stale watch first; a directory that was never watched is not an error
+        let _ = watcher.unwatch(dir);
+        watcher.watch(dir, mode).map_err(|e| WatchError::Watch {
+            path: dir.clone(),
+            detail: e.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Source skills directory plus every agent skills directory, created if missing.
+fn watch_dirs(config: &Config, base_dir: &Path, global: bool) -> (Vec<PathBuf>, Vec<SyncWarning>) {
+    let mut dirs: Vec<PathBuf> =
+        resolve_target_destinations(config, base_dir, global, TargetFeature::Skills)
+            .into_values()
+            .chain(Some(config.resolve_source_skills_path(base_dir, global)))
+            .collect();
+    dirs.sort();
+    dirs.dedup();
+    let warnings = dirs
+        .iter()
+        .filter_map(|dir| {
+            fs::create_dir_all(dir)
+                .err()
+                .map(|e| SyncWarning::IoFailed {
+                    operation: format!("create watched directory ({})", dir.display()),
+                    detail: e.to_string(),
+                })
+        })
+        .collect();
+    (dirs, warnings)
+}
+
+/// Skill ids touched by a batch: the entry under an agent directory, or the skill (and, with
+/// namespaces, `<category>/<skill>`) under the source.
+fn changed_skills(
+    config: &Config,
+    paths: &[PathBuf],
+    source_dir: &Path,
+    dirs: &[PathBuf],
+) -> BTreeSet<String> {
+    let mut changed = BTreeSet::new();
+    for path in paths {
+        let root = dirs
+            .iter()
+            .filter(|dir| path.starts_with(dir))
+            .max_by_key(|dir| dir.components().count());
+        let Some(root) = root else {
+            continue;
+        };
+        let mut parts = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .iter()
+            .map(|p| p.to_string_lossy().to_string());
+        let Some(first) = parts.next() else {
+            continue;
+        };
+        if root == source_dir {
+            if let Some(second) = parts.next().filter(|_| config.source.namespaces) {
+                changed.insert(format!("{first}/{second}"));
+            }
+            changed.insert(first);
+        } else {
+            changed.insert(config.skill_id_from_link_name(&first));
+        }
+    }
+    changed
+}
+
+/// Block for the first event, then keep collecting until `window` passes without one.
+/// Returns the changed paths and any watcher errors, or `None` once the watcher is gone.
+fn next_batch(
+    rx: &Receiver<notify::Result<Event>>,
+    window: Duration,
+) -> Option<(Vec<PathBuf>, Vec<String>)> {
+    let mut paths = Vec::new();
+    let mut errors = Vec::new();
+    let first = rx.recv().ok()?;
+    push_event(&mut paths, &mut errors, first);
+
+    while let Ok(event) = rx.recv_timeout(window) {
+        push_event(&mut paths, &mut errors, event);
+    }
+    paths.sort();
+    paths.dedup();
+    Some((paths, errors))
+}
+
+/// Reads (including sync's own directory scans) are not changes.
+fn push_event(paths: &mut Vec<PathBuf>, errors: &mut Vec<String>, event: notify::Result<Event>) {
+    match event {
+        Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+        Ok(event) => paths.extend(event.paths),
+        Err(e) => errors.push(e.to_string()),
+    }
+}
+
+/// Our own links landing in agent directories are not worth another sync round.
+fn is_relevant(path: &Path, source_dir: &Path) -> bool {
+    if !path.is_symlink() {
+        return true;
+    }
+    match (fs::canonicalize(path), fs::canonicalize(source_dir)) {
+        (Ok(resolved), Ok(source)) => !resolved.starts_with(source),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use std::thread;
+    use tempfile::TempDir;
+
+    fn event(path: &str) -> notify::Result<Event> {
+        Ok(Event::new(notify::EventKind::Any).add_path(PathBuf::from(path)))
+    }
+
+    #[test]
+    fn test_next_batch_debounces_burst() {
+        let (tx, rx) = channel();
+        tx.send(event("/a")).unwrap();
+        tx.send(event("/b")).unwrap();
+        tx.send(event("/a")).unwrap();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            tx.send(event("/late")).unwrap();
+        });
+
+        let (batch, errors) = next_batch(&rx, Duration::from_millis(50)).unwrap();
+        assert_eq!(batch, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+        assert!(errors.is_empty());
+
+        handle.join().unwrap();
+        let (batch, _) = next_batch(&rx, Duration::from_millis(50)).unwrap();
+        assert_eq!(batch, vec![PathBuf::from("/late")]);
+
+        let (tx, rx) = channel();
+        let access = Event::new(EventKind::Access(notify::event::AccessKind::Any));
+        tx.send(Ok(access.add_path(PathBuf::from("/read"))))
+            .unwrap();
+        drop(tx);
+        assert_eq!(
+            next_batch(&rx, Duration::from_millis(50)),
+            Some((vec![], vec![]))
+        );
+        assert!(next_batch(&rx, Duration::from_millis(50)).is_none());
+    }
+
+    #[test]
+    fn test_next_batch_keeps_watcher_errors() {
+        let (tx, rx) = channel();
+        tx.send(Err(notify::Error::generic("inotify watch limit reached")))
+            .unwrap();
+        tx.send(event("/a")).unwrap();
+        drop(tx);
+
+        let (batch, errors) = next_batch(&rx, Duration::from_millis(50)).unwrap();
+        assert_eq!(batch, vec![PathBuf::from("/a")]);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("watch limit"));
+    }
+
+    #[test]
+    fn test_watch_all_rewatches_recreated_source() {
+        let tmp = TempDir::new().unwrap();
+        let (dirs, _) = watch_dirs(&Config::default(), tmp.path(), false);
+        let source = tmp.path().join(".agents/skills");
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(tx).unwrap();
+        watch_all(&mut watcher, &dirs, &source).unwrap();
+
+        fs::remove_dir_all(&source).unwrap();
+        fs::create_dir_all(&source).unwrap();
+        watch_all(&mut watcher, &dirs, &source).unwrap();
+        while rx.recv_timeout(Duration::from_millis(100)).is_ok() {}
+
+        fs::create_dir_all(source.join("new-skill")).unwrap();
+        let (batch, errors) = next_batch(&rx, Duration::from_millis(100)).unwrap();
+        assert!(errors.is_empty());
+        assert!(batch.contains(&source.join("new-skill")));
+    }
+
+    #[test]
+    fn test_changed_skills_from_paths() {
+        let source = PathBuf::from("/p/.agents/skills");
+        let claude = PathBuf::from("/p/.claude/skills");
+        let dirs = vec![claude.clone(), source.clone()];
+        let paths = vec![
+            source.join("my-skill/scripts/run.sh"),
+            claude.join("new-skill"),
+            PathBuf::from("/elsewhere/file"),
+        ];
+
+        let changed = changed_skills(&Config::default(), &paths, &source, &dirs);
+        assert_eq!(
+            changed.into_iter().collect::<Vec<_>>(),
+            vec!["my-skill".to_string(), "new-skill".to_string()]
+        );
+
+        let config = Config::parse("[source]\nnamespaces = true\n").unwrap();
+        let paths = vec![source.join("frontend/react-review/SKILL.md")];
+        let changed = changed_skills(&config, &paths, &source, &dirs);
+        assert!(changed.contains("frontend/react-review"));
+    }
+
+    #[test]
+    fn test_managed_links_are_not_relevant() {
+        let tmp = TempDir::new().unwrap();
+        let source = tmp.path().join(".agents/skills");
+        fs::create_dir_all(source.join("my-skill")).unwrap();
+        let claude = tmp.path().join(".claude/skills");
+        fs::create_dir_all(claude.join("new-skill")).unwrap();
+        symlink(source.join("my-skill"), claude.join("my-skill")).unwrap();
+
+        assert!(!is_relevant(&claude.join("my-skill"), &source));
+        assert!(is_relevant(&claude.join("new-skill"), &source));
+        assert!(is_relevant(&claude.join("deleted"), &source));
+    }
+
+    #[test]
+    fn test_watch_dirs_include_source_and_targets() {
+        let tmp = TempDir::new().unwrap();
+        let (dirs, warnings) = watch_dirs(&Config::default(), tmp.path(), false);
+        assert!(warnings.is_empty());
+
+        assert!(dirs.contains(&tmp.path().join(".agents/skills")));
+        assert!(dirs.contains(&tmp.path().join(".claude/skills")));
+        assert!(dirs.contains(&tmp.path().join(".opencode/skills")));
+        assert!(dirs.iter().all(|d| d.is_dir()));
+    }
+
+    #[test]
+    fn test_watch_dirs_report_create_failures() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join(".claude"), "not a directory").unwrap();
+
+        let (_, warnings) = watch_dirs(&Config::default(), tmp.path(), false);
+        assert!(warnings.iter().any(|w| matches!(
+            w,
+            SyncWarning::IoFailed { operation, .. } if operation.contains(".claude")
+        )));
+    }
+}
@@ -0,0 +1,229 @@
+//! `hana workspace`: check or sync many repos at once.
+
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::config::Config;
+use crate::status::{self, StatusOk};
+use crate::sync::{self, SyncOptions};
+
+// --- Options ---
+
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct WorkspaceOptions {
+    /// Sync each repo before checking it; otherwise only check
+    pub sync: bool,
+    pub dry_run: bool,
+    pub force: bool,
+}
+
+// --- Ok ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct WorkspaceOk {
+    /// One report per repo, in `[workspace] repos` order
+    pub repos: Vec<RepoReport>,
+}
+
+impl WorkspaceOk {
+    /// Repos that failed or still have entries out of sync.
+    pub fn out_of_sync(&self) -> usize {
+        self.repos
+            .iter()
+            .filter(|repo| match &repo.outcome {
+                RepoOutcome::Checked { drift, .. } => drift.total() > 0,
+                RepoOutcome::Failed(_) => true,
+            })
+            .count()
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RepoReport {
+    pub path: PathBuf,
+    pub outcome: RepoOutcome,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RepoOutcome {
+    Checked {
+        /// Active `hana use` profile
+        profile: Option<String>,
+        /// Warnings from the sync run (always 0 when only checking)
+        warnings: usize,
+        /// What is still out of sync afterwards
+        drift: Drift,
+    },
+    /// hana.toml is missing or invalid
+    Failed(String),
+}
+
+/// Entries `hana sync` would still change, by section.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Drift {
+    pub skills: usize,
+    pub instructions: usize,
+    /// Files, subagents, commands, MCP servers and mappings
+    pub other: usize,
+}
+
+impl Drift {
+    pub fn total(self) -> usize {
+        self.skills + self.instructions + self.other
+    }
+
+    fn of(status: &StatusOk) -> Self {
+        let skills = status
+            .skills
+            .iter()
+            .flat_map(|skill| &skill.agents)
+            .filter(|cell| cell.state.is_problem())
+            .count();
+        let instructions = status
+            .instructions
+            .agents
+            .iter()
+            .filter(|cell| cell.state.is_problem())
+            .count();
+        Self {
+            skills,
+            instructions,
+            other: status.problem_count() - skills - instructions,
+        }
+    }
+}
+
+// --- Error ---
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WorkspaceError {
+    /// `[workspace] repos` is empty or matched nothing
+    NoRepos,
+}
+
+impl std::fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoRepos => write!(
+                f,
+                "no repos found. List repo roots or globs in [workspace] repos of ~/.agents/hana.toml"
+            ),
+        }
+    }
+}
+
+// --- pub fn run ---
+
+/// Sync and/or check every repo in `[workspace] repos` of the global config, one thread per repo.
+pub fn run(
+    config: &Config,
+    base_dir: &Path,
+    opts: &WorkspaceOptions,
+) -> Result<WorkspaceOk, WorkspaceError> {
+    let repos = config.resolve_workspace_repos(base_dir);
+    if repos.is_empty() {
+        return Err(WorkspaceError::NoRepos);
+    }
+
+    let reports = thread::scope(|scope| {
+        let handles: Vec<_> = repos
+            .iter()
+            .map(|repo| scope.spawn(|| run_repo(repo, opts)))
+            .collect();
+        handles
+            .into_iter()
+            .zip(&repos)
+            .map(|(handle, repo)| RepoReport {
+                path: repo.clone(),
+                outcome: handle
+                    .join()
+                    .unwrap_or_else(|_| RepoOutcome::Failed("panicked".to_string())),
+            })
+            .collect()
+    });
+
+    Ok(WorkspaceOk { repos: reports })
+}
+
+fn run_repo(repo: &Path, opts: &WorkspaceOptions) -> RepoOutcome {
+    let config = match Config::load_level(repo) {
+        Ok(config) => config,
+        Err(e) => return RepoOutcome::Failed(e.to_string()),
+    };
+
+    let warnings = if opts.sync {
+        let sync_opts = SyncOptions {
+            dry_run: opts.dry_run,
+            force: opts.force,
+            ..Default::default()
+        };
+        sync::run(&config, repo, &sync_opts).warnings.len()
+    } else {
+        0
+    };
+
+    RepoOutcome::Checked {
+        profile: config.active_profile.clone(),
+        warnings,
+        drift: Drift::of(&status::run(&config, repo, false)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Config) {
+        let tmp = TempDir::new().unwrap();
+        for repo in ["work/api", "work/web"] {
+            let root = tmp.path().join(repo);
+            fs::create_dir_all(root.join(".agents/skills/lint")).unwrap();
+            fs::write(root.join(".agents/skills/lint/SKILL.md"), "").unwrap();
+            fs::write(root.join(".agents/hana.toml"), "").unwrap();
+            fs::write(root.join("AGENTS.md"), "# Repo\n").unwrap();
+        }
+        let config = Config::parse("[workspace]\nrepos = [\"work/*\", \"work/gone\"]\n").unwrap();
+        (tmp, config)
+    }
+
+    #[test]
+    fn test_check_then_sync_every_repo() {
+        let (tmp, config) = setup();
+
+        let checked = run(&config, tmp.path(), &WorkspaceOptions::default()).unwrap();
+        assert_eq!(checked.repos.len(), 3);
+        assert_eq!(checked.out_of_sync(), 3);
+        assert!(matches!(
+            &checked.repos[0].outcome,
+            RepoOutcome::Checked { drift, .. } if drift.skills > 0 && drift.instructions > 0
+        ));
+        assert!(matches!(checked.repos[2].outcome, RepoOutcome::Failed(_)));
+
+        let synced = run(
+            &config,
+            tmp.path(),
+            &WorkspaceOptions {
+                sync: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(synced.out_of_sync(), 1);
+        assert!(tmp.path().join("work/web/.claude/skills/lint").is_symlink());
+    }
+
+    #[test]
+    fn test_no_repos_is_an_error() {
+        let tmp = TempDir::new().unwrap();
+        let result = run(&Config::default(), tmp.path(), &WorkspaceOptions::default());
+        assert!(matches!(result, Err(WorkspaceError::NoRepos)));
+    }
+}